@@ -2,12 +2,12 @@
 -- Direct Mail Attribution Platform Schema
 -- =================================================================
 
--- Clean slate: remove old tables that are being replaced.
--- In a real migration, you would use an ALTER TABLE script.
+-- Remove the tables `properties` replaced. This file runs on every start,
+-- so tables still in use are never dropped here; columns added to them
+-- later are listed in COLUMN_MIGRATIONS (src/db/connection.rs).
 PRAGMA foreign_keys = OFF;
 DROP TABLE IF EXISTS listing_observations;
 DROP TABLE IF EXISTS listings;
-DROP TABLE IF EXISTS properties;
 PRAGMA foreign_keys = ON;
 
 
@@ -16,16 +16,19 @@ PRAGMA foreign_keys = ON;
 -- ===============================
 -- This is the central table, representing a unique physical property.
 -- It holds the CURRENT state of the fields we track.
--- A property is uniquely identified by its address components.
+-- A property is uniquely identified by its normalized address key.
 CREATE TABLE IF NOT EXISTS properties (
   id INTEGER PRIMARY KEY,
 
-  -- Address fields (used for unique identification)
+  -- Address fields, as scraped (for display)
   address_line TEXT NOT NULL,
   city TEXT NOT NULL,
   postal_code TEXT NOT NULL,
   state_abbr TEXT, -- Can be null if not provided, but should be normalized
   county_name TEXT,
+  -- Normalized "LINE|CITY|ZIP" used for matching (see domain::address).
+  -- Filled on insert; rows from before it existed are re-keyed at startup.
+  address_key TEXT,
 
   -- Tracked fields (current state)
   status TEXT,
@@ -38,17 +41,34 @@ CREATE TABLE IF NOT EXISTS properties (
   is_foreclosure INTEGER,
   is_price_reduced INTEGER,
   is_coming_soon INTEGER,
+  beds INTEGER,
+  baths REAL,           -- half baths count as 0.5, e.g. 2.5
+  sqft INTEGER,
+  lot_sqft INTEGER,
+  property_type TEXT,   -- e.g. 'single_family', 'condos', 'land'
+  year_built INTEGER,
+  garage INTEGER,       -- garage spaces
+  stories INTEGER,
+  is_new_construction INTEGER,
+
+  -- Descriptive fields (kept current, not change-tracked)
+  subdivision TEXT,
+  latitude REAL,
+  longitude REAL,
+  county_fips INTEGER,
 
   -- Lifecycle
   first_seen_at DATETIME NOT NULL,
   last_seen_at DATETIME NOT NULL,
+  delisted_at DATETIME, -- set when a complete state sweep no longer finds it
 
   FOREIGN KEY (state_abbr) REFERENCES states(abbr),
-  UNIQUE (address_line, city, postal_code)
+  UNIQUE (address_key)
 );
 
 CREATE INDEX IF NOT EXISTS idx_properties_status ON properties(status);
 CREATE INDEX IF NOT EXISTS idx_properties_last_seen ON properties(last_seen_at);
+CREATE INDEX IF NOT EXISTS idx_properties_type_beds ON properties(property_type, beds);
 
 
 -- ===============================
@@ -70,8 +90,118 @@ CREATE TABLE IF NOT EXISTS property_history (
 
 CREATE INDEX IF NOT EXISTS idx_property_history_property_id ON property_history(property_id);
 CREATE INDEX IF NOT EXISTS idx_property_history_field_name ON property_history(field_name);
+CREATE INDEX IF NOT EXISTS idx_property_history_field_value ON property_history(field_name, current_value);
 CREATE INDEX IF NOT EXISTS idx_property_history_observed_at ON property_history(observed_at);
 
+-- Which sites reported each history row: the one whose scrape logged the
+-- change, plus any later site that reported the same value while it was
+-- current. Lets prices and statuses be cross-checked between sources.
+CREATE TABLE IF NOT EXISTS property_history_sources (
+  history_id INTEGER NOT NULL,
+  site TEXT NOT NULL,              -- e.g. 'realtor', 'zillow'
+  source_listing_id TEXT NOT NULL,
+  observed_at DATETIME NOT NULL,   -- first time this site reported the value
+  PRIMARY KEY (history_id, site),
+  FOREIGN KEY (history_id) REFERENCES property_history(id) ON DELETE CASCADE
+);
+
+-- Typed changes worked out when a listing is saved (new listing, price drop,
+-- went pending, ...), with the canonical status either side. The dashboard
+-- and exports read these rather than reinterpreting property_history.
+CREATE TABLE IF NOT EXISTS change_events (
+  id INTEGER PRIMARY KEY,
+  property_id INTEGER NOT NULL,
+  observed_at DATETIME NOT NULL,
+  event_type TEXT NOT NULL,      -- e.g. 'new_listing', 'price_drop', 'went_pending', 'delisted'
+  previous_status TEXT,          -- canonical status before; NULL for a new property
+  current_status TEXT NOT NULL,  -- canonical status after, e.g. 'Active', 'Pending', 'Off Market'
+  previous_price INTEGER,
+  current_price INTEGER,
+  site TEXT,                     -- site whose listing showed the change; NULL for delistings
+  FOREIGN KEY (property_id) REFERENCES properties(id) ON DELETE CASCADE
+);
+CREATE INDEX IF NOT EXISTS idx_change_events_property_id ON change_events(property_id);
+CREATE INDEX IF NOT EXISTS idx_change_events_observed_at ON change_events(observed_at);
+
+-- Each run of a property on the market, from listed to sold or delisted.
+-- A relist starts a new cycle. Rebuilt from property_history whenever the
+-- property changes.
+CREATE TABLE IF NOT EXISTS listing_cycles (
+  id INTEGER PRIMARY KEY,
+  property_id INTEGER NOT NULL,
+  cycle_number INTEGER NOT NULL,  -- 1 for the first listing we saw
+  listed_at DATETIME NOT NULL,
+  pending_at DATETIME,            -- first went under contract; NULL if unknown or never
+  ended_at DATETIME,              -- NULL while still on the market
+  outcome TEXT,                   -- 'sold' or 'delisted'; NULL while open
+  initial_price INTEGER,
+  last_price INTEGER,
+  price_cuts INTEGER NOT NULL DEFAULT 0,
+  days_on_market INTEGER,         -- NULL while open
+  days_to_pending INTEGER,
+  FOREIGN KEY (property_id) REFERENCES properties(id) ON DELETE CASCADE,
+  UNIQUE(property_id, cycle_number)
+);
+CREATE INDEX IF NOT EXISTS idx_listing_cycles_listed_at ON listing_cycles(listed_at);
+CREATE INDEX IF NOT EXISTS idx_listing_cycles_pending_at ON listing_cycles(pending_at);
+
+-- Daily market figures per area, worked out nightly from change_events,
+-- listing_cycles and properties. A state row has empty county_name and postal_code; a
+-- county row has an empty postal_code; a ZIP row has an empty county_name.
+CREATE TABLE IF NOT EXISTS market_stats (
+  id INTEGER PRIMARY KEY,
+  stat_date DATE NOT NULL,
+  state_abbr TEXT NOT NULL,
+  county_name TEXT NOT NULL DEFAULT '',
+  postal_code TEXT NOT NULL DEFAULT '',
+  active_inventory INTEGER NOT NULL,  -- 'Active' at the end of the day
+  new_listings INTEGER NOT NULL,      -- listing cycles begun that day
+  pendings INTEGER NOT NULL,          -- went under contract that day
+  solds INTEGER NOT NULL,             -- sold_date on that day
+  median_list_price INTEGER,          -- over active inventory
+  median_sold_price INTEGER,
+  median_days_on_market INTEGER,      -- over that day's sales we saw listed
+  price_cut_share REAL,               -- of active inventory, 0 to 1
+  sale_to_list_ratio REAL,            -- mean sold price / last list price
+  computed_at DATETIME NOT NULL,
+  UNIQUE(stat_date, state_abbr, county_name, postal_code)
+);
+-- ===============================
+-- Rental Listings
+-- ===============================
+-- Homes listed for rent, matched on the same normalized address key as
+-- properties but kept apart, so a monthly rent never reads as a list price.
+-- The same house can be in both tables at once.
+CREATE TABLE IF NOT EXISTS rental_listings (
+  id INTEGER PRIMARY KEY,
+  address_key TEXT NOT NULL UNIQUE,
+  address_line TEXT NOT NULL,
+  city TEXT NOT NULL,
+  postal_code TEXT NOT NULL,
+  state_abbr TEXT,
+  county_name TEXT,
+  source_name TEXT NOT NULL,
+  source_listing_id TEXT NOT NULL,  -- latest listing id seen for it
+  monthly_rent INTEGER,
+  beds INTEGER,
+  baths REAL,
+  sqft INTEGER,
+  property_type TEXT,
+  first_seen_at DATETIME NOT NULL,
+  last_seen_at DATETIME NOT NULL
+);
+
+-- Every change in asking rent, the first one included.
+CREATE TABLE IF NOT EXISTS rental_history (
+  id INTEGER PRIMARY KEY,
+  rental_id INTEGER NOT NULL,
+  observed_at DATETIME NOT NULL,
+  previous_rent INTEGER,   -- NULL when first seen
+  current_rent INTEGER,
+  FOREIGN KEY (rental_id) REFERENCES rental_listings(id) ON DELETE CASCADE
+);
+CREATE INDEX IF NOT EXISTS idx_rental_history_rental_id ON rental_history(rental_id);
+
 
 -- ===============================
 -- Property Sources Table (Source Data)
@@ -84,6 +214,7 @@ CREATE TABLE IF NOT EXISTS property_sources (
   property_id INTEGER NOT NULL,
   source_name TEXT NOT NULL,       -- e.g., 'realtor', 'zillow'
   source_listing_id TEXT NOT NULL, -- The ID from the original source
+  site TEXT NOT NULL DEFAULT 'realtor', -- the site it was scraped from: 'realtor' or 'zillow'
 
   first_seen_at DATETIME NOT NULL,
   last_seen_at DATETIME NOT NULL,
@@ -94,6 +225,80 @@ CREATE TABLE IF NOT EXISTS property_sources (
 
 CREATE INDEX IF NOT EXISTS idx_property_sources_property_id ON property_sources(property_id);
 
+-- Likely duplicate properties found by the duplicate scan, awaiting review.
+-- A pair is stored once (property_a_id < property_b_id); dismissed pairs
+-- stay so the scan doesn't raise them again.
+CREATE TABLE IF NOT EXISTS property_duplicate_candidates (
+  id INTEGER PRIMARY KEY,
+  property_a_id INTEGER NOT NULL,
+  property_b_id INTEGER NOT NULL,
+  reasons TEXT NOT NULL,          -- e.g. 'same street address; 4 m apart'
+  score REAL NOT NULL,            -- 0..1, higher is more likely the same parcel
+  detected_at INTEGER NOT NULL,
+  status TEXT NOT NULL DEFAULT 'open', -- 'open' or 'dismissed'
+  resolved_at INTEGER,
+  FOREIGN KEY (property_a_id) REFERENCES properties(id) ON DELETE CASCADE,
+  FOREIGN KEY (property_b_id) REFERENCES properties(id) ON DELETE CASCADE,
+  UNIQUE (property_a_id, property_b_id)
+);
+
+-- Audit log of property merges. No foreign keys: the merged property is gone
+-- by the time this row is written, so its identifying fields are copied here.
+CREATE TABLE IF NOT EXISTS property_merges (
+  id INTEGER PRIMARY KEY,
+  surviving_property_id INTEGER NOT NULL,
+  merged_property_id INTEGER NOT NULL,
+  merged_address TEXT NOT NULL,   -- display address of the merged property
+  merged_address_key TEXT,
+  reasons TEXT,                   -- why the pair was flagged, if from a candidate
+  history_moved INTEGER NOT NULL,
+  sources_moved INTEGER NOT NULL,
+  merged_by INTEGER,              -- admin user id
+  merged_at INTEGER NOT NULL
+);
+
+-- Open houses seen on listings. Times are local to the property, as listed.
+-- A row is cancelled when the listing stops showing it before it starts, and
+-- reinstated if it comes back.
+CREATE TABLE IF NOT EXISTS property_open_houses (
+  id INTEGER PRIMARY KEY,
+  property_id INTEGER NOT NULL,
+  starts_at DATETIME NOT NULL,
+  ends_at DATETIME,
+  time_zone TEXT,        -- e.g. 'MST'
+  first_seen_at DATETIME NOT NULL,
+  last_seen_at DATETIME NOT NULL,
+  cancelled_at DATETIME,
+  FOREIGN KEY (property_id) REFERENCES properties(id) ON DELETE CASCADE,
+  UNIQUE (property_id, starts_at)
+);
+CREATE INDEX IF NOT EXISTS idx_property_open_houses_starts_at ON property_open_houses(starts_at);
+
+-- What a listing's detail page adds, fetched by the optional enrichment pass
+-- for new or changed listings. One row per property, replaced on each fetch.
+CREATE TABLE IF NOT EXISTS property_details (
+  property_id INTEGER PRIMARY KEY,
+  detail_url TEXT NOT NULL,
+  agent_name TEXT,       -- the listing ("seller") agent
+  brokerage TEXT,        -- the listing agent's office
+  description TEXT,
+  days_on_market INTEGER,
+  photo_count INTEGER,
+  fetched_at DATETIME NOT NULL,
+  FOREIGN KEY (property_id) REFERENCES properties(id) ON DELETE CASCADE
+);
+-- The detail page's price history, as of the last fetch.
+CREATE TABLE IF NOT EXISTS property_price_events (
+  id INTEGER PRIMARY KEY,
+  property_id INTEGER NOT NULL,
+  event_date DATE NOT NULL,
+  event_name TEXT NOT NULL, -- e.g. 'Listed', 'Price Changed', 'Sold'
+  price INTEGER,
+  source_name TEXT,
+  FOREIGN KEY (property_id) REFERENCES properties(id) ON DELETE CASCADE
+);
+CREATE INDEX IF NOT EXISTS idx_property_price_events_property_id ON property_price_events(property_id);
+
 
 -- =================================================================
 -- Direct Mail Platform Schema (Campaigns, Media, Lists, Mailings)
@@ -215,7 +420,20 @@ CREATE TABLE IF NOT EXISTS scrape_runs (
   pages_fetched INTEGER,
   properties_seen INTEGER,
   success INTEGER,
-  error_message TEXT
+  error_message TEXT,
+  shard TEXT,       -- e.g. 'county:Salt Lake County', 'zip:84101'; NULL for a whole-state run
+  search_url TEXT,  -- base search URL the run paginates
+  expected_total INTEGER, -- result count reported by the site
+  split_into INTEGER,     -- number of price bands when the search hit the page cap
+  parent_run_id INTEGER,  -- the run a price band was split from
+  coverage REAL,          -- estimated share of expected_total scraped (0.0-1.0)
+  mode TEXT,              -- 'full' or 'incremental' (newest first, stops at known listings)
+  stop_after INTEGER,     -- incremental: known-and-unchanged streak that ends the run
+  blocked_attempts INTEGER, -- fetches answered with a captcha / bot wall, retries included
+  block_rate REAL,          -- blocked_attempts / (blocked_attempts + pages fetched)
+  search_kind TEXT,         -- 'for_sale', 'sold' or 'for_rent'; NULL on older runs (for sale)
+  config TEXT,              -- effective scraper config (JSON); NULL on older runs
+  capped INTEGER            -- 1 when the search hit the page cap before its last result
 );
 CREATE TABLE IF NOT EXISTS scrape_run_pages (
   id INTEGER PRIMARY KEY,
@@ -224,9 +442,47 @@ CREATE TABLE IF NOT EXISTS scrape_run_pages (
   page_url TEXT NOT NULL,
   success INTEGER,
   properties_found INTEGER,
+  blocked_attempts INTEGER NOT NULL DEFAULT 0, -- summed across retries of the page
   FOREIGN KEY (scrape_run_id) REFERENCES scrape_runs(id),
   UNIQUE (scrape_run_id, page_number)
 );
+-- Recurring scrapes, launched by the background scheduler when due.
+CREATE TABLE IF NOT EXISTS scrape_schedules (
+  id INTEGER PRIMARY KEY,
+  state TEXT NOT NULL,
+  shard_by TEXT NOT NULL DEFAULT 'state', -- 'state', 'county' or 'zip'
+  mode TEXT NOT NULL DEFAULT 'full',      -- 'full' or 'incremental'
+  stop_after INTEGER,                     -- incremental: known-listing streak limit
+  search_kind TEXT NOT NULL DEFAULT 'for_sale', -- 'for_sale', 'sold' or 'for_rent'
+  cadence_hours INTEGER NOT NULL,         -- e.g. 24 = daily, 168 = weekly
+  enabled INTEGER NOT NULL DEFAULT 1,
+  last_run_at INTEGER,
+  next_run_at INTEGER NOT NULL,
+  created_at INTEGER NOT NULL,
+  last_attempt_at INTEGER,                -- last time the scheduler tried to start it
+  last_error TEXT                         -- why that attempt failed; NULL once one starts
+);
+CREATE TABLE IF NOT EXISTS scrape_quarantine (
+  id INTEGER PRIMARY KEY,
+  scrape_run_id INTEGER,          -- NULL when the record came from outside a run
+  page_number INTEGER,
+  stage TEXT NOT NULL,            -- 'deserialize' (bad JSON shape) or 'validate' (missing fields)
+  raw_json TEXT NOT NULL,
+  error TEXT NOT NULL,
+  created_at INTEGER NOT NULL,
+  resolved_at INTEGER,            -- set once a re-process saves the record
+  FOREIGN KEY (scrape_run_id) REFERENCES scrape_runs(id)
+);
+CREATE INDEX IF NOT EXISTS idx_scrape_quarantine_open ON scrape_quarantine(resolved_at, id);
+CREATE TABLE IF NOT EXISTS scrape_page_archive (
+  scrape_run_id INTEGER NOT NULL,
+  page_number INTEGER NOT NULL,
+  page_url TEXT NOT NULL,
+  fetched_at INTEGER NOT NULL,
+  next_data BLOB NOT NULL,        -- gzip-compressed __NEXT_DATA__ JSON, as served
+  PRIMARY KEY (scrape_run_id, page_number),
+  FOREIGN KEY (scrape_run_id) REFERENCES scrape_runs(id)
+);
 
 -- ===============================
 -- Users, Auth, and Billing
@@ -302,6 +558,17 @@ create table if not exists sessions (
 );
 create index if not exists idx_sessions_user on sessions(user_id);
 create index if not exists idx_sessions_expires on sessions(expires_at);
+-- Open house calendar links, one per user and area; making a new link for
+-- an area retires that area's old one.
+create table if not exists calendar_feeds (
+  id          integer primary key,
+  user_id     integer not null,
+  area        text not null default '',  -- the link's filter query, '' for everywhere
+  token_hash  blob not null unique,
+  created_at  integer not null,
+  unique(user_id, area),
+  foreign key(user_id) references users(id) on delete cascade
+);
 
 
 -- Seed plans (idempotent)
@@ -0,0 +1,161 @@
+// src/calendar/ics.rs
+
+use crate::db::open_houses::OpenHouseEvent;
+use chrono::NaiveDateTime;
+
+/// Renders open houses as an iCalendar (RFC 5545) feed.
+///
+/// Times are written as floating local times, since listings give them in the
+/// property's own time zone; a calendar shows them as-is. Cancelled open
+/// houses stay in the feed with `STATUS:CANCELLED` so subscribers drop them.
+pub fn open_houses_ics(events: &[OpenHouseEvent], generated_at: NaiveDateTime) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//scraper_simple//Open Houses//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        "X-WR-CALNAME:Open Houses".to_string(),
+    ];
+
+    for event in events {
+        let location = format!(
+            "{}, {}, {} {}",
+            event.address_line,
+            event.city,
+            event.state_abbr.as_deref().unwrap_or(""),
+            event.postal_code
+        );
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:open-house-{}@scraper_simple", event.id));
+        lines.push(format!("DTSTAMP:{}", format_utc(generated_at)));
+        lines.push(format!("CREATED:{}", format_utc(event.first_seen_at)));
+        lines.push(format!("DTSTART:{}", format_local(event.starts_at)));
+        if let Some(end) = event.ends_at {
+            lines.push(format!("DTEND:{}", format_local(end)));
+        }
+        lines.push(format!(
+            "SUMMARY:{}",
+            escape_text(&format!("Open house: {}", event.address_line))
+        ));
+        lines.push(format!("LOCATION:{}", escape_text(&location)));
+        if let Some(price) = event.list_price {
+            lines.push(format!(
+                "DESCRIPTION:{}",
+                escape_text(&format!("Listed at ${}", group_thousands(price)))
+            ));
+        }
+        match event.cancelled_at {
+            Some(cancelled_at) => {
+                lines.push("STATUS:CANCELLED".to_string());
+                lines.push("SEQUENCE:1".to_string());
+                lines.push(format!("LAST-MODIFIED:{}", format_utc(cancelled_at)));
+            }
+            None => lines.push("STATUS:CONFIRMED".to_string()),
+        }
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    let mut out = String::new();
+    for line in lines {
+        out.push_str(&fold(&line));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+fn format_local(dt: NaiveDateTime) -> String {
+    dt.format("%Y%m%dT%H%M%S").to_string()
+}
+
+fn format_utc(dt: NaiveDateTime) -> String {
+    dt.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn group_thousands(n: i64) -> String {
+    let digits = n.abs().to_string();
+    let mut out = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    if n < 0 {
+        format!("-{out}")
+    } else {
+        out
+    }
+}
+
+/// Escapes a TEXT value: backslashes, semicolons, commas and newlines.
+fn escape_text(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Folds a content line to at most 75 octets, continuing with a space.
+fn fold(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut width = 0;
+    for c in line.chars() {
+        let len = c.len_utf8();
+        if width + len > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += len;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(h: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 10, 18)
+            .unwrap()
+            .and_hms_opt(h, 0, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn events_are_escaped_and_cancellations_kept() {
+        let event = OpenHouseEvent {
+            id: 7,
+            address_line: "77 E 400 S Apt 4".into(),
+            city: "Salt Lake City".into(),
+            state_abbr: Some("UT".into()),
+            postal_code: "84111".into(),
+            list_price: Some(315000),
+            starts_at: at(12),
+            ends_at: Some(at(14)),
+            first_seen_at: at(8),
+            cancelled_at: Some(at(9)),
+        };
+        let ics = open_houses_ics(&[event], at(10));
+
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert!(ics.contains("DTSTART:20251018T120000\r\n"));
+        assert!(ics.contains("DTEND:20251018T140000\r\n"));
+        assert!(ics.contains("LOCATION:77 E 400 S Apt 4\\, Salt Lake City\\, UT 84111\r\n"));
+        assert!(ics.contains("DESCRIPTION:Listed at $315\\,000\r\n"));
+        assert!(ics.contains("STATUS:CANCELLED\r\n"));
+    }
+
+    #[test]
+    fn long_lines_are_folded() {
+        let folded = fold(&"x".repeat(100));
+        let lines: Vec<&str> = folded.split("\r\n").collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].len(), 75);
+        assert_eq!(lines[1], format!(" {}", "x".repeat(25)));
+    }
+}
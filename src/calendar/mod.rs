@@ -0,0 +1,2 @@
+pub mod ics;
+pub use ics::open_houses_ics;
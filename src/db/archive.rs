@@ -0,0 +1,97 @@
+use crate::errors::ServerError;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::io::{Read, Write};
+
+/// The `__NEXT_DATA__` JSON of one fetched results page.
+#[derive(Debug)]
+pub struct ArchivedPage {
+    pub page_url: String,
+    pub fetched_at: i64,
+    pub next_data: String,
+}
+
+/// Stores a page's JSON, gzip-compressed. A page retried within the same run
+/// replaces the earlier copy.
+pub fn archive_page(
+    conn: &Connection,
+    run_id: i64,
+    page_number: usize,
+    page_url: &str,
+    next_data: &str,
+    now: i64,
+) -> Result<(), ServerError> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(next_data.as_bytes())
+        .map_err(|_| ServerError::InternalError)?;
+    let compressed = encoder.finish().map_err(|_| ServerError::InternalError)?;
+
+    conn.execute(
+        "INSERT OR REPLACE INTO scrape_page_archive (scrape_run_id, page_number, page_url, fetched_at, next_data)
+         VALUES (?, ?, ?, ?, ?)",
+        params![run_id, page_number, page_url, now, compressed],
+    )?;
+    Ok(())
+}
+
+pub fn get_archived_page(
+    conn: &Connection,
+    run_id: i64,
+    page_number: i64,
+) -> Result<Option<ArchivedPage>, ServerError> {
+    let row = conn
+        .query_row(
+            "SELECT page_url, fetched_at, next_data FROM scrape_page_archive
+             WHERE scrape_run_id = ? AND page_number = ?",
+            params![run_id, page_number],
+            |r| {
+                Ok((
+                    r.get::<_, String>(0)?,
+                    r.get::<_, i64>(1)?,
+                    r.get::<_, Vec<u8>>(2)?,
+                ))
+            },
+        )
+        .optional()?;
+
+    let Some((page_url, fetched_at, compressed)) = row else {
+        return Ok(None);
+    };
+
+    let mut next_data = String::new();
+    GzDecoder::new(compressed.as_slice())
+        .read_to_string(&mut next_data)
+        .map_err(|e| {
+            ServerError::DbError(format!(
+                "Corrupt archive for run {run_id} page {page_number}: {e}"
+            ))
+        })?;
+
+    Ok(Some(ArchivedPage {
+        page_url,
+        fetched_at,
+        next_data,
+    }))
+}
+
+/// (run id, page number) of every archived page, in the order they were
+/// scraped. With `from_run`, starts at that run.
+pub fn archived_page_keys(
+    conn: &Connection,
+    from_run: Option<i64>,
+) -> Result<Vec<(i64, i64)>, ServerError> {
+    let mut stmt = conn.prepare(
+        "SELECT a.scrape_run_id, a.page_number
+         FROM scrape_page_archive a
+         JOIN scrape_runs r ON r.id = a.scrape_run_id
+         WHERE ?1 IS NULL OR a.scrape_run_id >= ?1
+         ORDER BY r.started_at, a.scrape_run_id, a.page_number",
+    )?;
+    let keys = stmt
+        .query_map(params![from_run], |r| Ok((r.get(0)?, r.get(1)?)))?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(keys)
+}
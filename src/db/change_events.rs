@@ -0,0 +1,126 @@
+use crate::domain::events::{events_between, ChangeEvent, ChangeEventKind, ListingState};
+use crate::domain::history::{HistoryEntry, PropertySnapshot, MARKET_STATUS_FIELD};
+use crate::errors::ServerError;
+use chrono::NaiveDateTime;
+use rusqlite::{params, Connection, OptionalExtension};
+
+/// Stores the events one observation of a property produced.
+pub(crate) fn record_change_events(
+    tx: &Connection,
+    property_id: i64,
+    events: &[ChangeEvent],
+    site: Option<&str>,
+    now: NaiveDateTime,
+) -> Result<(), ServerError> {
+    let mut stmt = tx.prepare_cached(
+        "INSERT INTO change_events (
+            property_id, observed_at, event_type, previous_status, current_status,
+            previous_price, current_price, site
+         ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+    )?;
+    for event in events {
+        // Nobody reports a listing's absence.
+        let site = site.filter(|_| event.kind != ChangeEventKind::Delisted);
+        stmt.execute(params![
+            property_id,
+            now,
+            event.kind.as_str(),
+            event.previous_status,
+            event.current_status,
+            event.previous_price,
+            event.current_price,
+            site
+        ])?;
+    }
+    Ok(())
+}
+
+/// The stored state of a property, for working out what a change to it means.
+pub(crate) fn listing_state(
+    conn: &Connection,
+    property_id: i64,
+) -> Result<Option<ListingState>, ServerError> {
+    Ok(conn
+        .query_row(
+            "SELECT status, list_price, sold_date, is_pending, is_contingent, is_coming_soon,
+                    delisted_at IS NOT NULL
+             FROM properties WHERE id = ?",
+            [property_id],
+            |r| {
+                Ok(ListingState {
+                    status: r.get(0)?,
+                    list_price: r.get(1)?,
+                    sold_date: r.get(2)?,
+                    is_pending: r.get::<_, Option<bool>>(3)?.unwrap_or(false),
+                    is_contingent: r.get::<_, Option<bool>>(4)?.unwrap_or(false),
+                    is_coming_soon: r.get::<_, Option<bool>>(5)?.unwrap_or(false),
+                    delisted: r.get(6)?,
+                })
+            },
+        )
+        .optional()?)
+}
+
+/// Fills `change_events` from `property_history` for databases that predate
+/// it, replaying each property's status, price and flag changes in order.
+/// Does nothing once any events exist. Returns how many were written.
+pub fn backfill_change_events(conn: &mut Connection) -> Result<usize, ServerError> {
+    let tx = conn.transaction()?;
+    let has_events = tx
+        .query_row("SELECT 1 FROM change_events LIMIT 1", [], |_| Ok(()))
+        .optional()?
+        .is_some();
+    if has_events {
+        return Ok(0);
+    }
+
+    type HistoryRow = (i64, NaiveDateTime, String, String, Option<String>);
+    let rows: Vec<HistoryRow> = tx
+        .prepare(
+            "SELECT h.property_id, h.observed_at, h.field_name, h.current_value,
+                    (SELECT MIN(hs.site) FROM property_history_sources hs WHERE hs.history_id = h.id)
+             FROM property_history h
+             WHERE h.field_name IN ('status', 'list_price', 'sold_date', 'is_pending',
+                                    'is_contingent', 'is_coming_soon', ?1)
+             ORDER BY h.property_id, h.observed_at, h.id",
+        )?
+        .query_map([MARKET_STATUS_FIELD], |r| {
+            Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?, r.get(4)?))
+        })?
+        .collect::<rusqlite::Result<_>>()?;
+
+    let mut written = 0;
+    let mut i = 0;
+    let mut state: Option<PropertySnapshot> = None;
+    while i < rows.len() {
+        let (property_id, observed_at) = (rows[i].0, rows[i].1);
+        let before = state.take().filter(|s| s.property.id == property_id);
+        let mut after = before
+            .clone()
+            .unwrap_or_else(|| PropertySnapshot::new(property_id, observed_at));
+        let mut site = None;
+
+        // Everything logged for a property at one moment is one observation.
+        while i < rows.len() && rows[i].0 == property_id && rows[i].1 == observed_at {
+            let (_, _, field_name, current_value, row_site) = &rows[i];
+            after.apply(&HistoryEntry {
+                observed_at,
+                field_name: field_name.clone(),
+                current_value: current_value.clone(),
+            });
+            site = site.or(row_site.clone());
+            i += 1;
+        }
+
+        let events = events_between(
+            before.map(|s| s.listing_state()).as_ref(),
+            &after.listing_state(),
+        );
+        record_change_events(&tx, property_id, &events, site.as_deref(), observed_at)?;
+        written += events.len();
+        state = Some(after);
+    }
+
+    tx.commit()?;
+    Ok(written)
+}
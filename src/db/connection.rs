@@ -74,11 +74,106 @@ pub fn init_db(db: &Database, schema_path: &str) -> Result<(), ServerError> {
         .map_err(|e| ServerError::DbError(format!("Failed to read schema file: {e}")))?;
 
     db.with_conn(|conn| {
+        // Existing tables get their new columns first, so the schema's
+        // indexes on those columns can be created.
+        apply_column_migrations(conn)?;
         conn.execute_batch(&schema_sql)
             .map_err(|e| ServerError::DbError(format!("Failed to apply schema: {e}")))?;
+        // Before the re-key, whose merges rebuild the survivors' cycles.
+        let cycled = crate::db::cycles::backfill_listing_cycles(conn)?;
+        if cycled > 0 {
+            println!("🔁 Worked out listing cycles for {cycled} properties");
+        }
+        let merged = crate::db::properties::rekey_properties(conn)?;
+        if merged > 0 {
+            println!("🔑 Address re-key merged {merged} duplicate properties");
+        }
+        let backfilled = crate::db::change_events::backfill_change_events(conn)?;
+        if backfilled > 0 {
+            println!("🗂️ Backfilled {backfilled} change events from property history");
+        }
+        crate::geos::init_geo_schema(conn)?;
         Ok(())
     })?;
 
     println!("✅ Database initialized successfully from {}", schema_path);
     Ok(())
 }
+
+/// Columns added to tables after they were first created.
+///
+/// `CREATE TABLE IF NOT EXISTS` won't touch a table that already exists, so
+/// each new column is listed here and added on startup when it's missing.
+/// Tables that don't exist yet are skipped; the schema creates them whole.
+const COLUMN_MIGRATIONS: &[(&str, &str, &str)] = &[
+    ("scrape_runs", "shard", "TEXT"),
+    ("scrape_runs", "search_url", "TEXT"),
+    ("scrape_runs", "expected_total", "INTEGER"),
+    ("scrape_runs", "split_into", "INTEGER"),
+    ("scrape_runs", "parent_run_id", "INTEGER"),
+    ("scrape_runs", "coverage", "REAL"),
+    ("scrape_runs", "mode", "TEXT"),
+    ("scrape_runs", "stop_after", "INTEGER"),
+    ("scrape_runs", "blocked_attempts", "INTEGER"),
+    ("scrape_runs", "block_rate", "REAL"),
+    (
+        "scrape_run_pages",
+        "blocked_attempts",
+        "INTEGER NOT NULL DEFAULT 0",
+    ),
+    ("properties", "address_key", "TEXT"),
+    ("properties", "delisted_at", "DATETIME"),
+    ("properties", "beds", "INTEGER"),
+    ("properties", "baths", "REAL"),
+    ("properties", "sqft", "INTEGER"),
+    ("properties", "lot_sqft", "INTEGER"),
+    ("properties", "property_type", "TEXT"),
+    ("properties", "year_built", "INTEGER"),
+    ("properties", "garage", "INTEGER"),
+    ("properties", "stories", "INTEGER"),
+    ("properties", "is_new_construction", "INTEGER"),
+    ("properties", "subdivision", "TEXT"),
+    ("properties", "latitude", "REAL"),
+    ("properties", "longitude", "REAL"),
+    ("properties", "county_fips", "INTEGER"),
+    ("scrape_runs", "search_kind", "TEXT"),
+    ("scrape_runs", "config", "TEXT"),
+    ("scrape_runs", "capped", "INTEGER"),
+    (
+        "property_sources",
+        "site",
+        "TEXT NOT NULL DEFAULT 'realtor'",
+    ),
+];
+
+fn apply_column_migrations(conn: &Connection) -> Result<(), ServerError> {
+    for (table, column, decl) in COLUMN_MIGRATIONS {
+        if has_table(conn, table)? && !has_column(conn, table, column)? {
+            conn.execute_batch(&format!("ALTER TABLE {table} ADD COLUMN {column} {decl};"))
+                .map_err(|e| {
+                    ServerError::DbError(format!("Failed to add {table}.{column}: {e}"))
+                })?;
+        }
+    }
+    Ok(())
+}
+
+fn has_table(conn: &Connection, table: &str) -> Result<bool, ServerError> {
+    let count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?",
+        [table],
+        |r| r.get(0),
+    )?;
+    Ok(count > 0)
+}
+
+fn has_column(conn: &Connection, table: &str, column: &str) -> Result<bool, ServerError> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({table})"))?;
+    let names = stmt.query_map([], |r| r.get::<_, String>(1))?;
+    for name in names {
+        if name? == column {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
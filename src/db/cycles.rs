@@ -0,0 +1,141 @@
+use crate::domain::cycles::listing_cycles;
+use crate::domain::history::{observations, HistoryEntry};
+use crate::errors::ServerError;
+use chrono::NaiveDateTime;
+use rusqlite::{params, Connection, OptionalExtension, Result as RusqliteResult};
+
+/// Listing cycle figures for one county.
+#[derive(Debug)]
+pub struct CountyCycleSummary {
+    /// Empty for properties without a county.
+    pub county_name: String,
+    pub cycles: i64,
+    /// Still on the market.
+    pub open: i64,
+    pub sold: i64,
+    pub delisted: i64,
+    /// Over cycles that have ended.
+    pub avg_days_on_market: Option<f64>,
+    pub avg_days_to_pending: Option<f64>,
+    pub avg_price_cuts: Option<f64>,
+    /// Cycles with at least one price cut.
+    pub with_price_cuts: i64,
+}
+
+/// Rebuilds a property's listing cycles from its history. Call after
+/// anything is logged for it.
+pub(crate) fn refresh_listing_cycles(tx: &Connection, property_id: i64) -> Result<(), ServerError> {
+    let first_seen_at: Option<NaiveDateTime> = tx
+        .query_row(
+            "SELECT first_seen_at FROM properties WHERE id = ?",
+            [property_id],
+            |r| r.get(0),
+        )
+        .optional()?;
+    tx.execute(
+        "DELETE FROM listing_cycles WHERE property_id = ?",
+        [property_id],
+    )?;
+    let Some(first_seen_at) = first_seen_at else {
+        return Ok(());
+    };
+
+    let history: Vec<HistoryEntry> = tx
+        .prepare_cached(
+            "SELECT observed_at, field_name, current_value FROM property_history
+             WHERE property_id = ? ORDER BY observed_at, id",
+        )?
+        .query_map([property_id], |r| {
+            Ok(HistoryEntry {
+                observed_at: r.get(0)?,
+                field_name: r.get(1)?,
+                current_value: r.get(2)?,
+            })
+        })?
+        .collect::<RusqliteResult<_>>()?;
+
+    let mut insert = tx.prepare_cached(
+        "INSERT INTO listing_cycles (
+            property_id, cycle_number, listed_at, pending_at, ended_at, outcome,
+            initial_price, last_price, price_cuts, days_on_market, days_to_pending
+         ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )?;
+    let cycles = listing_cycles(first_seen_at, &observations(property_id, &history));
+    for (i, cycle) in cycles.iter().enumerate() {
+        insert.execute(params![
+            property_id,
+            i as i64 + 1,
+            cycle.listed_at,
+            cycle.pending_at,
+            cycle.ended_at,
+            cycle.outcome.map(|o| o.as_str()),
+            cycle.initial_price,
+            cycle.last_price,
+            cycle.price_cuts,
+            cycle.days_on_market(),
+            cycle.days_to_pending()
+        ])?;
+    }
+    Ok(())
+}
+
+/// Works out listing cycles for databases that predate them. Does nothing
+/// once any cycles exist. Returns how many properties were processed.
+pub fn backfill_listing_cycles(conn: &mut Connection) -> Result<usize, ServerError> {
+    let tx = conn.transaction()?;
+    let has_cycles = tx
+        .query_row("SELECT 1 FROM listing_cycles LIMIT 1", [], |_| Ok(()))
+        .optional()?
+        .is_some();
+    if has_cycles {
+        return Ok(0);
+    }
+
+    let ids: Vec<i64> = tx
+        .prepare("SELECT DISTINCT property_id FROM property_history")?
+        .query_map([], |r| r.get(0))?
+        .collect::<RusqliteResult<_>>()?;
+    for id in &ids {
+        refresh_listing_cycles(&tx, *id)?;
+    }
+    tx.commit()?;
+    Ok(ids.len())
+}
+
+/// Listing cycles in a state that began in `year`, summed up per county.
+pub fn county_cycle_summary(
+    conn: &Connection,
+    state: &str,
+    year: i32,
+) -> Result<Vec<CountyCycleSummary>, ServerError> {
+    let mut stmt = conn.prepare(
+        "SELECT COALESCE(p.county_name, ''),
+                COUNT(*),
+                SUM(c.outcome IS NULL),
+                SUM(c.outcome = 'sold'),
+                SUM(c.outcome = 'delisted'),
+                AVG(c.days_on_market),
+                AVG(c.days_to_pending),
+                AVG(c.price_cuts),
+                SUM(c.price_cuts > 0)
+         FROM listing_cycles c JOIN properties p ON p.id = c.property_id
+         WHERE p.state_abbr = ?1 AND strftime('%Y', c.listed_at) = ?2
+         GROUP BY 1 ORDER BY 1",
+    )?;
+    let rows = stmt
+        .query_map(params![state, year.to_string()], |r| {
+            Ok(CountyCycleSummary {
+                county_name: r.get(0)?,
+                cycles: r.get(1)?,
+                open: r.get(2)?,
+                sold: r.get(3)?,
+                delisted: r.get(4)?,
+                avg_days_on_market: r.get(5)?,
+                avg_days_to_pending: r.get(6)?,
+                avg_price_cuts: r.get(7)?,
+                with_price_cuts: r.get(8)?,
+            })
+        })?
+        .collect::<RusqliteResult<_>>()?;
+    Ok(rows)
+}
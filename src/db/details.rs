@@ -0,0 +1,64 @@
+use crate::domain::details::ListingDetails;
+use crate::errors::ServerError;
+use chrono::NaiveDateTime;
+use rusqlite::{params, Connection, OptionalExtension};
+
+/// Stores a detail page's enrichment for the property with `address_key`,
+/// replacing any earlier fetch. Returns `false` if no such property exists.
+pub fn save_listing_details(
+    conn: &mut Connection,
+    address_key: &str,
+    detail_url: &str,
+    details: &ListingDetails,
+    fetched_at: NaiveDateTime,
+) -> Result<bool, ServerError> {
+    let tx = conn.transaction()?;
+    let Some(property_id) = tx
+        .query_row(
+            "SELECT id FROM properties WHERE address_key = ?",
+            [address_key],
+            |r| r.get::<_, i64>(0),
+        )
+        .optional()?
+    else {
+        return Ok(false);
+    };
+
+    tx.execute(
+        "INSERT OR REPLACE INTO property_details (
+            property_id, detail_url, agent_name, brokerage, description,
+            days_on_market, photo_count, fetched_at
+         ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        params![
+            property_id,
+            detail_url,
+            details.agent_name,
+            details.brokerage,
+            details.description,
+            details.days_on_market,
+            details.photo_count,
+            fetched_at
+        ],
+    )?;
+    tx.execute(
+        "DELETE FROM property_price_events WHERE property_id = ?",
+        [property_id],
+    )?;
+    {
+        let mut stmt = tx.prepare(
+            "INSERT INTO property_price_events (property_id, event_date, event_name, price, source_name)
+             VALUES (?, ?, ?, ?, ?)",
+        )?;
+        for event in &details.price_history {
+            stmt.execute(params![
+                property_id,
+                event.date,
+                event.event_name,
+                event.price,
+                event.source_name
+            ])?;
+        }
+    }
+    tx.commit()?;
+    Ok(true)
+}
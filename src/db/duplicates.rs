@@ -0,0 +1,361 @@
+use crate::db::properties::merge_property_into;
+use crate::domain::address::split_unit;
+use crate::errors::ServerError;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::{BTreeMap, HashMap};
+
+/// Two properties closer than this with the same ZIP are flagged.
+const NEARBY_METERS: f64 = 20.0;
+
+/// Grid cell size for the coordinate comparison, in degrees (~55 m of
+/// latitude). Only properties in the same or adjacent cells are compared.
+const CELL_DEGREES: f64 = 0.0005;
+
+/// One side of a candidate pair, as shown on the review screen.
+#[derive(Debug)]
+pub struct CandidateSide {
+    pub id: i64,
+    pub address: String,
+    pub status: Option<String>,
+    pub list_price: Option<i64>,
+    pub first_seen_at: String,
+    pub sources: i64,
+}
+
+/// A pair of properties the duplicate scan thinks may be the same parcel.
+#[derive(Debug)]
+pub struct DuplicateCandidate {
+    pub id: i64,
+    pub a: CandidateSide,
+    pub b: CandidateSide,
+    pub reasons: String,
+    pub score: f64,
+    pub detected_at: i64,
+}
+
+/// A row from the `property_merges` audit log.
+#[derive(Debug)]
+pub struct PropertyMerge {
+    pub surviving_property_id: i64,
+    pub merged_property_id: i64,
+    pub merged_address: String,
+    pub reasons: Option<String>,
+    pub history_moved: i64,
+    pub sources_moved: i64,
+    pub merged_by: Option<i64>,
+    pub merged_at: i64,
+}
+
+struct KeyedProperty {
+    id: i64,
+    street_key: String,
+    unit: Option<String>,
+    zip: String,
+    coords: Option<(f64, f64)>,
+}
+
+/// Scans every property for likely duplicates and stores new candidate
+/// pairs. A pair is flagged when any of these hold:
+///
+/// - same normalized street address, where one has a unit and the other not;
+/// - coordinates within `NEARBY_METERS` in the same ZIP (unless both have
+///   different units, i.e. neighbouring condos);
+/// - both are linked to the same source listing id.
+///
+/// Pairs already stored, open or dismissed, are skipped. Returns how many new
+/// candidates were added.
+pub fn detect_duplicates(conn: &Connection, now: i64) -> Result<usize, ServerError> {
+    let props: Vec<KeyedProperty> = conn
+        .prepare(
+            "SELECT id, address_key, latitude, longitude FROM properties
+             WHERE address_key IS NOT NULL",
+        )?
+        .query_map([], |r| {
+            let id: i64 = r.get(0)?;
+            let key: String = r.get(1)?;
+            let lat: Option<f64> = r.get(2)?;
+            let lon: Option<f64> = r.get(3)?;
+            Ok((id, key, lat.zip(lon)))
+        })?
+        .map(|row| {
+            row.map(|(id, key, coords)| {
+                let mut parts = key.splitn(3, '|');
+                let line = parts.next().unwrap_or("");
+                let city = parts.next().unwrap_or("");
+                let zip = parts.next().unwrap_or("").to_string();
+                let (street, unit) = split_unit(line);
+                KeyedProperty {
+                    id,
+                    street_key: format!("{street}|{city}|{zip}"),
+                    unit: unit.map(str::to_string),
+                    zip,
+                    coords,
+                }
+            })
+        })
+        .collect::<rusqlite::Result<_>>()?;
+
+    let mut pairs: BTreeMap<(i64, i64), (Vec<String>, f64)> = BTreeMap::new();
+    let mut flag = |x: i64, y: i64, reason: String, weight: f64| {
+        let entry = pairs
+            .entry((x.min(y), x.max(y)))
+            .or_insert_with(|| (Vec::new(), 0.0));
+        entry.0.push(reason);
+        entry.1 = (entry.1 + weight).min(1.0);
+    };
+
+    // Same street address, unit vs no unit.
+    let mut by_street: HashMap<&str, Vec<&KeyedProperty>> = HashMap::new();
+    for p in &props {
+        by_street.entry(p.street_key.as_str()).or_default().push(p);
+    }
+    for group in by_street.values() {
+        for (i, x) in group.iter().enumerate() {
+            for y in &group[i + 1..] {
+                if x.unit.is_some() != y.unit.is_some() {
+                    flag(x.id, y.id, "same street address".to_string(), 0.5);
+                }
+            }
+        }
+    }
+
+    // Nearby coordinates in the same ZIP.
+    let cell = |(lat, lon): (f64, f64)| {
+        (
+            (lat / CELL_DEGREES).floor() as i64,
+            (lon / CELL_DEGREES).floor() as i64,
+        )
+    };
+    let mut grid: HashMap<(i64, i64), Vec<&KeyedProperty>> = HashMap::new();
+    for p in &props {
+        if let Some(c) = p.coords {
+            grid.entry(cell(c)).or_default().push(p);
+        }
+    }
+    for x in &props {
+        let Some(xc) = x.coords else { continue };
+        let (row, col) = cell(xc);
+        for dr in -1..=1 {
+            for dc in -1..=1 {
+                let Some(neighbours) = grid.get(&(row + dr, col + dc)) else {
+                    continue;
+                };
+                for y in neighbours {
+                    if y.id <= x.id || y.zip != x.zip {
+                        continue;
+                    }
+                    if matches!((&x.unit, &y.unit), (Some(u), Some(v)) if u != v) {
+                        continue;
+                    }
+                    let meters = distance_meters(xc, y.coords.unwrap_or(xc));
+                    if meters <= NEARBY_METERS {
+                        flag(x.id, y.id, format!("{meters:.0} m apart"), 0.3);
+                    }
+                }
+            }
+        }
+    }
+
+    // Shared source listing ids.
+    let shared: Vec<(i64, i64)> = conn
+        .prepare(
+            "SELECT DISTINCT a.property_id, b.property_id
+             FROM property_sources a
+             JOIN property_sources b
+               ON b.source_listing_id = a.source_listing_id AND b.property_id > a.property_id",
+        )?
+        .query_map([], |r| Ok((r.get(0)?, r.get(1)?)))?
+        .collect::<rusqlite::Result<_>>()?;
+    for (x, y) in shared {
+        flag(x, y, "shared listing id".to_string(), 0.6);
+    }
+
+    let mut added = 0;
+    for ((a, b), (reasons, score)) in pairs {
+        added += conn.execute(
+            "INSERT OR IGNORE INTO property_duplicate_candidates
+                (property_a_id, property_b_id, reasons, score, detected_at)
+             VALUES (?, ?, ?, ?, ?)",
+            params![a, b, reasons.join("; "), score, now],
+        )?;
+    }
+    Ok(added)
+}
+
+/// Great-circle distance between two (lat, lon) points.
+fn distance_meters((lat1, lon1): (f64, f64), (lat2, lon2): (f64, f64)) -> f64 {
+    const EARTH_RADIUS_M: f64 = 6_371_000.0;
+    let (p1, p2) = (lat1.to_radians(), lat2.to_radians());
+    let dp = (lat2 - lat1).to_radians();
+    let dl = (lon2 - lon1).to_radians();
+    let h = (dp / 2.0).sin().powi(2) + p1.cos() * p2.cos() * (dl / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_M * h.sqrt().asin()
+}
+
+const SIDE_COLUMNS: &str =
+    "id, address_line || ', ' || city || ' ' || postal_code, status, list_price,
+     first_seen_at, (SELECT COUNT(*) FROM property_sources s WHERE s.property_id = properties.id)";
+
+fn get_side(conn: &Connection, id: i64) -> Result<CandidateSide, ServerError> {
+    Ok(conn.query_row(
+        &format!("SELECT {SIDE_COLUMNS} FROM properties WHERE id = ?"),
+        [id],
+        |r| {
+            Ok(CandidateSide {
+                id: r.get(0)?,
+                address: r.get(1)?,
+                status: r.get(2)?,
+                list_price: r.get(3)?,
+                first_seen_at: r.get::<_, chrono::NaiveDateTime>(4)?.to_string(),
+                sources: r.get(5)?,
+            })
+        },
+    )?)
+}
+
+/// Open candidates, most likely first.
+pub fn list_open_candidates(
+    conn: &Connection,
+    limit: usize,
+) -> Result<Vec<DuplicateCandidate>, ServerError> {
+    let rows: Vec<(i64, i64, i64, String, f64, i64)> = conn
+        .prepare(
+            "SELECT id, property_a_id, property_b_id, reasons, score, detected_at
+             FROM property_duplicate_candidates
+             WHERE status = 'open' ORDER BY score DESC, id LIMIT ?",
+        )?
+        .query_map(params![limit], |r| {
+            Ok((
+                r.get(0)?,
+                r.get(1)?,
+                r.get(2)?,
+                r.get(3)?,
+                r.get(4)?,
+                r.get(5)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<_>>()?;
+
+    let mut candidates = Vec::with_capacity(rows.len());
+    for (id, a, b, reasons, score, detected_at) in rows {
+        candidates.push(DuplicateCandidate {
+            id,
+            a: get_side(conn, a)?,
+            b: get_side(conn, b)?,
+            reasons,
+            score,
+            detected_at,
+        });
+    }
+    Ok(candidates)
+}
+
+pub fn count_open_candidates(conn: &Connection) -> Result<i64, ServerError> {
+    Ok(conn.query_row(
+        "SELECT COUNT(*) FROM property_duplicate_candidates WHERE status = 'open'",
+        [],
+        |r| r.get(0),
+    )?)
+}
+
+/// Marks a pair as not a duplicate so later scans leave it alone.
+pub fn dismiss_candidate(conn: &Connection, id: i64, now: i64) -> Result<(), ServerError> {
+    let updated = conn.execute(
+        "UPDATE property_duplicate_candidates SET status = 'dismissed', resolved_at = ?
+         WHERE id = ? AND status = 'open'",
+        params![now, id],
+    )?;
+    if updated == 0 {
+        return Err(ServerError::NotFound);
+    }
+    Ok(())
+}
+
+/// Merges a candidate pair into `keep_id`, which must be one of the pair.
+/// History and sources move onto the survivor, the other property is deleted
+/// (its candidate rows go with it) and the merge is written to
+/// `property_merges`. Returns the audit row id.
+pub fn merge_candidate(
+    conn: &mut Connection,
+    candidate_id: i64,
+    keep_id: i64,
+    merged_by: Option<i64>,
+    now: i64,
+) -> Result<i64, ServerError> {
+    let tx = conn.transaction()?;
+
+    let (a, b, reasons): (i64, i64, String) = tx
+        .query_row(
+            "SELECT property_a_id, property_b_id, reasons FROM property_duplicate_candidates
+             WHERE id = ? AND status = 'open'",
+            [candidate_id],
+            |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)),
+        )
+        .optional()?
+        .ok_or(ServerError::NotFound)?;
+    let merged_id = match keep_id {
+        k if k == a => b,
+        k if k == b => a,
+        _ => {
+            return Err(ServerError::BadRequest(
+                "Surviving property must be one of the pair".into(),
+            ))
+        }
+    };
+
+    let (address, address_key): (String, Option<String>) = tx.query_row(
+        "SELECT address_line || ', ' || city || ' ' || postal_code, address_key
+         FROM properties WHERE id = ?",
+        [merged_id],
+        |r| Ok((r.get(0)?, r.get(1)?)),
+    )?;
+    let (history_moved, sources_moved) = merge_property_into(&tx, merged_id, keep_id)?;
+
+    tx.execute(
+        "INSERT INTO property_merges (
+            surviving_property_id, merged_property_id, merged_address, merged_address_key,
+            reasons, history_moved, sources_moved, merged_by, merged_at
+         ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        params![
+            keep_id,
+            merged_id,
+            address,
+            address_key,
+            reasons,
+            history_moved as i64,
+            sources_moved as i64,
+            merged_by,
+            now
+        ],
+    )?;
+    let merge_id = tx.last_insert_rowid();
+    tx.commit()?;
+    Ok(merge_id)
+}
+
+/// Most recent merges, newest first.
+pub fn list_recent_merges(
+    conn: &Connection,
+    limit: usize,
+) -> Result<Vec<PropertyMerge>, ServerError> {
+    let merges = conn
+        .prepare(
+            "SELECT surviving_property_id, merged_property_id, merged_address, reasons,
+                    history_moved, sources_moved, merged_by, merged_at
+             FROM property_merges ORDER BY id DESC LIMIT ?",
+        )?
+        .query_map(params![limit], |r| {
+            Ok(PropertyMerge {
+                surviving_property_id: r.get(0)?,
+                merged_property_id: r.get(1)?,
+                merged_address: r.get(2)?,
+                reasons: r.get(3)?,
+                history_moved: r.get(4)?,
+                sources_moved: r.get(5)?,
+                merged_by: r.get(6)?,
+                merged_at: r.get(7)?,
+            })
+        })?
+        .collect::<rusqlite::Result<_>>()?;
+    Ok(merges)
+}
@@ -0,0 +1,63 @@
+use crate::domain::history::{replay, HistoryEntry, PropertySnapshot};
+use crate::errors::ServerError;
+use chrono::NaiveDateTime;
+use rusqlite::{params, Connection};
+use std::collections::HashMap;
+
+/// A property's address and its reconstructed state at some moment.
+#[derive(Debug)]
+pub struct PropertyAsOf {
+    pub address_line: String,
+    pub city: String,
+    pub state_abbr: Option<String>,
+    pub postal_code: String,
+    pub county_name: Option<String>,
+    pub snapshot: PropertySnapshot,
+}
+
+/// Every property in a state as it stood at `as_of`, rebuilt from
+/// `property_history`. Properties first seen after `as_of` are left out.
+pub fn state_as_of(
+    conn: &Connection,
+    state: &str,
+    as_of: NaiveDateTime,
+) -> Result<Vec<PropertyAsOf>, ServerError> {
+    let mut history: HashMap<i64, Vec<HistoryEntry>> = HashMap::new();
+    let mut stmt = conn.prepare(
+        "SELECT h.property_id, h.observed_at, h.field_name, h.current_value
+         FROM property_history h JOIN properties p ON p.id = h.property_id
+         WHERE p.state_abbr = ?1 AND h.observed_at <= ?2
+         ORDER BY h.observed_at, h.id",
+    )?;
+    let mut rows = stmt.query(params![state, as_of])?;
+    while let Some(row) = rows.next()? {
+        history.entry(row.get(0)?).or_default().push(HistoryEntry {
+            observed_at: row.get(1)?,
+            field_name: row.get(2)?,
+            current_value: row.get(3)?,
+        });
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT id, address_line, city, state_abbr, postal_code, county_name
+         FROM properties WHERE state_abbr = ?1
+         ORDER BY county_name, city, address_line",
+    )?;
+    let mut rows = stmt.query([state])?;
+    let mut properties = Vec::new();
+    while let Some(row) = rows.next()? {
+        let id: i64 = row.get(0)?;
+        let Some(snapshot) = history.get(&id).and_then(|h| replay(id, h, as_of)) else {
+            continue;
+        };
+        properties.push(PropertyAsOf {
+            address_line: row.get(1)?,
+            city: row.get(2)?,
+            state_abbr: row.get(3)?,
+            postal_code: row.get(4)?,
+            county_name: row.get(5)?,
+            snapshot,
+        });
+    }
+    Ok(properties)
+}
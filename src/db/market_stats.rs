@@ -0,0 +1,372 @@
+use crate::domain::events::{ChangeEventKind, OFF_MARKET_STATUS};
+use crate::domain::market::{MarketStats, MarketStatsBuilder};
+use crate::errors::ServerError;
+use chrono::{Days, NaiveDate, NaiveDateTime};
+use rusqlite::{params, Connection, Result as RusqliteResult};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// How many days each nightly run recomputes, so sales that turn up a few
+/// weeks after closing still count on the day they closed.
+const RECOMPUTE_DAYS: u64 = 30;
+
+/// One row of `market_stats`.
+#[derive(Debug)]
+pub struct MarketStatsRow {
+    pub stat_date: NaiveDate,
+    pub state_abbr: String,
+    /// Empty on state and ZIP rows.
+    pub county_name: String,
+    /// Empty on state and county rows.
+    pub postal_code: String,
+    pub stats: MarketStats,
+}
+
+/// Which rows to read. A ZIP wins over a county; with neither, the rows for
+/// the state as a whole.
+#[derive(Debug, Default)]
+pub struct MarketStatsFilter {
+    pub state: String,
+    pub county: Option<String>,
+    pub postal_code: Option<String>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+/// What the running replay knows about a property at the end of a day.
+#[derive(Debug, Default)]
+struct Listing {
+    status: String,
+    list_price: Option<i64>,
+    /// Cut in price since its current cycle began.
+    cut: bool,
+}
+
+/// Works out `market_stats` for every day from `from` to `to` inclusive,
+/// replacing any rows already there. Returns how many rows were written.
+///
+/// Listings are replayed from `change_events` one day at a time, with new
+/// listings and pendings read from `listing_cycles` and sales from the
+/// `sold_date` rows in `property_history`. Each day is written in
+/// its own transaction, so a long backfill never holds the write lock for
+/// more than a day's rows.
+pub fn compute_market_stats(
+    conn: &mut Connection,
+    from: NaiveDate,
+    to: NaiveDate,
+    now: NaiveDateTime,
+) -> Result<usize, ServerError> {
+    let mut areas: HashMap<i64, Vec<Area>> = HashMap::new();
+    {
+        let mut stmt = conn.prepare(
+            "SELECT id, state_abbr, COALESCE(county_name, ''), postal_code
+             FROM properties WHERE state_abbr IS NOT NULL",
+        )?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            let state: String = row.get(1)?;
+            let county: String = row.get(2)?;
+            let postal_code: String = row.get(3)?;
+            let mut property_areas = vec![(state.clone(), String::new(), String::new())];
+            if !county.is_empty() {
+                property_areas.push((state.clone(), county, String::new()));
+            }
+            if !postal_code.is_empty() {
+                property_areas.push((state, String::new(), postal_code));
+            }
+            areas.insert(row.get(0)?, property_areas);
+        }
+    }
+
+    let mut listings: HashMap<i64, Listing> = HashMap::new();
+    let mut active: BTreeSet<i64> = BTreeSet::new();
+    replay_events(conn, None, midnight(from), &mut listings, &mut active)?;
+
+    let mut written = 0;
+    for day in from.iter_days().take_while(|d| *d <= to) {
+        let (start, end) = (midnight(day), midnight(day + Days::new(1)));
+        replay_events(conn, Some(start), end, &mut listings, &mut active)?;
+
+        let mut builders: BTreeMap<&Area, MarketStatsBuilder> = BTreeMap::new();
+        let mut add = |id: i64, f: &dyn Fn(&mut MarketStatsBuilder)| {
+            for area in areas.get(&id).into_iter().flatten() {
+                f(builders.entry(area).or_default());
+            }
+        };
+
+        for id in &active {
+            let listing = &listings[id];
+            add(*id, &|b| b.add_active(listing.list_price, listing.cut));
+        }
+        for id in cycles_starting(conn, "listed_at", start, end)? {
+            add(id, &|b| b.add_new_listing());
+        }
+        for id in cycles_starting(conn, "pending_at", start, end)? {
+            add(id, &|b| b.add_pending());
+        }
+        for (id, sold_price, list_price, days_on_market) in sales_between(conn, start, end)? {
+            add(id, &|b| b.add_sale(sold_price, list_price, days_on_market));
+        }
+
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM market_stats WHERE stat_date = ?", [day])?;
+        {
+            let mut insert = tx.prepare_cached(
+                "INSERT INTO market_stats (
+                    stat_date, state_abbr, county_name, postal_code,
+                    active_inventory, new_listings, pendings, solds,
+                    median_list_price, median_sold_price, median_days_on_market,
+                    price_cut_share, sale_to_list_ratio, computed_at
+                 ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            )?;
+            for ((state, county, postal_code), builder) in builders {
+                let stats = builder.build();
+                insert.execute(params![
+                    day,
+                    state,
+                    county,
+                    postal_code,
+                    stats.active_inventory,
+                    stats.new_listings,
+                    stats.pendings,
+                    stats.solds,
+                    stats.median_list_price,
+                    stats.median_sold_price,
+                    stats.median_days_on_market,
+                    stats.price_cut_share,
+                    stats.sale_to_list_ratio,
+                    now
+                ])?;
+                written += 1;
+            }
+        }
+        tx.commit()?;
+    }
+    Ok(written)
+}
+
+/// A state, county or ZIP: (state, county or '', ZIP or '').
+type Area = (String, String, String);
+
+fn midnight(day: NaiveDate) -> NaiveDateTime {
+    day.and_hms_opt(0, 0, 0).unwrap_or_default()
+}
+
+/// Applies the change events observed in `[from, to)` to `listings`, oldest
+/// first, keeping `active` to the ids whose status is "Active". With no
+/// `from`, replays everything before `to`.
+fn replay_events(
+    conn: &Connection,
+    from: Option<NaiveDateTime>,
+    to: NaiveDateTime,
+    listings: &mut HashMap<i64, Listing>,
+    active: &mut BTreeSet<i64>,
+) -> Result<(), ServerError> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT property_id, event_type, current_status, current_price FROM change_events
+         WHERE (?1 IS NULL OR observed_at >= ?1) AND observed_at < ?2
+         ORDER BY observed_at, id",
+    )?;
+    let mut rows = stmt.query(params![from, to])?;
+    while let Some(row) = rows.next()? {
+        let id: i64 = row.get(0)?;
+        let event_type: String = row.get(1)?;
+        let listing = listings.entry(id).or_default();
+        listing.status = row.get(2)?;
+        listing.list_price = row.get(3)?;
+
+        // As in domain::cycles: selling or dropping off ends the cycle, and a
+        // cut only counts towards the cycle it happened in.
+        let ended = matches!(listing.status.as_str(), "Sold" | OFF_MARKET_STATUS);
+        if ended {
+            listing.cut = false;
+        } else if event_type == ChangeEventKind::PriceDrop.as_str() {
+            listing.cut = true;
+        }
+
+        if listing.status == "Active" {
+            active.insert(id);
+        } else {
+            active.remove(&id);
+        }
+    }
+    Ok(())
+}
+
+/// (property id, sold price, list price, days on market of the cycle it ended).
+type Sale = (i64, Option<i64>, Option<i64>, Option<i64>);
+
+/// Sales that closed in `[from, to)`, read from each `sold_date` the
+/// property's history recorded, so a home sold twice counts both times. The
+/// prices are the ones its history held when the sale was seen. A sold date
+/// later corrected counts only on the corrected day.
+fn sales_between(
+    conn: &Connection,
+    from: NaiveDateTime,
+    to: NaiveDateTime,
+) -> Result<Vec<Sale>, ServerError> {
+    let sales = conn
+        .prepare_cached(
+            "SELECT d.property_id,
+                    (SELECT CAST(NULLIF(h.current_value, '') AS INTEGER) FROM property_history h
+                     WHERE h.property_id = d.property_id AND h.field_name = 'sold_price'
+                       AND h.observed_at <= d.observed_at
+                     ORDER BY h.observed_at DESC, h.id DESC LIMIT 1),
+                    (SELECT CAST(NULLIF(h.current_value, '') AS INTEGER) FROM property_history h
+                     WHERE h.property_id = d.property_id AND h.field_name = 'list_price'
+                       AND h.observed_at <= d.observed_at
+                     ORDER BY h.observed_at DESC, h.id DESC LIMIT 1),
+                    (SELECT c.days_on_market FROM listing_cycles c
+                     WHERE c.property_id = d.property_id AND c.outcome = 'sold'
+                       AND c.ended_at <= d.observed_at
+                     ORDER BY c.ended_at DESC LIMIT 1)
+             FROM property_history d
+             WHERE d.field_name = 'sold_date' AND d.current_value >= ?1 AND d.current_value < ?2
+               AND NOT EXISTS (
+                   SELECT 1 FROM property_history n
+                   WHERE n.property_id = d.property_id AND n.field_name = 'sold_date'
+                     AND n.id > d.id AND n.previous_value = d.current_value
+                     AND n.current_value <> ''
+               )",
+        )?
+        .query_map(params![from, to], |r| {
+            Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?))
+        })?
+        .collect::<RusqliteResult<_>>()?;
+    Ok(sales)
+}
+
+/// Properties with a listing cycle whose `column` ("listed_at" or
+/// "pending_at") falls in `[from, to)`, once per cycle.
+fn cycles_starting(
+    conn: &Connection,
+    column: &str,
+    from: NaiveDateTime,
+    to: NaiveDateTime,
+) -> Result<Vec<i64>, ServerError> {
+    let ids = conn
+        .prepare_cached(&format!(
+            "SELECT property_id FROM listing_cycles WHERE {column} >= ?1 AND {column} < ?2"
+        ))?
+        .query_map(params![from, to], |r| r.get(0))?
+        .collect::<RusqliteResult<_>>()?;
+    Ok(ids)
+}
+
+/// The nightly run: once yesterday has no figures yet, recomputes the last
+/// few weeks up to yesterday. The first run goes back to the earliest
+/// history. Returns how many rows were written.
+pub fn refresh_market_stats(
+    conn: &mut Connection,
+    now: NaiveDateTime,
+) -> Result<usize, ServerError> {
+    let Some(yesterday) = now.date().checked_sub_days(Days::new(1)) else {
+        return Ok(0);
+    };
+    let last: Option<NaiveDate> =
+        conn.query_row("SELECT MAX(stat_date) FROM market_stats", [], |r| r.get(0))?;
+    if last.is_some_and(|last| last >= yesterday) {
+        return Ok(0);
+    }
+
+    let from = match last {
+        Some(last) => {
+            let window = yesterday - Days::new(RECOMPUTE_DAYS - 1);
+            (last + Days::new(1)).min(window)
+        }
+        None => {
+            let earliest: Option<NaiveDateTime> =
+                conn.query_row("SELECT MIN(observed_at) FROM change_events", [], |r| {
+                    r.get(0)
+                })?;
+            match earliest {
+                Some(earliest) => earliest.date(),
+                None => return Ok(0),
+            }
+        }
+    };
+    compute_market_stats(conn, from, yesterday, now)
+}
+
+/// Rows matching `filter`, newest day first.
+pub fn get_market_stats(
+    conn: &Connection,
+    filter: &MarketStatsFilter,
+) -> Result<Vec<MarketStatsRow>, ServerError> {
+    let (county, postal_code) = match (&filter.postal_code, &filter.county) {
+        (Some(zip), _) => ("", zip.as_str()),
+        (None, Some(county)) => (county.as_str(), ""),
+        (None, None) => ("", ""),
+    };
+    let mut stmt = conn.prepare(
+        "SELECT stat_date, state_abbr, county_name, postal_code,
+                active_inventory, new_listings, pendings, solds,
+                median_list_price, median_sold_price, median_days_on_market,
+                price_cut_share, sale_to_list_ratio
+         FROM market_stats
+         WHERE state_abbr = ?1 AND county_name = ?2 AND postal_code = ?3
+           AND (?4 IS NULL OR stat_date >= ?4)
+           AND (?5 IS NULL OR stat_date <= ?5)
+         ORDER BY stat_date DESC",
+    )?;
+    let rows = stmt
+        .query_map(
+            params![filter.state, county, postal_code, filter.from, filter.to],
+            market_stats_row,
+        )?
+        .collect::<RusqliteResult<_>>()?;
+    Ok(rows)
+}
+
+/// Every row for a state with a day in `year`: the state's own rows, then
+/// its counties, then its ZIPs.
+pub fn market_stats_for_year(
+    conn: &Connection,
+    state: &str,
+    year: i32,
+) -> Result<Vec<MarketStatsRow>, ServerError> {
+    let mut stmt = conn.prepare(
+        "SELECT stat_date, state_abbr, county_name, postal_code,
+                active_inventory, new_listings, pendings, solds,
+                median_list_price, median_sold_price, median_days_on_market,
+                price_cut_share, sale_to_list_ratio
+         FROM market_stats
+         WHERE state_abbr = ?1 AND strftime('%Y', stat_date) = ?2
+         ORDER BY postal_code <> '', county_name <> '', county_name, postal_code, stat_date",
+    )?;
+    let rows = stmt
+        .query_map(params![state, year.to_string()], market_stats_row)?
+        .collect::<RusqliteResult<_>>()?;
+    Ok(rows)
+}
+
+/// Distinct counties with figures in a state, for the filter form.
+pub fn market_stats_counties(conn: &Connection, state: &str) -> Result<Vec<String>, ServerError> {
+    let counties = conn
+        .prepare(
+            "SELECT DISTINCT county_name FROM market_stats
+             WHERE state_abbr = ? AND county_name <> '' ORDER BY county_name",
+        )?
+        .query_map([state], |r| r.get(0))?
+        .collect::<RusqliteResult<_>>()?;
+    Ok(counties)
+}
+
+fn market_stats_row(r: &rusqlite::Row) -> RusqliteResult<MarketStatsRow> {
+    Ok(MarketStatsRow {
+        stat_date: r.get(0)?,
+        state_abbr: r.get(1)?,
+        county_name: r.get(2)?,
+        postal_code: r.get(3)?,
+        stats: MarketStats {
+            active_inventory: r.get(4)?,
+            new_listings: r.get(5)?,
+            pendings: r.get(6)?,
+            solds: r.get(7)?,
+            median_list_price: r.get(8)?,
+            median_sold_price: r.get(9)?,
+            median_days_on_market: r.get(10)?,
+            price_cut_share: r.get(11)?,
+            sale_to_list_ratio: r.get(12)?,
+        },
+    })
+}
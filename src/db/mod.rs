@@ -1,10 +1,21 @@
+pub mod archive;
 pub mod auth;
 pub mod campaigns;
+pub mod change_events;
 pub mod connection;
+pub mod cycles;
+pub mod details;
 pub mod downloads;
+pub mod duplicates;
+pub mod history;
 pub mod magic_auth;
 pub mod mailings;
+pub mod market_stats;
+pub mod open_houses;
 pub mod plans;
 pub mod properties;
+pub mod quarantine;
+pub mod rentals;
+pub mod schedules;
 pub mod scrapes;
 pub mod users;
@@ -0,0 +1,155 @@
+use crate::domain::address::normalize_postal_code;
+use crate::errors::ServerError;
+use base64::Engine;
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use rand::{rngs::OsRng, RngCore};
+use rusqlite::{params, Connection, OptionalExtension};
+use sha2::{Digest, Sha256};
+
+/// An open house as it appears in a calendar feed.
+#[derive(Debug)]
+pub struct OpenHouseEvent {
+    pub id: i64,
+    pub address_line: String,
+    pub city: String,
+    pub state_abbr: Option<String>,
+    pub postal_code: String,
+    pub list_price: Option<i64>,
+    pub starts_at: NaiveDateTime,
+    pub ends_at: Option<NaiveDateTime>,
+    pub first_seen_at: NaiveDateTime,
+    pub cancelled_at: Option<NaiveDateTime>,
+}
+
+/// Narrows a feed to one area. Empty fields don't filter.
+#[derive(Debug, Default)]
+pub struct OpenHouseFilter {
+    pub state: Option<String>,
+    /// With or without the "County" suffix.
+    pub county: Option<String>,
+    pub zip: Option<String>,
+}
+
+impl OpenHouseFilter {
+    /// Reads the `state`, `county` and `zip` pairs of a query string, as
+    /// stored for a calendar link's area.
+    pub fn from_query(query: &str) -> Self {
+        let mut filter = Self::default();
+        for (key, value) in url::form_urlencoded::parse(query.as_bytes()) {
+            let value = Some(value.trim().to_string()).filter(|v| !v.is_empty());
+            match key.as_ref() {
+                "state" => filter.state = value,
+                "county" => filter.county = value,
+                "zip" => filter.zip = value,
+                _ => {}
+            }
+        }
+        filter
+    }
+}
+
+/// Hours behind UTC of the westernmost US time zone (Hawaii).
+const WESTERNMOST_UTC_OFFSET_HOURS: i64 = 10;
+
+/// The earliest date it still is anywhere in the US at `now` (UTC).
+///
+/// Open house times are the property's local wall-clock time with no zone,
+/// so they can't be compared with the clock. Nothing on or after this date
+/// can be over everywhere yet.
+pub fn earliest_local_date(now: NaiveDateTime) -> NaiveDate {
+    (now - Duration::hours(WESTERNMOST_UTC_OFFSET_HOURS)).date()
+}
+
+/// Open houses on or after the date `from`, soonest first. Cancelled ones
+/// are included so subscribed calendars drop them.
+pub fn upcoming_open_houses(
+    conn: &Connection,
+    filter: &OpenHouseFilter,
+    from: NaiveDate,
+) -> Result<Vec<OpenHouseEvent>, ServerError> {
+    let from = from.and_hms_opt(0, 0, 0).unwrap_or_default();
+    let county = filter.county.as_deref().map(|c| {
+        let c = c.trim();
+        c.strip_suffix(" County").unwrap_or(c).to_string()
+    });
+    let zip = filter.zip.as_deref().map(normalize_postal_code);
+
+    let events = conn
+        .prepare(
+            "SELECT o.id, p.address_line, p.city, p.state_abbr, p.postal_code, p.list_price,
+                    o.starts_at, o.ends_at, o.first_seen_at, o.cancelled_at
+             FROM property_open_houses o
+             JOIN properties p ON p.id = o.property_id
+             WHERE o.starts_at >= ?1
+               AND (?2 IS NULL OR p.state_abbr = ?2)
+               AND (?3 IS NULL OR p.county_name = ?3 COLLATE NOCASE
+                    OR p.county_name = ?3 || ' County' COLLATE NOCASE)
+               AND (?4 IS NULL OR substr(p.postal_code, 1, 5) = ?4)
+             ORDER BY o.starts_at, o.id",
+        )?
+        .query_map(
+            params![
+                from,
+                filter.state.as_deref().map(str::to_uppercase),
+                county,
+                zip
+            ],
+            |r| {
+                Ok(OpenHouseEvent {
+                    id: r.get(0)?,
+                    address_line: r.get(1)?,
+                    city: r.get(2)?,
+                    state_abbr: r.get(3)?,
+                    postal_code: r.get(4)?,
+                    list_price: r.get(5)?,
+                    starts_at: r.get(6)?,
+                    ends_at: r.get(7)?,
+                    first_seen_at: r.get(8)?,
+                    cancelled_at: r.get(9)?,
+                })
+            },
+        )?
+        .collect::<rusqlite::Result<_>>()?;
+    Ok(events)
+}
+
+/// Issues a new calendar feed token for one of the user's areas, retiring
+/// any earlier token for that area; links for other areas keep working.
+/// `area` is the link's filter query, empty for everywhere. Only the hash is
+/// stored, so the link can't be shown again later.
+pub fn rotate_feed_token(
+    conn: &Connection,
+    user_id: i64,
+    area: &str,
+    now: i64,
+) -> Result<String, ServerError> {
+    let mut raw = [0u8; 32];
+    OsRng.fill_bytes(&mut raw);
+    let token = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(raw);
+    let hash = Sha256::digest(token.as_bytes());
+
+    conn.execute(
+        "INSERT INTO calendar_feeds (user_id, area, token_hash, created_at) VALUES (?, ?, ?, ?)
+         ON CONFLICT(user_id, area) DO UPDATE SET
+            token_hash = excluded.token_hash, created_at = excluded.created_at",
+        params![user_id, area, hash.as_slice(), now],
+    )?;
+    Ok(token)
+}
+
+/// The user a calendar feed token belongs to and the area it was issued
+/// for, if it's current.
+pub fn feed_for_token(
+    conn: &Connection,
+    token: &str,
+) -> Result<Option<(i64, OpenHouseFilter)>, ServerError> {
+    let hash = Sha256::digest(token.as_bytes());
+    let feed: Option<(i64, String)> = conn
+        .query_row(
+            "SELECT user_id, area FROM calendar_feeds WHERE token_hash = ?",
+            params![hash.as_slice()],
+            |r| Ok((r.get(0)?, r.get(1)?)),
+        )
+        .optional()?;
+    Ok(feed.map(|(user_id, area)| (user_id, OpenHouseFilter::from_query(&area))))
+}
@@ -1,36 +1,88 @@
 // Force recompile to ensure schema changes are picked up
+use crate::db::change_events::{listing_state, record_change_events};
 use crate::db::connection::Database;
+use crate::db::cycles::refresh_listing_cycles;
+use crate::db::rentals::save_rental;
+use crate::domain::address::address_key;
 use crate::domain::changes::ChangeViewModel;
-use crate::domain::logic::derive_canonical_status;
-use crate::domain::property::{PropertyChange, ScrapedProperty, TrackedProperty};
+use crate::domain::events::{events_between, ChangeEventKind, ListingState};
+use crate::domain::history::{BACK_ON_MARKET, MARKET_STATUS_FIELD, OFF_MARKET, ON_MARKET, SOLD};
+use crate::domain::property::{
+    OpenHouseSlot, PropertyChange, ScrapedProperty, TrackedProperty, REALTOR_SITE,
+};
+use crate::domain::property_page::{PropertyPageViewModel, SourceLink, TimelineEntry};
 use crate::errors::ServerError;
 use crate::scraper::models::Property as ScraperProperty;
+use crate::scraper::scraper::SearchKind;
 use chrono::{NaiveDateTime, Utc};
 use rusqlite::{params, Connection, OptionalExtension, Result as RusqliteResult};
 
+/// What saving one scraped listing did.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SaveOutcome {
+    /// First time we've seen this property.
+    New,
+    /// Known property with new tracked values, or seen under a new listing id.
+    Changed,
+    /// Known listing, nothing changed since it was last seen.
+    Unchanged,
+    /// Failed validation and wasn't saved; carries the validation error.
+    Skipped(String),
+}
+
+/// History field for open houses being added and cancelled.
+pub const OPEN_HOUSE_FIELD: &str = "open_house";
+pub const OPEN_HOUSE_CANCELLED: &str = "cancelled";
+
 /// Main entry point for saving scraped data.
 ///
 /// This function orchestrates the entire change-tracking process. It takes raw
 /// scraped data, converts it into a clean domain model, and then, within a single
 /// database transaction, processes each property to identify, log, and store any
 /// changes from its previously known state.
+///
+/// Returns one outcome per input listing, in input order.
 pub fn save_scraped_properties(
     db: &Database,
     scraper_properties: &[ScraperProperty],
-) -> Result<(), ServerError> {
+) -> Result<Vec<SaveOutcome>, ServerError> {
+    save_scraped_properties_at(db, scraper_properties, Utc::now().naive_utc())
+}
+
+/// Same as `save_scraped_properties`, with history stamped at `observed_at`
+/// instead of now. Used when replaying archived pages.
+pub fn save_scraped_properties_at(
+    db: &Database,
+    scraper_properties: &[ScraperProperty],
+    observed_at: NaiveDateTime,
+) -> Result<Vec<SaveOutcome>, ServerError> {
     // First, convert the raw, nested scraper models into our clean, flattened
     // domain models. This validates that we have the necessary data to proceed.
-    let properties: Vec<ScrapedProperty> = scraper_properties
+    // Listings that fail come back as `Skipped` so the caller can quarantine them.
+    let properties: Vec<Result<ScrapedProperty, String>> = scraper_properties
         .iter()
-        .filter_map(|p| match ScrapedProperty::from_scraper_property(p) {
-            Ok(sp) => Some(sp),
-            Err(e) => {
-                eprintln!("Skipping property due to validation error: {}", e);
-                None
-            }
-        })
+        .map(ScrapedProperty::from_scraper_property)
         .collect();
+    save_converted(db, &properties, observed_at)
+}
 
+/// Saves listings a source adapter has already converted, with history
+/// stamped at `observed_at`.
+pub fn save_listings_at(
+    db: &Database,
+    listings: &[ScrapedProperty],
+    observed_at: NaiveDateTime,
+) -> Result<Vec<SaveOutcome>, ServerError> {
+    let properties: Vec<Result<ScrapedProperty, String>> =
+        listings.iter().cloned().map(Ok).collect();
+    save_converted(db, &properties, observed_at)
+}
+
+fn save_converted(
+    db: &Database,
+    properties: &[Result<ScrapedProperty, String>],
+    observed_at: NaiveDateTime,
+) -> Result<Vec<SaveOutcome>, ServerError> {
     // Perform the entire operation within a single database transaction to ensure
     // that our main table and history log remain perfectly consistent.
     db.with_conn(|conn| {
@@ -38,11 +90,18 @@ pub fn save_scraped_properties(
             .transaction()
             .map_err(|e| ServerError::DbError(e.to_string()))?;
 
-        for prop in &properties {
-            process_one_property(&tx, prop)?;
+        let mut outcomes = Vec::with_capacity(properties.len());
+        for prop in properties {
+            outcomes.push(match prop {
+                Ok(prop) if prop.is_rental() => save_rental(&tx, prop, observed_at)?,
+                Ok(prop) => process_one_property(&tx, prop, observed_at)?,
+                Err(e) => SaveOutcome::Skipped(e.clone()),
+            });
         }
 
-        tx.commit().map_err(|e| ServerError::DbError(e.to_string()))
+        tx.commit()
+            .map_err(|e| ServerError::DbError(e.to_string()))?;
+        Ok(outcomes)
     })
 }
 
@@ -50,67 +109,491 @@ pub fn save_scraped_properties(
 fn process_one_property(
     tx: &Connection,
     scraped_prop: &ScrapedProperty,
-) -> Result<(), ServerError> {
-    let now = Utc::now().naive_utc();
-
+    now: NaiveDateTime,
+) -> Result<SaveOutcome, ServerError> {
     // Attempt to find an existing property in our database using its unique address.
     let maybe_tracked_prop = find_property_by_address(tx, scraped_prop)?;
 
     match maybe_tracked_prop {
         // If the property already exists, we check for changes.
-        Some(tracked_prop) => {
-            let changes = tracked_prop.diff(scraped_prop);
+        Some((tracked_prop, was_delisted)) => {
+            // A replayed page older than what we already know about the
+            // property would roll its current state back, so it's ignored.
+            if last_observed_at(tx, tracked_prop.id)?.is_some_and(|seen| now < seen) {
+                return Ok(SaveOutcome::Unchanged);
+            }
+            let mut current = scraped_prop.clone();
+            if current.site != REALTOR_SITE {
+                current.fill_unreported(&tracked_prop);
+            }
+            let changes = tracked_prop.diff(&current);
             if !changes.is_empty() {
-                log_changes(tx, &changes)?;
-                update_property(tx, tracked_prop.id, scraped_prop, now)?;
+                log_changes(tx, &changes, now)?;
+                update_property(tx, tracked_prop.id, &current, now)?;
+            }
+            record_reports(tx, tracked_prop.id, scraped_prop, now)?;
+            update_descriptive_fields(tx, tracked_prop.id, scraped_prop)?;
+            // Always update the source's `last_seen_at` timestamp. A listing
+            // id we haven't seen before (e.g. a relist) gets linked here.
+            let source_known = update_source(tx, scraped_prop, now)?;
+            if !source_known {
+                insert_or_update_source(tx, tracked_prop.id, scraped_prop, now)?;
+            }
+            let relisted = relist_if_delisted(tx, tracked_prop.id, &current, now)?;
+            // A sighting that doesn't relist it leaves the property off the market.
+            let mut listing_state = ListingState::from_scraped(&current);
+            listing_state.delisted = was_delisted && !relisted;
+            let events = events_between(
+                Some(&ListingState::from_tracked(&tracked_prop, was_delisted)),
+                &listing_state,
+            );
+            record_change_events(tx, tracked_prop.id, &events, Some(scraped_prop.site), now)?;
+            if !changes.is_empty() || relisted {
+                refresh_listing_cycles(tx, tracked_prop.id)?;
+            }
+            let open_houses_changed = match &scraped_prop.open_houses {
+                Some(slots) => sync_open_houses(tx, tracked_prop.id, slots, now)?,
+                None => false,
+            };
+
+            if changes.is_empty() && source_known && !relisted && !open_houses_changed {
+                Ok(SaveOutcome::Unchanged)
+            } else {
+                Ok(SaveOutcome::Changed)
             }
-            // Always update the source's `last_seen_at` timestamp.
-            update_source(tx, scraped_prop, now)?;
         }
         // If it's a new property, we create it and log its initial state.
         None => {
             let property_id = insert_property(tx, scraped_prop, now)?;
             log_initial_state(tx, property_id, scraped_prop, now)?;
+            let events = events_between(None, &ListingState::from_scraped(scraped_prop));
+            record_change_events(tx, property_id, &events, Some(scraped_prop.site), now)?;
+            refresh_listing_cycles(tx, property_id)?;
+            record_reports(tx, property_id, scraped_prop, now)?;
             insert_or_update_source(tx, property_id, scraped_prop, now)?;
+            if let Some(slots) = &scraped_prop.open_houses {
+                sync_open_houses(tx, property_id, slots, now)?;
+            }
+            Ok(SaveOutcome::New)
         }
     }
+}
+
+/// Credits the listing's site on the current history row of every field it
+/// reports a matching value for. A site is recorded once per row, the first
+/// time it reported that value.
+fn record_reports(
+    tx: &Connection,
+    property_id: i64,
+    prop: &ScrapedProperty,
+    now: NaiveDateTime,
+) -> Result<(), ServerError> {
+    let mut stmt = tx.prepare_cached(
+        r#"
+        INSERT OR IGNORE INTO property_history_sources (history_id, site, source_listing_id, observed_at)
+        SELECT h.id, ?3, ?4, ?5 FROM property_history h
+        WHERE h.id = (
+            SELECT MAX(id) FROM property_history WHERE property_id = ?1 AND field_name = ?2
+        ) AND h.current_value = ?6
+        "#,
+    )?;
+    for (field, value) in prop.reported_values() {
+        stmt.execute(params![
+            property_id,
+            field,
+            prop.site,
+            &prop.source_listing_id,
+            now,
+            value
+        ])?;
+    }
     Ok(())
 }
 
-/// Finds a property by its unique address components.
+/// When we last learned anything about a property: its latest sighting by
+/// any source, or its delisting if that came later.
+fn last_observed_at(
+    tx: &Connection,
+    property_id: i64,
+) -> Result<Option<NaiveDateTime>, ServerError> {
+    Ok(tx.query_row(
+        "SELECT MAX(
+            p.last_seen_at,
+            COALESCE(p.delisted_at, p.last_seen_at),
+            COALESCE((SELECT MAX(s.last_seen_at) FROM property_sources s
+                      WHERE s.property_id = p.id), p.last_seen_at)
+         ) FROM properties p WHERE p.id = ?",
+        [property_id],
+        |r| r.get(0),
+    )?)
+}
+
+/// Clears a delisting on a property that has been seen again. Seen for sale,
+/// it's logged "back on market"; seen sold, it's logged as sold, since it
+/// never came back. Any other sighting leaves it delisted. Returns whether
+/// the delisting was cleared.
+fn relist_if_delisted(
+    tx: &Connection,
+    property_id: i64,
+    prop: &ScrapedProperty,
+    now: NaiveDateTime,
+) -> Result<bool, ServerError> {
+    let status = ListingState::from_scraped(prop).canonical_status();
+    let market_status = if SearchKind::ForSale.shows_status(status) {
+        BACK_ON_MARKET
+    } else if SearchKind::Sold.shows_status(status) {
+        SOLD
+    } else {
+        return Ok(false);
+    };
+    let relisted = tx.execute(
+        "UPDATE properties SET delisted_at = NULL, last_seen_at = ?1
+         WHERE id = ?2 AND delisted_at IS NOT NULL",
+        params![now, property_id],
+    )? > 0;
+    if relisted {
+        log_market_status(tx, property_id, OFF_MARKET, market_status, now)?;
+    }
+    Ok(relisted)
+}
+
+fn log_market_status(
+    tx: &Connection,
+    property_id: i64,
+    previous: &str,
+    current: &str,
+    now: NaiveDateTime,
+) -> Result<(), ServerError> {
+    tx.execute(
+        "INSERT INTO property_history (property_id, observed_at, field_name, previous_value, current_value)
+         VALUES (?, ?, ?, ?, ?)",
+        params![property_id, now, MARKET_STATUS_FIELD, previous, current],
+    )?;
+    Ok(())
+}
+
+/// Brings a property's open houses in line with the ones its listing shows:
+/// new slots are added, upcoming ones it no longer shows are cancelled, and
+/// both are logged to history. Slots already past are left alone. Returns
+/// whether anything was added or cancelled.
+fn sync_open_houses(
+    tx: &Connection,
+    property_id: i64,
+    slots: &[OpenHouseSlot],
+    now: NaiveDateTime,
+) -> Result<bool, ServerError> {
+    let mut changed = false;
+    for slot in slots {
+        let existing: Option<(i64, bool)> = tx
+            .query_row(
+                "SELECT id, cancelled_at IS NOT NULL FROM property_open_houses
+                 WHERE property_id = ? AND starts_at = ?",
+                params![property_id, slot.starts_at],
+                |r| Ok((r.get(0)?, r.get(1)?)),
+            )
+            .optional()?;
+        match existing {
+            Some((id, cancelled)) => {
+                tx.execute(
+                    "UPDATE property_open_houses
+                     SET ends_at = ?, time_zone = ?, last_seen_at = ?, cancelled_at = NULL
+                     WHERE id = ?",
+                    params![slot.ends_at, slot.time_zone, now, id],
+                )?;
+                if !cancelled {
+                    continue;
+                }
+            }
+            None => {
+                tx.execute(
+                    "INSERT INTO property_open_houses
+                        (property_id, starts_at, ends_at, time_zone, first_seen_at, last_seen_at)
+                     VALUES (?, ?, ?, ?, ?, ?)",
+                    params![
+                        property_id,
+                        slot.starts_at,
+                        slot.ends_at,
+                        slot.time_zone,
+                        now,
+                        now
+                    ],
+                )?;
+            }
+        }
+        log_open_house(tx, property_id, None, &slot.label(), now)?;
+        changed = true;
+    }
+
+    let dropped: Vec<(i64, OpenHouseSlot)> = tx
+        .prepare(
+            "SELECT id, starts_at, ends_at, time_zone FROM property_open_houses
+             WHERE property_id = ? AND cancelled_at IS NULL AND starts_at > ?",
+        )?
+        .query_map(params![property_id, now], |r| {
+            Ok((
+                r.get(0)?,
+                OpenHouseSlot {
+                    starts_at: r.get(1)?,
+                    ends_at: r.get(2)?,
+                    time_zone: r.get(3)?,
+                },
+            ))
+        })?
+        .filter(|row| {
+            row.as_ref().map_or(true, |(_, o)| {
+                !slots.iter().any(|s| s.starts_at == o.starts_at)
+            })
+        })
+        .collect::<RusqliteResult<_>>()?;
+    for (id, slot) in dropped {
+        tx.execute(
+            "UPDATE property_open_houses SET cancelled_at = ? WHERE id = ?",
+            params![now, id],
+        )?;
+        log_open_house(
+            tx,
+            property_id,
+            Some(&slot.label()),
+            OPEN_HOUSE_CANCELLED,
+            now,
+        )?;
+        changed = true;
+    }
+    Ok(changed)
+}
+
+fn log_open_house(
+    tx: &Connection,
+    property_id: i64,
+    previous: Option<&str>,
+    current: &str,
+    now: NaiveDateTime,
+) -> Result<(), ServerError> {
+    tx.execute(
+        "INSERT INTO property_history (property_id, observed_at, field_name, previous_value, current_value)
+         VALUES (?, ?, ?, ?, ?)",
+        params![property_id, now, OPEN_HOUSE_FIELD, previous, current],
+    )?;
+    Ok(())
+}
+
+/// Fills in `address_key` for rows stored before it existed.
+///
+/// Rows whose addresses normalize to the same key are merged into the oldest
+/// one: history, sources and list rows move over and the duplicate is
+/// deleted. The survivor keeps its own current state, which the next scrape
+/// brings up to date. Only unkeyed rows are touched, so this is a no-op once
+/// it has run. Returns how many duplicates were merged away.
+pub fn rekey_properties(conn: &mut Connection) -> Result<usize, ServerError> {
+    let tx = conn.transaction()?;
+
+    let rows: Vec<(i64, String, String, String)> = tx
+        .prepare(
+            "SELECT id, address_line, city, postal_code FROM properties
+             WHERE address_key IS NULL ORDER BY id",
+        )?
+        .query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?)))?
+        .collect::<RusqliteResult<_>>()?;
+
+    let mut merged = 0;
+    for (id, line, city, postal_code) in rows {
+        let key = address_key(&line, &city, &postal_code);
+        let survivor: Option<i64> = tx
+            .query_row(
+                "SELECT id FROM properties WHERE address_key = ?",
+                [&key],
+                |r| r.get(0),
+            )
+            .optional()?;
+        match survivor {
+            Some(into_id) => {
+                merge_property_into(&tx, id, into_id)?;
+                merged += 1;
+            }
+            None => {
+                tx.execute(
+                    "UPDATE properties SET address_key = ? WHERE id = ?",
+                    params![key, id],
+                )?;
+            }
+        }
+    }
+
+    // Tables created before the key existed still carry the old raw-address
+    // constraint; the index gives them the new one.
+    tx.execute_batch(
+        "CREATE UNIQUE INDEX IF NOT EXISTS idx_properties_address_key ON properties(address_key);",
+    )?;
+    tx.commit()?;
+    Ok(merged)
+}
+
+/// Moves everything that points at property `from_id` onto `into_id`, widens
+/// the survivor's seen window to cover both, and deletes `from_id`.
+/// Returns how many (history, source) rows moved.
+pub(crate) fn merge_property_into(
+    tx: &Connection,
+    from_id: i64,
+    into_id: i64,
+) -> Result<(usize, usize), ServerError> {
+    let mut moved = [0; 4];
+    for (i, table) in [
+        "property_history",
+        "property_sources",
+        "list_rows",
+        "change_events",
+    ]
+    .iter()
+    .enumerate()
+    {
+        moved[i] = tx.execute(
+            &format!("UPDATE {table} SET property_id = ?1 WHERE property_id = ?2"),
+            params![into_id, from_id],
+        )?;
+    }
+    // Open houses the survivor already has stay as they are.
+    tx.execute(
+        "UPDATE OR IGNORE property_open_houses SET property_id = ?1 WHERE property_id = ?2",
+        params![into_id, from_id],
+    )?;
+    // The survivor keeps its own detail page enrichment if it has one.
+    let details_moved = tx.execute(
+        "UPDATE property_details SET property_id = ?1 WHERE property_id = ?2
+           AND NOT EXISTS (SELECT 1 FROM property_details WHERE property_id = ?1)",
+        params![into_id, from_id],
+    )?;
+    if details_moved > 0 {
+        tx.execute(
+            "UPDATE property_price_events SET property_id = ?1 WHERE property_id = ?2",
+            params![into_id, from_id],
+        )?;
+    }
+    tx.execute(
+        "UPDATE properties SET
+            first_seen_at = MIN(first_seen_at, (SELECT first_seen_at FROM properties WHERE id = ?2)),
+            last_seen_at = MAX(last_seen_at, (SELECT last_seen_at FROM properties WHERE id = ?2))
+         WHERE id = ?1",
+        params![into_id, from_id],
+    )?;
+    // Cycles are rebuilt from the combined history.
+    tx.execute(
+        "DELETE FROM listing_cycles WHERE property_id = ?",
+        [from_id],
+    )?;
+    tx.execute("DELETE FROM properties WHERE id = ?", [from_id])?;
+    refresh_listing_cycles(tx, into_id)?;
+    Ok((moved[0], moved[1]))
+}
+
+/// Marks every listed property in the state that a `kind` search would show
+/// but that wasn't seen since `seen_since` as off market, logging the
+/// transition. Sold homes missed by a for-sale sweep are left alone, and so
+/// are properties only another site has listed, since a Realtor sweep says
+/// nothing about those. Only call this after a sweep that covered the whole
+/// state, or listings outside the sweep would be delisted too. Returns how
+/// many properties were delisted.
+pub fn mark_unseen_delisted(
+    conn: &mut Connection,
+    state_abbr: &str,
+    kind: SearchKind,
+    seen_since: NaiveDateTime,
+    now: NaiveDateTime,
+) -> Result<usize, ServerError> {
+    let tx = conn.transaction()?;
+
+    let ids: Vec<i64> = tx
+        .prepare(
+            "SELECT p.id FROM properties p
+             WHERE p.state_abbr = ?1 AND p.delisted_at IS NULL AND p.last_seen_at < ?2
+               AND EXISTS (
+                   SELECT 1 FROM property_sources s
+                   WHERE s.property_id = p.id AND s.site = ?3
+               )
+               AND NOT EXISTS (
+                   SELECT 1 FROM property_sources s
+                   WHERE s.property_id = p.id AND s.last_seen_at >= ?2
+               )",
+        )?
+        .query_map(params![state_abbr, seen_since, REALTOR_SITE], |r| r.get(0))?
+        .collect::<RusqliteResult<_>>()?;
+
+    let mut delisted = 0;
+    for id in &ids {
+        let Some(before) = listing_state(&tx, *id)? else {
+            continue;
+        };
+        if !kind.shows_status(before.canonical_status()) {
+            continue;
+        }
+        let after = ListingState {
+            delisted: true,
+            ..before.clone()
+        };
+        record_change_events(&tx, *id, &events_between(Some(&before), &after), None, now)?;
+        tx.execute(
+            "UPDATE properties SET delisted_at = ? WHERE id = ?",
+            params![now, id],
+        )?;
+        log_market_status(&tx, *id, ON_MARKET, OFF_MARKET, now)?;
+        refresh_listing_cycles(&tx, *id)?;
+        delisted += 1;
+    }
+
+    tx.commit()?;
+    Ok(delisted)
+}
+
+/// Finds a property by its normalized address key, along with whether it's
+/// currently delisted.
 fn find_property_by_address(
     conn: &Connection,
     prop: &ScrapedProperty,
-) -> Result<Option<TrackedProperty>, ServerError> {
+) -> Result<Option<(TrackedProperty, bool)>, ServerError> {
     conn.query_row(
-        r#"
-        SELECT
-            id, status, list_price, sold_price, sold_date, is_pending, is_contingent,
-            is_new_listing, is_foreclosure, is_price_reduced, is_coming_soon
-        FROM properties
-        WHERE address_line = ?1 AND city = ?2 AND postal_code = ?3
-        "#,
-        params![&prop.address_line, &prop.city, &prop.postal_code],
+        &format!("SELECT {TRACKED_COLUMNS}, delisted_at FROM properties WHERE address_key = ?1"),
+        params![prop.address_key()],
         |row| {
-            Ok(TrackedProperty {
-                id: row.get(0)?,
-                status: row.get(1)?,
-                list_price: row.get(2)?,
-                sold_price: row.get(3)?,
-                sold_date: row.get(4)?,
-                is_pending: row.get(5)?,
-                is_contingent: row.get(6)?,
-                is_new_listing: row.get(7)?,
-                is_foreclosure: row.get(8)?,
-                is_price_reduced: row.get(9)?,
-                is_coming_soon: row.get(10)?,
-            })
+            let delisted_at: Option<NaiveDateTime> = row.get("delisted_at")?;
+            Ok((tracked_property_from_row(row)?, delisted_at.is_some()))
         },
     )
     .optional()
     .map_err(|e| ServerError::DbError(e.to_string()))
 }
 
+/// The columns `tracked_property_from_row` reads, in order.
+const TRACKED_COLUMNS: &str = "
+    id, status, list_price, sold_price, sold_date, is_pending, is_contingent,
+    is_new_listing, is_foreclosure, is_price_reduced, is_coming_soon,
+    beds, baths, sqft, lot_sqft, property_type, year_built, garage, stories,
+    is_new_construction";
+
+/// Reads a `TrackedProperty` from a row that starts with `TRACKED_COLUMNS`.
+fn tracked_property_from_row(row: &rusqlite::Row) -> RusqliteResult<TrackedProperty> {
+    Ok(TrackedProperty {
+        id: row.get(0)?,
+        status: row.get(1)?,
+        list_price: row.get(2)?,
+        sold_price: row.get(3)?,
+        sold_date: row.get(4)?,
+        is_pending: row.get(5)?,
+        is_contingent: row.get(6)?,
+        is_new_listing: row.get(7)?,
+        is_foreclosure: row.get(8)?,
+        is_price_reduced: row.get(9)?,
+        is_coming_soon: row.get(10)?,
+        beds: row.get(11)?,
+        baths: row.get(12)?,
+        sqft: row.get(13)?,
+        lot_sqft: row.get(14)?,
+        property_type: row.get(15)?,
+        year_built: row.get(16)?,
+        garage: row.get(17)?,
+        stories: row.get(18)?,
+        is_new_construction: row.get(19)?,
+    })
+}
+
 /// Inserts a new record into the main `properties` table.
 fn insert_property(
     tx: &Connection,
@@ -123,8 +606,13 @@ fn insert_property(
             address_line, city, postal_code, state_abbr, county_name,
             status, list_price, sold_price, sold_date, is_pending, is_contingent,
             is_new_listing, is_foreclosure, is_price_reduced, is_coming_soon,
-            first_seen_at, last_seen_at
-        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)
+            beds, baths, sqft, lot_sqft, property_type, year_built, garage, stories,
+            is_new_construction, subdivision, latitude, longitude, county_fips,
+            first_seen_at, last_seen_at, address_key
+        ) VALUES (
+            ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15,
+            ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25, ?26, ?27, ?28, ?29, ?30, ?31
+        )
         "#,
     )?;
     stmt.execute(params![
@@ -143,8 +631,22 @@ fn insert_property(
         &prop.is_foreclosure,
         &prop.is_price_reduced,
         &prop.is_coming_soon,
+        &prop.beds,
+        &prop.baths,
+        &prop.sqft,
+        &prop.lot_sqft,
+        &prop.property_type,
+        &prop.year_built,
+        &prop.garage,
+        &prop.stories,
+        &prop.is_new_construction,
+        &prop.subdivision,
+        &prop.latitude,
+        &prop.longitude,
+        &prop.county_fips,
         now,
         now,
+        prop.address_key(),
     ])?;
     Ok(tx.last_insert_rowid())
 }
@@ -161,8 +663,10 @@ fn update_property(
         UPDATE properties SET
             status = ?1, list_price = ?2, sold_price = ?3, sold_date = ?4,
             is_pending = ?5, is_contingent = ?6, is_new_listing = ?7, is_foreclosure = ?8,
-            is_price_reduced = ?9, is_coming_soon = ?10, last_seen_at = ?11
-        WHERE id = ?12
+            is_price_reduced = ?9, is_coming_soon = ?10, last_seen_at = ?11,
+            beds = ?12, baths = ?13, sqft = ?14, lot_sqft = ?15, property_type = ?16,
+            year_built = ?17, garage = ?18, stories = ?19, is_new_construction = ?20
+        WHERE id = ?21
         "#,
         params![
             &prop.status,
@@ -176,6 +680,45 @@ fn update_property(
             &prop.is_price_reduced,
             &prop.is_coming_soon,
             now,
+            &prop.beds,
+            &prop.baths,
+            &prop.sqft,
+            &prop.lot_sqft,
+            &prop.property_type,
+            &prop.year_built,
+            &prop.garage,
+            &prop.stories,
+            &prop.is_new_construction,
+            property_id,
+        ],
+    )?;
+    Ok(())
+}
+
+/// Keeps the untracked descriptive fields current. A field the listing
+/// leaves out keeps its stored value. Only writes when one of them actually
+/// differs.
+fn update_descriptive_fields(
+    tx: &Connection,
+    property_id: i64,
+    prop: &ScrapedProperty,
+) -> Result<(), ServerError> {
+    tx.execute(
+        r#"
+        UPDATE properties SET
+            subdivision = COALESCE(?1, subdivision), latitude = COALESCE(?2, latitude),
+            longitude = COALESCE(?3, longitude), county_fips = COALESCE(?4, county_fips)
+        WHERE id = ?5
+          AND (COALESCE(?1, subdivision) IS NOT subdivision
+               OR COALESCE(?2, latitude) IS NOT latitude
+               OR COALESCE(?3, longitude) IS NOT longitude
+               OR COALESCE(?4, county_fips) IS NOT county_fips)
+        "#,
+        params![
+            &prop.subdivision,
+            &prop.latitude,
+            &prop.longitude,
+            &prop.county_fips,
             property_id,
         ],
     )?;
@@ -183,14 +726,17 @@ fn update_property(
 }
 
 /// Inserts a batch of changes into the `property_history` table.
-fn log_changes(tx: &Connection, changes: &[PropertyChange]) -> RusqliteResult<()> {
+fn log_changes(
+    tx: &Connection,
+    changes: &[PropertyChange],
+    now: NaiveDateTime,
+) -> RusqliteResult<()> {
     let mut stmt = tx.prepare(
         r#"
         INSERT INTO property_history (property_id, observed_at, field_name, previous_value, current_value)
         VALUES (?1, ?2, ?3, ?4, ?5)
         "#,
     )?;
-    let now = Utc::now().naive_utc();
     for change in changes {
         stmt.execute(params![
             change.property_id,
@@ -217,25 +763,10 @@ fn log_initial_state(
         "#,
     )?;
 
-    macro_rules! log_field {
-        ($field:ident, $field_name:expr) => {
-            if let Some(value) = &prop.$field {
-                stmt.execute(params![property_id, now, $field_name, value.to_string(),])?;
-            }
-        };
+    for (field, value) in prop.reported_values() {
+        stmt.execute(params![property_id, now, field, value])?;
     }
 
-    log_field!(status, "status");
-    log_field!(list_price, "list_price");
-    log_field!(sold_price, "sold_price");
-    log_field!(sold_date, "sold_date");
-    log_field!(is_pending, "is_pending");
-    log_field!(is_contingent, "is_contingent");
-    log_field!(is_new_listing, "is_new_listing");
-    log_field!(is_foreclosure, "is_foreclosure");
-    log_field!(is_price_reduced, "is_price_reduced");
-    log_field!(is_coming_soon, "is_coming_soon");
-
     Ok(())
 }
 
@@ -248,13 +779,20 @@ fn insert_or_update_source(
 ) -> RusqliteResult<()> {
     tx.execute(
         r#"
-        INSERT INTO property_sources (property_id, source_name, source_listing_id, first_seen_at, last_seen_at)
-        VALUES (?1, ?2, ?3, ?4, ?5)
+        INSERT INTO property_sources (property_id, source_name, source_listing_id, site, first_seen_at, last_seen_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6)
         ON CONFLICT(source_name, source_listing_id) DO UPDATE SET
             property_id = excluded.property_id,
             last_seen_at = excluded.last_seen_at
         "#,
-        params![property_id, &prop.source_name, &prop.source_listing_id, now, now],
+        params![
+            property_id,
+            &prop.source_name,
+            &prop.source_listing_id,
+            prop.site,
+            now,
+            now
+        ],
     )?;
     Ok(())
 }
@@ -268,91 +806,90 @@ pub fn get_change_events_for_export(
 ) -> Result<Vec<ChangeViewModel>, ServerError> {
     let mut stmt = conn.prepare(
         r#"
-        -- This complex query is designed to construct our "Change Event" log.
-        -- We select not just the history event itself, but also the full context
-        -- of the property's state *at the time of the change*. To do this, we
-        -- have to join the history table with the properties table.
+        -- One row per typed change event, with the property's current
+        -- attributes alongside for filtering.
         SELECT
-            h.observed_at,
-            h.field_name,
-            h.previous_value,
-            h.current_value,
+            e.observed_at,
+            e.event_type,
+            e.previous_status,
+            e.current_status,
+            e.previous_price,
+            e.current_price,
+            e.site AS reported_by,
+            p.id AS property_id,
             p.address_line,
             p.city,
             p.state_abbr,
             p.postal_code,
             p.county_name,
             p.list_price,
-            p.sold_date,
             p.status AS raw_status, -- The status from the scraper
-            p.is_pending,
-            p.is_contingent,
-            p.is_coming_soon,
             p.is_new_listing,
             p.is_price_reduced,
-            p.is_foreclosure
-        FROM property_history h
-        JOIN properties p ON h.property_id = p.id
+            p.is_foreclosure,
+            p.beds,
+            p.baths,
+            p.sqft,
+            p.lot_sqft,
+            p.property_type,
+            p.year_built,
+            p.garage,
+            p.stories,
+            p.subdivision,
+            p.is_new_construction,
+            p.latitude,
+            p.longitude,
+            p.county_fips,
+            d.agent_name AS listing_agent,
+            d.brokerage,
+            c.cycle_number,
+            COALESCE(c.days_on_market, CAST(julianday('now') - julianday(c.listed_at) AS INTEGER))
+                AS days_on_market,
+            c.days_to_pending,
+            c.price_cuts
+        FROM change_events e
+        JOIN properties p ON e.property_id = p.id
+        LEFT JOIN property_details d ON d.property_id = p.id
+        -- The latest cycle to start by the time of the change.
+        LEFT JOIN listing_cycles c ON c.id = (
+            SELECT lc.id FROM listing_cycles lc
+            WHERE lc.property_id = p.id AND lc.listed_at <= e.observed_at
+            ORDER BY lc.listed_at DESC LIMIT 1
+        )
         WHERE
             p.state_abbr = ?1
-            AND strftime('%Y', h.observed_at) = ?2
-            -- We only want to create primary spreadsheet rows for these two change types
-            AND h.field_name IN ('status', 'list_price')
-        ORDER BY h.observed_at DESC
+            AND strftime('%Y', e.observed_at) = ?2
+        ORDER BY e.observed_at DESC, e.id DESC
         "#,
     )?;
 
     let year_str = year.to_string();
     let rows = stmt.query_map(params![state, year_str], |row| {
-        let field_name: String = row.get("field_name")?;
-
-        // --- Business Logic for Canonical Status ---
-        // We derive the canonical status for both the previous and current state
-        // based on our business rules, creating a much cleaner output.
-        let previous_value_str: Option<String> = row.get("previous_value")?;
-        let current_value_str: String = row.get("current_value")?;
-
-        // Extract fields explicitly to ensure type safety and handle NULLs
-        let sold_date: Option<NaiveDateTime> = row.get("sold_date")?;
-        let raw_status: Option<String> = row.get("raw_status")?;
-        let is_pending: bool = row.get::<_, Option<bool>>("is_pending")?.unwrap_or(false);
-        let is_contingent: bool = row
-            .get::<_, Option<bool>>("is_contingent")?
-            .unwrap_or(false);
-        let is_coming_soon: bool = row
-            .get::<_, Option<bool>>("is_coming_soon")?
-            .unwrap_or(false);
-
-        let current_status = derive_canonical_status(
-            &sold_date,
-            is_pending,
-            is_contingent,
-            is_coming_soon,
-            &raw_status,
-        );
+        let event_type: String = row.get("event_type")?;
+        let kind = ChangeEventKind::parse(&event_type).ok_or_else(|| {
+            rusqlite::Error::FromSqlConversionFailure(
+                1,
+                rusqlite::types::Type::Text,
+                format!("unknown change event type '{event_type}'").into(),
+            )
+        })?;
+        let previous_status: Option<String> = row.get("previous_status")?;
+        let current_status: String = row.get("current_status")?;
+        let previous_price: Option<i64> = row.get("previous_price")?;
+        let current_price: Option<i64> = row.get("current_price")?;
 
-        let (change_type, previous_value, current_value) = if field_name == "status" {
-            // For status changes, the previous and current values are our derived statuses.
-            let prev_status = derive_canonical_status(
-                &sold_date,
-                // For previous state, we default flags to false as we don't have history for them in this row.
-                false,
-                false,
-                false,
-                &previous_value_str,
-            );
+        // Price events show the prices; everything else shows the statuses.
+        let (previous_value, current_value) = if kind.is_price_change() {
             (
-                "Status Change".to_string(),
-                prev_status.to_string(),
-                current_status.to_string(),
+                previous_price.map(|p| p.to_string()).unwrap_or_default(),
+                current_price.map(|p| p.to_string()).unwrap_or_default(),
             )
         } else {
-            // list_price
-            (
-                "Price Change".to_string(),
-                previous_value_str.unwrap_or_default(),
-                current_value_str,
-            )
+            (previous_status.unwrap_or_default(), current_status.clone())
+        };
+        let price_reduction = match (kind, previous_price, current_price) {
+            (ChangeEventKind::PriceDrop, Some(prev), Some(curr)) => Some(prev - curr),
+            _ => None,
         };
 
         // --- Populate the rest of the ViewModel ---
@@ -360,18 +897,7 @@ pub fn get_change_events_for_export(
         let city: String = row.get("city")?;
         let state_abbr: Option<String> = row.get("state_abbr")?;
         let postal_code: String = row.get("postal_code")?;
-
-        let price_reduction = if change_type == "Price Change" {
-            let prev = previous_value.parse::<i64>().ok();
-            let curr = current_value.parse::<i64>().ok();
-            if let (Some(p), Some(c)) = (prev, curr) {
-                Some(p - c)
-            } else {
-                None
-            }
-        } else {
-            None
-        };
+        let raw_status: Option<String> = row.get("raw_status")?;
 
         let address_full = format!(
             "{}, {}, {} {}",
@@ -383,9 +909,10 @@ pub fn get_change_events_for_export(
 
         Ok(ChangeViewModel {
             change_date: row.get("observed_at")?,
-            change_type,
+            kind,
             previous_value,
             current_value,
+            property_id: row.get("property_id")?,
             address_full,
             address_line,
             city,
@@ -393,7 +920,19 @@ pub fn get_change_events_for_export(
             postal_code,
             county_name: row.get("county_name")?,
             price: row.get("list_price")?,
-            canonical_status: current_status.to_string(),
+            canonical_status: current_status,
+            beds: row.get("beds")?,
+            baths: row.get("baths")?,
+            sqft: row.get("sqft")?,
+            lot_sqft: row.get("lot_sqft")?,
+            property_type: row.get("property_type")?,
+            year_built: row.get("year_built")?,
+            garage: row.get("garage")?,
+            stories: row.get("stories")?,
+            subdivision: row.get("subdivision")?,
+            latitude: row.get("latitude")?,
+            longitude: row.get("longitude")?,
+            county_fips: row.get("county_fips")?,
             is_new_listing: row
                 .get::<_, Option<bool>>("is_new_listing")?
                 .unwrap_or(false),
@@ -403,8 +942,20 @@ pub fn get_change_events_for_export(
             is_foreclosure: row
                 .get::<_, Option<bool>>("is_foreclosure")?
                 .unwrap_or(false),
+            is_new_construction: row
+                .get::<_, Option<bool>>("is_new_construction")?
+                .unwrap_or(false),
             is_ready_to_build: raw_status.as_deref() == Some("ready_to_build"),
             price_reduction,
+            listing_cycle: row.get("cycle_number")?,
+            days_on_market: row.get("days_on_market")?,
+            days_to_pending: row.get("days_to_pending")?,
+            price_cuts: row.get("price_cuts")?,
+            reported_by: row
+                .get::<_, Option<String>>("reported_by")?
+                .unwrap_or_default(),
+            listing_agent: row.get("listing_agent")?,
+            brokerage: row.get("brokerage")?,
         })
     })?;
 
@@ -416,19 +967,20 @@ pub fn get_change_events_for_export(
 }
 
 /// Updates the `last_seen_at` timestamp for an existing source listing.
+/// Returns whether a source row for this listing already existed.
 fn update_source(
     tx: &Connection,
     prop: &ScrapedProperty,
     now: NaiveDateTime,
-) -> RusqliteResult<()> {
-    tx.execute(
+) -> RusqliteResult<bool> {
+    let updated = tx.execute(
         r#"
         UPDATE property_sources SET last_seen_at = ?1
         WHERE source_name = ?2 AND source_listing_id = ?3
         "#,
         params![now, &prop.source_name, &prop.source_listing_id],
     )?;
-    Ok(())
+    Ok(updated > 0)
 }
 
 /// Gets a list of distinct years from the property history for the filter dropdown.
@@ -436,7 +988,7 @@ pub fn get_distinct_change_years(conn: &Connection) -> Result<Vec<String>, Serve
     let mut stmt = conn.prepare(
         r#"
         SELECT DISTINCT strftime('%Y', observed_at) AS year
-        FROM property_history
+        FROM change_events
         ORDER BY year DESC
         "#,
     )?;
@@ -474,3 +1026,87 @@ pub fn get_recent_changes(
 
     Ok(recent_changes)
 }
+
+/// Loads everything the property page shows, or `None` for an unknown id.
+pub fn get_property_page(
+    conn: &Connection,
+    property_id: i64,
+) -> Result<Option<PropertyPageViewModel>, ServerError> {
+    let page = conn
+        .query_row(
+            &format!(
+                "SELECT {TRACKED_COLUMNS},
+                        p.address_line, p.city, p.state_abbr, p.postal_code, p.county_name,
+                        p.subdivision, p.first_seen_at, p.last_seen_at, p.delisted_at,
+                        d.agent_name, d.brokerage, d.detail_url
+                 FROM properties p
+                 LEFT JOIN property_details d ON d.property_id = p.id
+                 WHERE p.id = ?1"
+            ),
+            [property_id],
+            |row| {
+                let tracked = tracked_property_from_row(row)?;
+                let delisted_at: Option<NaiveDateTime> = row.get("delisted_at")?;
+                Ok(PropertyPageViewModel {
+                    address_line: row.get("address_line")?,
+                    city: row.get("city")?,
+                    state_abbr: row.get("state_abbr")?,
+                    postal_code: row.get("postal_code")?,
+                    county_name: row.get("county_name")?,
+                    subdivision: row.get("subdivision")?,
+                    canonical_status: ListingState::from_tracked(&tracked, delisted_at.is_some())
+                        .canonical_status(),
+                    tracked,
+                    first_seen_at: row.get("first_seen_at")?,
+                    last_seen_at: row.get("last_seen_at")?,
+                    delisted_at,
+                    listing_agent: row.get("agent_name")?,
+                    brokerage: row.get("brokerage")?,
+                    detail_url: row.get("detail_url")?,
+                    sources: Vec::new(),
+                    timeline: Vec::new(),
+                })
+            },
+        )
+        .optional()?;
+    let Some(mut page) = page else {
+        return Ok(None);
+    };
+
+    page.sources = conn
+        .prepare(
+            "SELECT source_name, source_listing_id, first_seen_at, last_seen_at
+             FROM property_sources WHERE property_id = ?1
+             ORDER BY first_seen_at, id",
+        )?
+        .query_map([property_id], |row| {
+            Ok(SourceLink {
+                source_name: row.get(0)?,
+                source_listing_id: row.get(1)?,
+                first_seen_at: row.get(2)?,
+                last_seen_at: row.get(3)?,
+            })
+        })?
+        .collect::<RusqliteResult<_>>()?;
+
+    page.timeline = conn
+        .prepare(
+            "SELECT h.observed_at, h.field_name, h.previous_value, h.current_value,
+                    (SELECT GROUP_CONCAT(hs.site, ', ') FROM property_history_sources hs
+                     WHERE hs.history_id = h.id)
+             FROM property_history h WHERE h.property_id = ?1
+             ORDER BY h.observed_at, h.id",
+        )?
+        .query_map([property_id], |row| {
+            Ok(TimelineEntry {
+                observed_at: row.get(0)?,
+                field_name: row.get(1)?,
+                previous_value: row.get(2)?,
+                current_value: row.get(3)?,
+                reported_by: row.get::<_, Option<String>>(4)?.unwrap_or_default(),
+            })
+        })?
+        .collect::<RusqliteResult<_>>()?;
+
+    Ok(Some(page))
+}
@@ -0,0 +1,153 @@
+use crate::db::connection::Database;
+use crate::db::properties::{save_scraped_properties, SaveOutcome};
+use crate::errors::ServerError;
+use crate::scraper::Property;
+use rusqlite::{params, Connection, OptionalExtension};
+
+/// A scraped listing that couldn't be saved, kept for inspection and
+/// re-processing after a model fix.
+#[derive(Debug)]
+pub struct QuarantinedRecord {
+    pub id: i64,
+    pub scrape_run_id: Option<i64>,
+    pub page_number: Option<i64>,
+    /// `deserialize` or `validate`.
+    pub stage: String,
+    pub raw_json: String,
+    pub error: String,
+    pub created_at: i64,
+    pub resolved_at: Option<i64>,
+}
+
+const QUARANTINE_COLUMNS: &str =
+    "id, scrape_run_id, page_number, stage, raw_json, error, created_at, resolved_at";
+
+fn map_record(row: &rusqlite::Row) -> rusqlite::Result<QuarantinedRecord> {
+    Ok(QuarantinedRecord {
+        id: row.get(0)?,
+        scrape_run_id: row.get(1)?,
+        page_number: row.get(2)?,
+        stage: row.get(3)?,
+        raw_json: row.get(4)?,
+        error: row.get(5)?,
+        created_at: row.get(6)?,
+        resolved_at: row.get(7)?,
+    })
+}
+
+/// Stores a rejected record. Replaying an archived page doesn't duplicate a
+/// record already quarantined from that page, open or resolved.
+pub fn quarantine_record(
+    conn: &Connection,
+    run_id: Option<i64>,
+    page_number: Option<usize>,
+    stage: &str,
+    raw_json: &str,
+    error: &str,
+    now: i64,
+) -> Result<(), ServerError> {
+    conn.execute(
+        "INSERT INTO scrape_quarantine (scrape_run_id, page_number, stage, raw_json, error, created_at)
+         SELECT ?1, ?2, ?3, ?4, ?5, ?6
+         WHERE NOT EXISTS (
+            SELECT 1 FROM scrape_quarantine
+            WHERE scrape_run_id IS ?1 AND page_number IS ?2 AND raw_json = ?4
+         )",
+        params![run_id, page_number, stage, raw_json, error, now],
+    )?;
+    Ok(())
+}
+
+/// Unresolved records, newest first.
+pub fn list_open_quarantine(
+    conn: &Connection,
+    limit: usize,
+) -> Result<Vec<QuarantinedRecord>, ServerError> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {QUARANTINE_COLUMNS} FROM scrape_quarantine
+         WHERE resolved_at IS NULL ORDER BY id DESC LIMIT ?"
+    ))?;
+    let rows = stmt.query_map(params![limit], map_record)?;
+
+    let mut records = Vec::new();
+    for r in rows {
+        records.push(r?);
+    }
+    Ok(records)
+}
+
+pub fn count_open_quarantine(conn: &Connection) -> Result<i64, ServerError> {
+    Ok(conn.query_row(
+        "SELECT COUNT(*) FROM scrape_quarantine WHERE resolved_at IS NULL",
+        [],
+        |r| r.get(0),
+    )?)
+}
+
+pub fn get_quarantined(
+    conn: &Connection,
+    id: i64,
+) -> Result<Option<QuarantinedRecord>, ServerError> {
+    Ok(conn
+        .query_row(
+            &format!("SELECT {QUARANTINE_COLUMNS} FROM scrape_quarantine WHERE id = ?"),
+            params![id],
+            map_record,
+        )
+        .optional()?)
+}
+
+/// Runs a quarantined record through the pipeline again. If it saves, the
+/// record is marked resolved; otherwise its error and stage are updated and
+/// it stays in quarantine. Returns whether it saved.
+pub fn reprocess_quarantined(db: &Database, id: i64, now: i64) -> Result<bool, ServerError> {
+    let record = db
+        .with_conn(|conn| get_quarantined(conn, id))?
+        .ok_or(ServerError::NotFound)?;
+    if record.resolved_at.is_some() {
+        return Ok(true);
+    }
+
+    let failure = match serde_json::from_str::<Property>(&record.raw_json) {
+        Err(e) => Some(("deserialize", e.to_string())),
+        Ok(property) => match save_scraped_properties(db, &[property])?.pop() {
+            Some(SaveOutcome::Skipped(e)) => Some(("validate", e)),
+            _ => None,
+        },
+    };
+
+    db.with_conn(|conn| {
+        match &failure {
+            Some((stage, error)) => conn.execute(
+                "UPDATE scrape_quarantine SET stage = ?, error = ? WHERE id = ?",
+                params![stage, error, id],
+            )?,
+            None => conn.execute(
+                "UPDATE scrape_quarantine SET resolved_at = ? WHERE id = ?",
+                params![now, id],
+            )?,
+        };
+        Ok(())
+    })?;
+    Ok(failure.is_none())
+}
+
+/// Re-processes every open record. Returns (saved, still failing).
+pub fn reprocess_all_quarantined(db: &Database, now: i64) -> Result<(usize, usize), ServerError> {
+    let ids: Vec<i64> = db.with_conn(|conn| {
+        let mut stmt =
+            conn.prepare("SELECT id FROM scrape_quarantine WHERE resolved_at IS NULL ORDER BY id")?;
+        let ids = stmt
+            .query_map([], |r| r.get(0))?
+            .collect::<Result<Vec<i64>, _>>()?;
+        Ok(ids)
+    })?;
+
+    let mut saved = 0;
+    for id in &ids {
+        if reprocess_quarantined(db, *id, now)? {
+            saved += 1;
+        }
+    }
+    Ok((saved, ids.len() - saved))
+}
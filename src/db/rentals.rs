@@ -0,0 +1,103 @@
+use crate::db::properties::SaveOutcome;
+use crate::domain::property::ScrapedProperty;
+use crate::errors::ServerError;
+use chrono::NaiveDateTime;
+use rusqlite::{params, Connection, OptionalExtension};
+
+/// Saves a rental listing to `rental_listings`, logging its rent to
+/// `rental_history` when first seen and whenever it changes. Rentals never
+/// touch `properties`, even when the same house is also for sale.
+pub(crate) fn save_rental(
+    tx: &Connection,
+    prop: &ScrapedProperty,
+    now: NaiveDateTime,
+) -> Result<SaveOutcome, ServerError> {
+    let key = prop.address_key();
+    let existing: Option<(i64, Option<i64>, String, NaiveDateTime)> = tx
+        .query_row(
+            "SELECT id, monthly_rent, source_listing_id, last_seen_at
+             FROM rental_listings WHERE address_key = ?",
+            [&key],
+            |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?)),
+        )
+        .optional()?;
+
+    let Some((rental_id, previous_rent, listing_id, last_seen_at)) = existing else {
+        tx.execute(
+            "INSERT INTO rental_listings (
+                address_key, address_line, city, postal_code, state_abbr, county_name,
+                source_name, source_listing_id, monthly_rent, beds, baths, sqft, property_type,
+                first_seen_at, last_seen_at
+             ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            params![
+                key,
+                prop.address_line,
+                prop.city,
+                prop.postal_code,
+                prop.state_abbr,
+                prop.county_name,
+                prop.source_name,
+                prop.source_listing_id,
+                prop.list_price,
+                prop.beds,
+                prop.baths,
+                prop.sqft,
+                prop.property_type,
+                now,
+                now
+            ],
+        )?;
+        log_rent(tx, tx.last_insert_rowid(), None, prop.list_price, now)?;
+        return Ok(SaveOutcome::New);
+    };
+
+    // A replayed page older than the latest sighting would roll the rent back.
+    if now < last_seen_at {
+        return Ok(SaveOutcome::Unchanged);
+    }
+
+    tx.execute(
+        "UPDATE rental_listings SET
+            source_name = ?1, source_listing_id = ?2, monthly_rent = ?3,
+            beds = COALESCE(?4, beds), baths = COALESCE(?5, baths),
+            sqft = COALESCE(?6, sqft), property_type = COALESCE(?7, property_type),
+            last_seen_at = ?8
+         WHERE id = ?9",
+        params![
+            prop.source_name,
+            prop.source_listing_id,
+            prop.list_price,
+            prop.beds,
+            prop.baths,
+            prop.sqft,
+            prop.property_type,
+            now,
+            rental_id
+        ],
+    )?;
+    let rent_changed = previous_rent != prop.list_price;
+    if rent_changed {
+        log_rent(tx, rental_id, previous_rent, prop.list_price, now)?;
+    }
+
+    if rent_changed || listing_id != prop.source_listing_id {
+        Ok(SaveOutcome::Changed)
+    } else {
+        Ok(SaveOutcome::Unchanged)
+    }
+}
+
+fn log_rent(
+    tx: &Connection,
+    rental_id: i64,
+    previous: Option<i64>,
+    current: Option<i64>,
+    now: NaiveDateTime,
+) -> Result<(), ServerError> {
+    tx.execute(
+        "INSERT INTO rental_history (rental_id, observed_at, previous_rent, current_rent)
+         VALUES (?, ?, ?, ?)",
+        params![rental_id, now, previous, current],
+    )?;
+    Ok(())
+}
@@ -0,0 +1,184 @@
+use crate::errors::ServerError;
+use rusqlite::{params, Connection, OptionalExtension};
+
+#[derive(Debug)]
+pub struct ScrapeSchedule {
+    pub id: i64,
+    pub state: String,
+    /// `state`, `county` or `zip`.
+    pub shard_by: String,
+    /// `full` or `incremental`.
+    pub mode: String,
+    pub stop_after: Option<i64>,
+    /// `for_sale`, `sold` or `for_rent`.
+    pub search_kind: String,
+    pub cadence_hours: i64,
+    pub enabled: bool,
+    pub last_run_at: Option<i64>,
+    pub next_run_at: i64,
+    pub last_attempt_at: Option<i64>,
+    /// Why the last attempt to start it failed, until one succeeds.
+    pub last_error: Option<String>,
+}
+
+/// Fields an admin can set on a schedule.
+pub struct ScheduleForm {
+    pub state: String,
+    pub shard_by: String,
+    pub mode: String,
+    pub stop_after: Option<i64>,
+    pub search_kind: String,
+    pub cadence_hours: i64,
+}
+
+const SCHEDULE_COLUMNS: &str =
+    "id, state, shard_by, mode, stop_after, cadence_hours, enabled, last_run_at, next_run_at, search_kind, last_attempt_at, last_error";
+
+fn map_schedule(row: &rusqlite::Row) -> rusqlite::Result<ScrapeSchedule> {
+    Ok(ScrapeSchedule {
+        id: row.get(0)?,
+        state: row.get(1)?,
+        shard_by: row.get(2)?,
+        mode: row.get(3)?,
+        stop_after: row.get(4)?,
+        cadence_hours: row.get(5)?,
+        enabled: row.get(6)?,
+        last_run_at: row.get(7)?,
+        next_run_at: row.get(8)?,
+        search_kind: row.get(9)?,
+        last_attempt_at: row.get(10)?,
+        last_error: row.get(11)?,
+    })
+}
+
+/// Creates an enabled schedule whose first run is `first_run_at`.
+pub fn create_schedule(
+    conn: &Connection,
+    form: &ScheduleForm,
+    first_run_at: i64,
+    now: i64,
+) -> Result<i64, ServerError> {
+    conn.execute(
+        "INSERT INTO scrape_schedules (state, shard_by, mode, stop_after, search_kind, cadence_hours, enabled, next_run_at, created_at)
+         VALUES (?, ?, ?, ?, ?, ?, 1, ?, ?)",
+        params![
+            form.state,
+            form.shard_by,
+            form.mode,
+            form.stop_after,
+            form.search_kind,
+            form.cadence_hours,
+            first_run_at,
+            now
+        ],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Updates a schedule. A changed cadence takes effect from the last run.
+pub fn update_schedule(conn: &Connection, id: i64, form: &ScheduleForm) -> Result<(), ServerError> {
+    let updated = conn.execute(
+        "UPDATE scrape_schedules SET
+            state = ?1, shard_by = ?2, mode = ?3, stop_after = ?4, cadence_hours = ?5,
+            next_run_at = CASE WHEN last_run_at IS NULL THEN next_run_at
+                               ELSE last_run_at + ?5 * 3600 END,
+            search_kind = ?7
+         WHERE id = ?6",
+        params![
+            form.state,
+            form.shard_by,
+            form.mode,
+            form.stop_after,
+            form.cadence_hours,
+            id,
+            form.search_kind
+        ],
+    )?;
+    if updated == 0 {
+        return Err(ServerError::NotFound);
+    }
+    Ok(())
+}
+
+pub fn set_schedule_enabled(conn: &Connection, id: i64, enabled: bool) -> Result<(), ServerError> {
+    let updated = conn.execute(
+        "UPDATE scrape_schedules SET enabled = ? WHERE id = ?",
+        params![enabled, id],
+    )?;
+    if updated == 0 {
+        return Err(ServerError::NotFound);
+    }
+    Ok(())
+}
+
+pub fn get_schedule(conn: &Connection, id: i64) -> Result<Option<ScrapeSchedule>, ServerError> {
+    Ok(conn
+        .query_row(
+            &format!("SELECT {SCHEDULE_COLUMNS} FROM scrape_schedules WHERE id = ?"),
+            params![id],
+            map_schedule,
+        )
+        .optional()?)
+}
+
+pub fn list_schedules(conn: &Connection) -> Result<Vec<ScrapeSchedule>, ServerError> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {SCHEDULE_COLUMNS} FROM scrape_schedules ORDER BY state, id"
+    ))?;
+    let rows = stmt.query_map([], map_schedule)?;
+
+    let mut schedules = Vec::new();
+    for r in rows {
+        schedules.push(r?);
+    }
+    Ok(schedules)
+}
+
+/// Enabled schedules that are due and whose state has no scrape running.
+pub fn due_schedules(conn: &Connection, now: i64) -> Result<Vec<ScrapeSchedule>, ServerError> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {SCHEDULE_COLUMNS} FROM scrape_schedules s
+         WHERE enabled = 1 AND next_run_at <= ?
+           AND NOT EXISTS (
+               SELECT 1 FROM scrape_runs r WHERE r.state = s.state AND r.finished_at IS NULL
+           )
+         ORDER BY next_run_at"
+    ))?;
+    let rows = stmt.query_map(params![now], map_schedule)?;
+
+    let mut schedules = Vec::new();
+    for r in rows {
+        schedules.push(r?);
+    }
+    Ok(schedules)
+}
+
+/// Records that a schedule was launched and moves it to its next slot.
+pub fn mark_schedule_run(conn: &Connection, id: i64, now: i64) -> Result<(), ServerError> {
+    conn.execute(
+        "UPDATE scrape_schedules SET
+            last_run_at = ?1, last_attempt_at = ?1, last_error = NULL,
+            next_run_at = ?1 + cadence_hours * 3600
+         WHERE id = ?2",
+        params![now, id],
+    )?;
+    Ok(())
+}
+
+/// Records why a schedule couldn't be started and moves it to its next slot,
+/// so a failure that won't clear up on its own is retried once per cadence
+/// rather than every tick.
+pub fn mark_schedule_failed(
+    conn: &Connection,
+    id: i64,
+    error: &str,
+    now: i64,
+) -> Result<(), ServerError> {
+    conn.execute(
+        "UPDATE scrape_schedules SET
+            last_attempt_at = ?1, last_error = ?2, next_run_at = ?1 + cadence_hours * 3600
+         WHERE id = ?3",
+        params![now, error, id],
+    )?;
+    Ok(())
+}
@@ -1,5 +1,5 @@
 use crate::errors::ServerError;
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OptionalExtension};
 
 #[derive(Debug)]
 pub struct ScrapeRun {
@@ -11,12 +11,95 @@ pub struct ScrapeRun {
     pub properties_seen: Option<i64>,
     pub success: Option<bool>,
     pub error_message: Option<String>,
+    pub shard: Option<String>,
+    pub search_url: Option<String>,
+    /// Result count the site reported for the search, if it did.
+    pub expected_total: Option<i64>,
+    /// Set when the search hit the page cap and was split into price bands.
+    pub split_into: Option<i64>,
+    pub parent_run_id: Option<i64>,
+    /// Estimated share of `expected_total` actually scraped, 0.0–1.0.
+    pub coverage: Option<f64>,
+    pub mode: Option<String>,
+    pub stop_after: Option<i64>,
+    /// Fetches that came back as a captcha or bot wall, retries included.
+    pub blocked_attempts: Option<i64>,
+    pub block_rate: Option<f64>,
+    /// `for_sale`, `sold` or `for_rent`; `None` for runs from before it was
+    /// recorded, which were all for sale.
+    pub search_kind: Option<String>,
+    /// The `ScraperConfig` the run used, as JSON.
+    pub config: Option<String>,
+    /// Whether the search hit the page cap before reaching the last result.
+    pub capped: Option<bool>,
 }
 
-pub fn start_scrape_run(conn: &Connection, state_abbr: &str, now: i64) -> Result<i64, ServerError> {
+const RUN_COLUMNS: &str = "id, state, started_at, finished_at, pages_fetched, properties_seen, success, error_message, shard, search_url, expected_total, split_into, parent_run_id, coverage, mode, stop_after, blocked_attempts, block_rate, search_kind, config, capped";
+
+fn map_run(row: &rusqlite::Row) -> rusqlite::Result<ScrapeRun> {
+    Ok(ScrapeRun {
+        id: row.get(0)?,
+        state: row.get(1)?,
+        started_at: row.get(2)?,
+        finished_at: row.get(3)?,
+        pages_fetched: row.get(4)?,
+        properties_seen: row.get(5)?,
+        success: row.get(6)?,
+        error_message: row.get(7)?,
+        shard: row.get(8)?,
+        search_url: row.get(9)?,
+        expected_total: row.get(10)?,
+        split_into: row.get(11)?,
+        parent_run_id: row.get(12)?,
+        coverage: row.get(13)?,
+        mode: row.get(14)?,
+        stop_after: row.get(15)?,
+        blocked_attempts: row.get(16)?,
+        block_rate: row.get(17)?,
+        search_kind: row.get(18)?,
+        config: row.get(19)?,
+        capped: row.get(20)?,
+    })
+}
+
+/// What a new `scrape_runs` row describes.
+pub struct NewScrapeRun<'a> {
+    pub state_abbr: &'a str,
+    /// `None` for a whole-state run, otherwise a label such as
+    /// `county:Salt Lake County`.
+    pub shard: Option<&'a str>,
+    pub search_url: &'a str,
+    /// Links a price band to the run that was split.
+    pub parent_run_id: Option<i64>,
+    /// `full` or `incremental`.
+    pub mode: &'a str,
+    /// Incremental mode's known-listing streak limit.
+    pub stop_after: Option<usize>,
+    /// `for_sale`, `sold` or `for_rent`.
+    pub search_kind: &'a str,
+    /// The effective `ScraperConfig`, as JSON.
+    pub config: Option<&'a str>,
+}
+
+pub fn start_scrape_run(
+    conn: &Connection,
+    run: &NewScrapeRun,
+    now: i64,
+) -> Result<i64, ServerError> {
     conn.execute(
-        "INSERT INTO scrape_runs (state, started_at, success) VALUES (?, ?, 0)",
-        params![state_abbr, now],
+        "INSERT INTO scrape_runs (state, shard, search_url, parent_run_id, mode, stop_after, search_kind, config, started_at, success)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, 0)",
+        params![
+            run.state_abbr,
+            run.shard,
+            run.search_url,
+            run.parent_run_id,
+            run.mode,
+            run.stop_after,
+            run.search_kind,
+            run.config,
+            now
+        ],
     )
     .map_err(|e| ServerError::DbError(e.to_string()))?;
     Ok(conn.last_insert_rowid())
@@ -40,22 +123,13 @@ pub fn end_scrape_run(
 
 pub fn get_recent_scrapes(conn: &Connection) -> Result<Vec<ScrapeRun>, ServerError> {
     let mut stmt = conn
-        .prepare("SELECT id, state, started_at, finished_at, pages_fetched, properties_seen, success, error_message FROM scrape_runs ORDER BY started_at DESC LIMIT 50")
+        .prepare(&format!(
+            "SELECT {RUN_COLUMNS} FROM scrape_runs ORDER BY started_at DESC, id DESC LIMIT 50"
+        ))
         .map_err(|e| ServerError::DbError(e.to_string()))?;
 
     let rows = stmt
-        .query_map([], |row| {
-            Ok(ScrapeRun {
-                id: row.get(0)?,
-                state: row.get(1)?,
-                started_at: row.get(2)?,
-                finished_at: row.get(3)?,
-                pages_fetched: row.get(4)?,
-                properties_seen: row.get(5)?,
-                success: row.get(6)?,
-                error_message: row.get(7)?,
-            })
-        })
+        .query_map([], map_run)
         .map_err(|e| ServerError::DbError(e.to_string()))?;
 
     let mut runs = Vec::new();
@@ -64,3 +138,227 @@ pub fn get_recent_scrapes(conn: &Connection) -> Result<Vec<ScrapeRun>, ServerErr
     }
     Ok(runs)
 }
+
+pub fn get_scrape_run(conn: &Connection, run_id: i64) -> Result<Option<ScrapeRun>, ServerError> {
+    conn.query_row(
+        &format!("SELECT {RUN_COLUMNS} FROM scrape_runs WHERE id = ?"),
+        params![run_id],
+        map_run,
+    )
+    .optional()
+    .map_err(|e| ServerError::DbError(e.to_string()))
+}
+
+/// Stores the reported result count and the coverage it implies for a
+/// finished run.
+pub fn set_expected_total(
+    conn: &Connection,
+    run_id: i64,
+    expected_total: Option<usize>,
+) -> Result<(), ServerError> {
+    conn.execute(
+        "UPDATE scrape_runs SET
+            expected_total = ?1,
+            coverage = CASE WHEN ?1 > 0 THEN MIN(1.0, COALESCE(properties_seen, 0) * 1.0 / ?1) END
+         WHERE id = ?2",
+        params![expected_total, run_id],
+    )
+    .map_err(|e| ServerError::DbError(e.to_string()))?;
+    Ok(())
+}
+
+/// Flags a run whose search hit the page cap.
+pub fn set_capped(conn: &Connection, run_id: i64) -> Result<(), ServerError> {
+    conn.execute(
+        "UPDATE scrape_runs SET capped = 1 WHERE id = ?",
+        params![run_id],
+    )
+    .map_err(|e| ServerError::DbError(e.to_string()))?;
+    Ok(())
+}
+
+pub fn set_split_into(conn: &Connection, run_id: i64, bands: usize) -> Result<(), ServerError> {
+    conn.execute(
+        "UPDATE scrape_runs SET split_into = ? WHERE id = ?",
+        params![bands, run_id],
+    )
+    .map_err(|e| ServerError::DbError(e.to_string()))?;
+    Ok(())
+}
+
+/// Recomputes a split run's coverage from its price bands once they finish.
+/// Bands without a reported total count only what they actually saw.
+pub fn roll_up_coverage(conn: &Connection, run_id: i64) -> Result<(), ServerError> {
+    conn.execute(
+        "UPDATE scrape_runs SET coverage = MIN(1.0, (
+            SELECT COALESCE(SUM(COALESCE(c.coverage * c.expected_total, c.properties_seen, 0)), 0)
+            FROM scrape_runs c
+            WHERE c.parent_run_id = scrape_runs.id
+         ) * 1.0 / expected_total)
+         WHERE id = ? AND expected_total > 0",
+        params![run_id],
+    )
+    .map_err(|e| ServerError::DbError(e.to_string()))?;
+    Ok(())
+}
+
+/// Records the outcome of one page. A page retried on resume overwrites its
+/// earlier failed attempt, but its blocked attempts accumulate.
+pub fn record_scrape_page(
+    conn: &Connection,
+    run_id: i64,
+    page_number: usize,
+    page_url: &str,
+    success: bool,
+    properties_found: usize,
+    blocked_attempts: u32,
+) -> Result<(), ServerError> {
+    conn.execute(
+        "INSERT INTO scrape_run_pages (scrape_run_id, page_number, page_url, success, properties_found, blocked_attempts)
+         VALUES (?, ?, ?, ?, ?, ?)
+         ON CONFLICT(scrape_run_id, page_number) DO UPDATE SET
+            page_url = excluded.page_url,
+            success = excluded.success,
+            properties_found = excluded.properties_found,
+            blocked_attempts = blocked_attempts + excluded.blocked_attempts",
+        params![run_id, page_number, page_url, success, properties_found, blocked_attempts],
+    )
+    .map_err(|e| ServerError::DbError(e.to_string()))?;
+    Ok(())
+}
+
+pub fn last_successful_page(conn: &Connection, run_id: i64) -> Result<Option<usize>, ServerError> {
+    conn.query_row(
+        "SELECT MAX(page_number) FROM scrape_run_pages WHERE scrape_run_id = ? AND success = 1",
+        params![run_id],
+        |r| r.get(0),
+    )
+    .map_err(|e| ServerError::DbError(e.to_string()))
+}
+
+/// Successful page count and properties found across every attempt of a run.
+pub fn get_page_totals(conn: &Connection, run_id: i64) -> Result<(usize, usize), ServerError> {
+    conn.query_row(
+        "SELECT COUNT(*), COALESCE(SUM(properties_found), 0) FROM scrape_run_pages WHERE scrape_run_id = ? AND success = 1",
+        params![run_id],
+        |r| Ok((r.get(0)?, r.get(1)?)),
+    )
+    .map_err(|e| ServerError::DbError(e.to_string()))
+}
+
+/// Properties found, and the most found on one page, across the run's
+/// successful pages before `page`.
+pub fn get_page_totals_before(
+    conn: &Connection,
+    run_id: i64,
+    page: usize,
+) -> Result<(usize, usize), ServerError> {
+    conn.query_row(
+        "SELECT COALESCE(SUM(properties_found), 0), COALESCE(MAX(properties_found), 0)
+         FROM scrape_run_pages WHERE scrape_run_id = ? AND success = 1 AND page_number < ?",
+        params![run_id, page],
+        |r| Ok((r.get(0)?, r.get(1)?)),
+    )
+    .map_err(|e| ServerError::DbError(e.to_string()))
+}
+
+/// Rolls the per-page block counts up onto the run, with the share of
+/// fetches that were blocked.
+pub fn set_block_stats(conn: &Connection, run_id: i64) -> Result<(), ServerError> {
+    conn.execute(
+        "UPDATE scrape_runs SET
+            blocked_attempts = p.blocked,
+            block_rate = CASE WHEN p.blocked + p.fetched > 0
+                THEN CAST(p.blocked AS REAL) / (p.blocked + p.fetched) END
+         FROM (SELECT COALESCE(SUM(blocked_attempts), 0) AS blocked,
+                      COALESCE(SUM(success), 0) AS fetched
+               FROM scrape_run_pages WHERE scrape_run_id = ?1) AS p
+         WHERE id = ?1",
+        params![run_id],
+    )
+    .map_err(|e| ServerError::DbError(e.to_string()))?;
+    Ok(())
+}
+
+/// Coverage a run needs for its sweep to count as complete. Site totals
+/// drift a little while a sweep is running.
+const COMPLETE_SWEEP_COVERAGE: f64 = 0.98;
+
+/// Whether every run succeeded and covered its whole search, so anything
+/// not seen during them really is gone from the site.
+pub fn sweep_is_complete(conn: &Connection, run_ids: &[i64]) -> Result<bool, ServerError> {
+    if run_ids.is_empty() {
+        return Ok(false);
+    }
+    for run_id in run_ids {
+        if !run_is_complete(conn, *run_id)? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// A run is complete when it succeeded and either reached the end of its
+/// results or was split into price bands that are all complete. A capped
+/// run whose coverage is unknown can't be trusted to have seen everything.
+fn run_is_complete(conn: &Connection, run_id: i64) -> Result<bool, ServerError> {
+    let Some(run) = get_scrape_run(conn, run_id)? else {
+        return Ok(false);
+    };
+    let covered = match run.coverage {
+        Some(coverage) => coverage >= COMPLETE_SWEEP_COVERAGE,
+        None => run.capped != Some(true) || run.split_into.is_some(),
+    };
+    if run.success != Some(true) || !covered {
+        return Ok(false);
+    }
+    let Some(split_into) = run.split_into else {
+        return Ok(true);
+    };
+
+    let bands: Vec<i64> = conn
+        .prepare("SELECT id FROM scrape_runs WHERE parent_run_id = ?")?
+        .query_map(params![run_id], |r| r.get(0))?
+        .collect::<Result<_, _>>()?;
+    if bands.len() < split_into as usize {
+        return Ok(false);
+    }
+    for band in bands {
+        if !run_is_complete(conn, band)? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Marks a finished run as running again so it can be resumed.
+pub fn reopen_scrape_run(conn: &Connection, run_id: i64) -> Result<(), ServerError> {
+    conn.execute(
+        "UPDATE scrape_runs SET finished_at = NULL, success = 0, error_message = NULL WHERE id = ?",
+        params![run_id],
+    )
+    .map_err(|e| ServerError::DbError(e.to_string()))?;
+    Ok(())
+}
+
+/// Whether any run for the state hasn't finished yet.
+pub fn state_has_running_scrape(conn: &Connection, state_abbr: &str) -> Result<bool, ServerError> {
+    conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM scrape_runs WHERE state = ? AND finished_at IS NULL)",
+        params![state_abbr],
+        |r| r.get(0),
+    )
+    .map_err(|e| ServerError::DbError(e.to_string()))
+}
+
+/// Runs execute on threads inside the server process, so any run still open
+/// at startup died with the previous process. Marks them failed so they can
+/// be resumed and don't block their state. Returns how many were closed.
+pub fn fail_interrupted_runs(conn: &Connection, now: i64) -> Result<usize, ServerError> {
+    conn.execute(
+        "UPDATE scrape_runs SET finished_at = ?, success = 0, error_message = 'Interrupted by server restart'
+         WHERE finished_at IS NULL",
+        params![now],
+    )
+    .map_err(|e| ServerError::DbError(e.to_string()))
+}
@@ -0,0 +1,247 @@
+// src/domain/address.rs
+
+//! Address normalization for property identity.
+//!
+//! Listing sites spell the same address many ways ("123 North Main Street",
+//! "123 N. Main St"). The normalized key folds those together so a property is
+//! matched regardless of spelling, while the scraped form is kept for display.
+
+/// USPS street suffix abbreviations (Publication 28, Appendix C1), limited to
+/// the common ones we see in listings.
+const STREET_SUFFIXES: &[(&str, &str)] = &[
+    ("ALLEY", "ALY"),
+    ("AVENUE", "AVE"),
+    ("AV", "AVE"),
+    ("BOULEVARD", "BLVD"),
+    ("CANYON", "CYN"),
+    ("CIRCLE", "CIR"),
+    ("COURT", "CT"),
+    ("COVE", "CV"),
+    ("CROSSING", "XING"),
+    ("DRIVE", "DR"),
+    ("EXPRESSWAY", "EXPY"),
+    ("HEIGHTS", "HTS"),
+    ("HIGHWAY", "HWY"),
+    ("HOLLOW", "HOLW"),
+    ("LANE", "LN"),
+    ("PARKWAY", "PKWY"),
+    ("PLACE", "PL"),
+    ("PLAZA", "PLZ"),
+    ("POINT", "PT"),
+    ("RIDGE", "RDG"),
+    ("ROAD", "RD"),
+    ("SQUARE", "SQ"),
+    ("STREET", "ST"),
+    ("STR", "ST"),
+    ("TERRACE", "TER"),
+    ("TRAIL", "TRL"),
+    ("VIEW", "VW"),
+];
+
+const DIRECTIONALS: &[(&str, &str)] = &[
+    ("NORTH", "N"),
+    ("SOUTH", "S"),
+    ("EAST", "E"),
+    ("WEST", "W"),
+    ("NORTHEAST", "NE"),
+    ("NORTHWEST", "NW"),
+    ("SOUTHEAST", "SE"),
+    ("SOUTHWEST", "SW"),
+];
+
+/// Words that introduce a secondary unit. They all collapse to `#` in the key,
+/// since "Apt 4", "Unit 4" and "#4" at one street address are the same door.
+const UNIT_DESIGNATORS: &[&str] = &[
+    "#",
+    "APARTMENT",
+    "APT",
+    "BLDG",
+    "BUILDING",
+    "SPACE",
+    "SPC",
+    "STE",
+    "SUITE",
+    "UNIT",
+];
+
+/// The key a property is matched on: normalized street line, city and ZIP.
+pub fn address_key(address_line: &str, city: &str, postal_code: &str) -> String {
+    format!(
+        "{}|{}|{}",
+        normalize_address_line(address_line),
+        normalize_city(city),
+        normalize_postal_code(postal_code)
+    )
+}
+
+/// Uppercases, strips punctuation and abbreviates directionals, the street
+/// suffix and any unit designator.
+pub fn normalize_address_line(line: &str) -> String {
+    let tokens = tokenize(line);
+
+    // Split the street part from the unit part at the first designator.
+    let unit_at = tokens
+        .iter()
+        .skip(1)
+        .position(|t| UNIT_DESIGNATORS.contains(&t.as_str()))
+        .map(|i| i + 1);
+    let (street, unit) = match unit_at {
+        Some(i) => tokens.split_at(i),
+        None => (&tokens[..], &[][..]),
+    };
+
+    let mut out: Vec<String> = Vec::with_capacity(tokens.len());
+    let last = street.len().saturating_sub(1);
+    for (i, token) in street.iter().enumerate() {
+        let word = if i == last && i > 0 {
+            lookup(STREET_SUFFIXES, token).or_else(|| lookup(DIRECTIONALS, token))
+        } else if keeps_directional_as_name(street, i) {
+            None
+        } else {
+            lookup(DIRECTIONALS, token)
+        };
+        out.push(word.unwrap_or(token).to_string());
+    }
+
+    // "Apt 4", "Unit #4" and "#4" all become "# 4".
+    let unit_ids: Vec<&str> = unit
+        .iter()
+        .map(String::as_str)
+        .filter(|t| !UNIT_DESIGNATORS.contains(t))
+        .collect();
+    if !unit_ids.is_empty() {
+        out.push("#".to_string());
+        out.extend(unit_ids.iter().map(|t| t.to_string()));
+    }
+
+    out.join(" ")
+}
+
+/// Splits a normalized line into the street part and unit, e.g.
+/// "77 E 400 S # 4" -> ("77 E 400 S", Some("4")).
+pub fn split_unit(normalized_line: &str) -> (&str, Option<&str>) {
+    match normalized_line.split_once(" # ") {
+        Some((street, unit)) => (street, Some(unit)),
+        None => (normalized_line, None),
+    }
+}
+
+/// Uppercases and collapses whitespace; "Saint" and "St." read the same.
+pub fn normalize_city(city: &str) -> String {
+    tokenize(city)
+        .into_iter()
+        .map(|t| if t == "SAINT" { "ST".to_string() } else { t })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Five-digit ZIP: drops the +4 and restores leading zeros lost to
+/// spreadsheets ("2134" -> "02134"). Non-numeric codes are just trimmed.
+pub fn normalize_postal_code(postal_code: &str) -> String {
+    let zip = postal_code.trim();
+    let base = zip.split('-').next().unwrap_or("");
+    if !base.is_empty() && base.len() <= 5 && base.bytes().all(|b| b.is_ascii_digit()) {
+        format!("{:0>5}", base)
+    } else {
+        zip.to_uppercase()
+    }
+}
+
+/// Uppercase words with periods and commas removed. A leading `#` is split
+/// off its number so "#4" and "# 4" tokenize the same.
+fn tokenize(s: &str) -> Vec<String> {
+    let cleaned: String = s
+        .chars()
+        .filter(|c| *c != '.' && *c != ',')
+        .collect::<String>()
+        .to_uppercase();
+    let mut tokens = Vec::new();
+    for word in cleaned.split_whitespace() {
+        match word.strip_prefix('#') {
+            Some(rest) => {
+                tokens.push("#".to_string());
+                if !rest.is_empty() {
+                    tokens.push(rest.to_string());
+                }
+            }
+            None => tokens.push(word.to_string()),
+        }
+    }
+    tokens
+}
+
+/// A directional is part of the street name when nothing else names the
+/// street, e.g. "123 North St" or "40 West Ave".
+fn keeps_directional_as_name(street: &[String], i: usize) -> bool {
+    if lookup(DIRECTIONALS, &street[i]).is_none() {
+        return false;
+    }
+    let last = street.len() - 1;
+    street.iter().enumerate().skip(1).all(|(j, t)| {
+        j == i
+            || lookup(DIRECTIONALS, t).is_some()
+            || (j == last && lookup(STREET_SUFFIXES, t).is_some())
+    })
+}
+
+fn lookup(table: &[(&str, &'static str)], token: &str) -> Option<&'static str> {
+    table
+        .iter()
+        .find(|(long, short)| *long == token || *short == token)
+        .map(|(_, short)| *short)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spellings_of_the_same_address_share_a_key() {
+        let key = address_key("123 N Main St", "Salt Lake City", "84101");
+        assert_eq!(key, "123 N MAIN ST|SALT LAKE CITY|84101");
+        assert_eq!(
+            address_key("123 North Main Street", "salt lake city", "84101-1234"),
+            key
+        );
+        assert_eq!(
+            address_key("123 N. Main St.", "Salt  Lake City", "84101"),
+            key
+        );
+        assert_ne!(
+            address_key("123 N Main St Apt 4", "Salt Lake City", "84101"),
+            key
+        );
+    }
+
+    #[test]
+    fn unit_designators_collapse_to_one_form() {
+        assert_eq!(normalize_address_line("77 E 400 S Apt 4"), "77 E 400 S # 4");
+        assert_eq!(
+            normalize_address_line("77 East 400 South Unit #4"),
+            "77 E 400 S # 4"
+        );
+        assert_eq!(normalize_address_line("77 E 400 S #4"), "77 E 400 S # 4");
+        assert_eq!(
+            normalize_address_line("10 Main St Suite 200"),
+            "10 MAIN ST # 200"
+        );
+    }
+
+    #[test]
+    fn directionals_that_name_the_street_are_kept() {
+        assert_eq!(normalize_address_line("123 North Street"), "123 NORTH ST");
+        assert_eq!(normalize_address_line("9 Canyon Road"), "9 CANYON RD");
+        assert_eq!(
+            normalize_address_line("4410 West Sunset Drive"),
+            "4410 W SUNSET DR"
+        );
+    }
+
+    #[test]
+    fn zip_codes_are_padded_to_five_digits() {
+        assert_eq!(normalize_postal_code("2134"), "02134");
+        assert_eq!(normalize_postal_code(" 84604-0001 "), "84604");
+        assert_eq!(normalize_postal_code("k1a 0b1"), "K1A 0B1");
+        assert_eq!(normalize_city("Saint George"), normalize_city("St. George"));
+    }
+}
@@ -1,5 +1,6 @@
 // src/domain/changes.rs
 
+use crate::domain::events::ChangeEventKind;
 use chrono::NaiveDateTime;
 
 /// A ViewModel representing a single change event for a property.
@@ -9,14 +10,17 @@ use chrono::NaiveDateTime;
 pub struct ChangeViewModel {
     // === Event Details ===
     pub change_date: NaiveDateTime,
-    /// The primary type of change, simplified for the user (e.g., "Status Change", "Price Change").
-    pub change_type: String,
-    /// The value of the field before the change. For a status change, this will be the canonical status.
+    /// What happened, e.g. a price drop or going pending.
+    pub kind: ChangeEventKind,
+    /// The list price before a price change, otherwise the canonical status before.
+    /// Empty for a property seen for the first time.
     pub previous_value: String,
-    /// The value of the field after the change. For a status change, this will be the canonical status.
+    /// The list price after a price change, otherwise the canonical status after.
     pub current_value: String,
 
     // === Property Context (current state at time of change) ===
+    /// Links to the property page.
+    pub property_id: i64,
     // Address
     pub address_full: String,
     pub address_line: String,
@@ -27,13 +31,45 @@ pub struct ChangeViewModel {
     // Details
     pub price: Option<i64>,       // The current price for context
     pub canonical_status: String, // The derived lifecycle status at the time of change
+    // Attributes
+    pub beds: Option<i64>,
+    pub baths: Option<f64>,
+    pub sqft: Option<i64>,
+    pub lot_sqft: Option<i64>,
+    pub property_type: Option<String>,
+    pub year_built: Option<i64>,
+    pub garage: Option<i64>,
+    pub stories: Option<i64>,
+    pub subdivision: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub county_fips: Option<i64>,
     // Flags
     pub is_ready_to_build: bool,
     pub is_new_listing: bool,
     pub is_price_reduced: bool,
     pub is_foreclosure: bool,
+    pub is_new_construction: bool,
 
     // === Calculated Deltas ===
     /// The amount of a price reduction, if applicable.
     pub price_reduction: Option<i64>,
+
+    // === Listing Cycle (the run on the market the change falls in) ===
+    /// 1 for the first listing we saw, higher for relists. `None` for
+    /// properties we never saw on the market, e.g. first seen sold.
+    pub listing_cycle: Option<i64>,
+    /// Listed to sold or delisted, or to today while still on the market.
+    pub days_on_market: Option<i64>,
+    pub days_to_pending: Option<i64>,
+    pub price_cuts: Option<i64>,
+
+    // === Provenance ===
+    /// The site whose listing showed this change, e.g. "realtor". Empty for delistings.
+    pub reported_by: String,
+
+    // === Detail Page Enrichment ===
+    /// The competing listing agent and their office, if the detail page was fetched.
+    pub listing_agent: Option<String>,
+    pub brokerage: Option<String>,
 }
@@ -0,0 +1,177 @@
+// src/domain/cycles.rs
+
+use crate::domain::events::OFF_MARKET_STATUS;
+use chrono::NaiveDateTime;
+
+/// How a listing cycle ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CycleOutcome {
+    Sold,
+    Delisted,
+}
+
+impl CycleOutcome {
+    /// Stored in `listing_cycles.outcome`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CycleOutcome::Sold => "sold",
+            CycleOutcome::Delisted => "delisted",
+        }
+    }
+}
+
+/// A property's canonical status and list price after one observation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Observation {
+    pub observed_at: NaiveDateTime,
+    pub status: &'static str,
+    pub list_price: Option<i64>,
+}
+
+/// One run of a property on the market, from being listed to selling or
+/// dropping off the site. A relisted property starts a new cycle.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListingCycle {
+    pub listed_at: NaiveDateTime,
+    /// When it first went under contract (pending or contingent). `None` if
+    /// it never did, or was already under contract when first seen.
+    pub pending_at: Option<NaiveDateTime>,
+    /// `None` while the listing is still on the market.
+    pub ended_at: Option<NaiveDateTime>,
+    pub outcome: Option<CycleOutcome>,
+    pub initial_price: Option<i64>,
+    pub last_price: Option<i64>,
+    /// How many times the list price went down during the cycle.
+    pub price_cuts: i64,
+}
+
+impl ListingCycle {
+    /// Days from listing to the end of the cycle. `None` while it's open.
+    pub fn days_on_market(&self) -> Option<i64> {
+        self.ended_at.map(|end| (end - self.listed_at).num_days())
+    }
+
+    pub fn days_to_pending(&self) -> Option<i64> {
+        self.pending_at
+            .map(|pending| (pending - self.listed_at).num_days())
+    }
+}
+
+/// Splits a property's observations, oldest first, into listing cycles.
+/// `first_seen_at` dates the first cycle if the property was on the market
+/// when first seen. Properties first seen sold never had a cycle we saw.
+pub fn listing_cycles(
+    first_seen_at: NaiveDateTime,
+    observations: &[Observation],
+) -> Vec<ListingCycle> {
+    let mut cycles: Vec<ListingCycle> = Vec::new();
+    // The open cycle, and whether it has been under contract yet.
+    let mut open: Option<(ListingCycle, bool)> = None;
+
+    for (i, obs) in observations.iter().enumerate() {
+        let under_contract = matches!(obs.status, "Pending" | "Contingent");
+        let Some((cycle, contracted)) = open.as_mut() else {
+            if under_contract || matches!(obs.status, "Active" | "Coming Soon") {
+                let cycle = ListingCycle {
+                    listed_at: if i == 0 {
+                        first_seen_at.min(obs.observed_at)
+                    } else {
+                        obs.observed_at
+                    },
+                    pending_at: None,
+                    ended_at: None,
+                    outcome: None,
+                    initial_price: obs.list_price,
+                    last_price: obs.list_price,
+                    price_cuts: 0,
+                };
+                open = Some((cycle, under_contract));
+            }
+            continue;
+        };
+
+        if let (Some(prev), Some(curr)) = (cycle.last_price, obs.list_price) {
+            if curr < prev {
+                cycle.price_cuts += 1;
+            }
+        }
+        cycle.last_price = obs.list_price.or(cycle.last_price);
+
+        // A deal falling through keeps the cycle, and its first pending date.
+        if under_contract && !*contracted {
+            cycle.pending_at = Some(obs.observed_at);
+            *contracted = true;
+        }
+        let outcome = match obs.status {
+            "Sold" => Some(CycleOutcome::Sold),
+            OFF_MARKET_STATUS => Some(CycleOutcome::Delisted),
+            _ => None,
+        };
+        if let Some(outcome) = outcome {
+            cycle.ended_at = Some(obs.observed_at);
+            cycle.outcome = Some(outcome);
+            cycles.extend(open.take().map(|(cycle, _)| cycle));
+        }
+    }
+    cycles.extend(open.map(|(cycle, _)| cycle));
+    cycles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, NaiveDate};
+
+    #[test]
+    fn relists_start_a_new_cycle_and_price_cuts_are_counted_per_cycle() {
+        let day0 = NaiveDate::from_ymd_opt(2025, 1, 6)
+            .unwrap()
+            .and_hms_opt(8, 0, 0)
+            .unwrap();
+        let obs = |day: i64, status: &'static str, price: i64| Observation {
+            observed_at: day0 + Duration::days(day),
+            status,
+            list_price: Some(price),
+        };
+        let observations = [
+            obs(0, "Active", 500_000),
+            obs(14, "Active", 489_000),
+            obs(30, "Active", 475_000),
+            obs(41, "Pending", 475_000),
+            obs(50, "Active", 475_000), // fell through
+            obs(60, "Pending", 475_000),
+            obs(75, OFF_MARKET_STATUS, 475_000),
+            obs(120, "Active", 465_000),
+            obs(131, "Contingent", 465_000),
+            obs(160, "Sold", 465_000),
+        ];
+
+        let cycles = listing_cycles(day0 - Duration::hours(1), &observations);
+        assert_eq!(cycles.len(), 2);
+
+        let first = &cycles[0];
+        assert_eq!(first.listed_at, day0 - Duration::hours(1));
+        assert_eq!(first.price_cuts, 2);
+        assert_eq!(first.days_to_pending(), Some(41));
+        assert_eq!(first.days_on_market(), Some(75));
+        assert_eq!(first.outcome, Some(CycleOutcome::Delisted));
+
+        let relist = &cycles[1];
+        assert_eq!(relist.listed_at, day0 + Duration::days(120));
+        assert_eq!(relist.initial_price, Some(465_000));
+        assert_eq!(relist.price_cuts, 0);
+        assert_eq!(relist.days_to_pending(), Some(11));
+        assert_eq!(relist.days_on_market(), Some(40));
+        assert_eq!(relist.outcome, Some(CycleOutcome::Sold));
+
+        // First seen already sold: never on the market while we watched.
+        assert!(listing_cycles(day0, &[obs(0, "Sold", 1)]).is_empty());
+        // Still listed: open, with no days on market yet.
+        let open = listing_cycles(day0, &observations[..2]);
+        assert_eq!(open[0].ended_at, None);
+        assert_eq!(open[0].days_on_market(), None);
+        // First seen under contract: when it went pending is unknown.
+        let late = listing_cycles(day0, &[obs(0, "Pending", 1), obs(9, "Pending", 2)]);
+        assert_eq!(late[0].days_to_pending(), None);
+    }
+}
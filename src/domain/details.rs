@@ -0,0 +1,119 @@
+// src/domain/details.rs
+
+use crate::scraper::models::PropertyDetail;
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+
+/// What a listing's detail page adds to the search result: who is listing
+/// it, the full description and its price history.
+#[derive(Debug, PartialEq)]
+pub struct ListingDetails {
+    pub agent_name: Option<String>,
+    pub brokerage: Option<String>,
+    pub description: Option<String>,
+    pub days_on_market: Option<i64>,
+    pub photo_count: Option<i64>,
+    pub price_history: Vec<PriceEvent>,
+}
+
+/// One row of the detail page's price history, e.g. "Listed" or "Price Changed".
+#[derive(Debug, PartialEq)]
+pub struct PriceEvent {
+    pub date: NaiveDate,
+    pub event_name: String,
+    pub price: Option<i64>,
+    pub source_name: Option<String>,
+}
+
+impl ListingDetails {
+    /// Flattens a parsed detail page. The listing agent is the "seller"
+    /// advertiser; days on market are counted from the list date to
+    /// `fetched_at` when the page doesn't give them.
+    pub fn from_detail(detail: &PropertyDetail, fetched_at: NaiveDateTime) -> Self {
+        let advertisers = detail.advertisers.as_deref().unwrap_or_default();
+        let agent = advertisers
+            .iter()
+            .find(|a| a.advertiser_type.as_deref() == Some("seller"))
+            .or(advertisers.first());
+
+        let days_on_market = detail.days_on_market.or_else(|| {
+            let listed = parse_date(detail.list_date.as_deref()?)?;
+            Some((fetched_at.date() - listed).num_days().max(0))
+        });
+
+        let price_history = detail
+            .property_history
+            .iter()
+            .flatten()
+            .filter_map(|entry| {
+                Some(PriceEvent {
+                    date: parse_date(entry.date.as_deref()?)?,
+                    event_name: entry.event_name.clone()?,
+                    price: entry.price,
+                    source_name: entry.source_name.clone(),
+                })
+            })
+            .collect();
+
+        ListingDetails {
+            agent_name: agent.and_then(|a| a.name.clone()),
+            brokerage: agent
+                .and_then(|a| a.office.as_ref())
+                .and_then(|o| o.name.clone()),
+            description: detail
+                .description
+                .as_ref()
+                .and_then(|d| d.text.clone())
+                .filter(|t| !t.trim().is_empty()),
+            days_on_market,
+            photo_count: detail
+                .photo_count
+                .or(detail.photos.as_ref().map(|p| p.len() as i64)),
+            price_history,
+        }
+    }
+}
+
+/// Detail pages mix plain dates ("2025-08-15") and timestamps.
+fn parse_date(s: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").ok().or_else(|| {
+        DateTime::parse_from_rfc3339(s)
+            .ok()
+            .map(|dt| dt.date_naive())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seller_is_the_listing_agent_and_days_on_market_fall_back_to_list_date() {
+        let detail: PropertyDetail = serde_json::from_value(serde_json::json!({
+            "list_date": "2025-09-01T12:00:00.000000Z",
+            "description": { "text": "Updated rambler near the park." },
+            "advertisers": [
+                { "type": "buyer", "name": "Pat Buyer", "office": { "name": "Other Realty" } },
+                { "type": "seller", "name": "Kerry Oman", "office": { "name": "Summit Realty" } }
+            ],
+            "property_history": [
+                { "date": "2025-09-20", "event_name": "Price Changed", "price": 439000 },
+                { "date": "2025-09-01", "event_name": "Listed", "price": 450000 },
+                { "date": null, "event_name": "Listed", "price": 1 }
+            ],
+            "photos": [{}, {}, {}]
+        }))
+        .unwrap();
+        let fetched_at = NaiveDate::from_ymd_opt(2025, 10, 1)
+            .unwrap()
+            .and_hms_opt(8, 0, 0)
+            .unwrap();
+
+        let details = ListingDetails::from_detail(&detail, fetched_at);
+        assert_eq!(details.agent_name.as_deref(), Some("Kerry Oman"));
+        assert_eq!(details.brokerage.as_deref(), Some("Summit Realty"));
+        assert_eq!(details.days_on_market, Some(30));
+        assert_eq!(details.photo_count, Some(3));
+        assert_eq!(details.price_history.len(), 2);
+        assert_eq!(details.price_history[0].price, Some(439000));
+    }
+}
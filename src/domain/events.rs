@@ -0,0 +1,251 @@
+// src/domain/events.rs
+
+use crate::domain::logic::derive_canonical_status;
+use crate::domain::property::{ScrapedProperty, TrackedProperty};
+use chrono::NaiveDateTime;
+
+/// Canonical status of a listing that has dropped off the site.
+pub const OFF_MARKET_STATUS: &str = "Off Market";
+
+/// What happened to a listing, as shown to users. Worked out when a listing
+/// is saved, so the dashboard and exports don't reinterpret raw field changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeEventKind {
+    NewListing,
+    PriceDrop,
+    PriceIncrease,
+    WentPending,
+    WentContingent,
+    BackOnMarket,
+    Sold,
+    Delisted,
+    ComingSoon,
+}
+
+impl ChangeEventKind {
+    pub const ALL: [ChangeEventKind; 9] = [
+        ChangeEventKind::NewListing,
+        ChangeEventKind::PriceDrop,
+        ChangeEventKind::PriceIncrease,
+        ChangeEventKind::WentPending,
+        ChangeEventKind::WentContingent,
+        ChangeEventKind::BackOnMarket,
+        ChangeEventKind::Sold,
+        ChangeEventKind::Delisted,
+        ChangeEventKind::ComingSoon,
+    ];
+
+    /// Stored in `change_events.event_type`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeEventKind::NewListing => "new_listing",
+            ChangeEventKind::PriceDrop => "price_drop",
+            ChangeEventKind::PriceIncrease => "price_increase",
+            ChangeEventKind::WentPending => "went_pending",
+            ChangeEventKind::WentContingent => "went_contingent",
+            ChangeEventKind::BackOnMarket => "back_on_market",
+            ChangeEventKind::Sold => "sold",
+            ChangeEventKind::Delisted => "delisted",
+            ChangeEventKind::ComingSoon => "coming_soon",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|k| k.as_str() == s)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ChangeEventKind::NewListing => "New Listing",
+            ChangeEventKind::PriceDrop => "Price Drop",
+            ChangeEventKind::PriceIncrease => "Price Increase",
+            ChangeEventKind::WentPending => "Went Pending",
+            ChangeEventKind::WentContingent => "Went Contingent",
+            ChangeEventKind::BackOnMarket => "Back on Market",
+            ChangeEventKind::Sold => "Sold",
+            ChangeEventKind::Delisted => "Delisted",
+            ChangeEventKind::ComingSoon => "Coming Soon",
+        }
+    }
+
+    pub fn is_price_change(&self) -> bool {
+        matches!(
+            self,
+            ChangeEventKind::PriceDrop | ChangeEventKind::PriceIncrease
+        )
+    }
+}
+
+/// One typed change, with the listing's canonical status either side of it
+/// and its list price either side of a price change.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChangeEvent {
+    pub kind: ChangeEventKind,
+    /// `None` for a property seen for the first time.
+    pub previous_status: Option<&'static str>,
+    pub current_status: &'static str,
+    pub previous_price: Option<i64>,
+    pub current_price: Option<i64>,
+}
+
+/// The fields that decide a listing's canonical status and price.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ListingState {
+    pub status: Option<String>,
+    pub list_price: Option<i64>,
+    pub sold_date: Option<NaiveDateTime>,
+    pub is_pending: bool,
+    pub is_contingent: bool,
+    pub is_coming_soon: bool,
+    pub delisted: bool,
+}
+
+impl ListingState {
+    pub fn from_tracked(tracked: &TrackedProperty, delisted: bool) -> Self {
+        Self {
+            status: tracked.status.clone(),
+            list_price: tracked.list_price,
+            sold_date: tracked.sold_date,
+            is_pending: tracked.is_pending.unwrap_or(false),
+            is_contingent: tracked.is_contingent.unwrap_or(false),
+            is_coming_soon: tracked.is_coming_soon.unwrap_or(false),
+            delisted,
+        }
+    }
+
+    /// A listing just scraped is on the market by definition.
+    pub fn from_scraped(prop: &ScrapedProperty) -> Self {
+        Self {
+            status: prop.status.clone(),
+            list_price: prop.list_price,
+            sold_date: prop.sold_date,
+            is_pending: prop.is_pending.unwrap_or(false),
+            is_contingent: prop.is_contingent.unwrap_or(false),
+            is_coming_soon: prop.is_coming_soon.unwrap_or(false),
+            delisted: false,
+        }
+    }
+
+    pub fn canonical_status(&self) -> &'static str {
+        if self.delisted {
+            return OFF_MARKET_STATUS;
+        }
+        derive_canonical_status(
+            &self.sold_date,
+            self.is_pending,
+            self.is_contingent,
+            self.is_coming_soon,
+            &self.status,
+        )
+    }
+}
+
+/// The events a listing going from `before` to `after` amounts to. `before`
+/// is `None` the first time a property is seen. A status change and a price
+/// change in the same observation are two events, status first.
+pub fn events_between(before: Option<&ListingState>, after: &ListingState) -> Vec<ChangeEvent> {
+    let current_status = after.canonical_status();
+    let mut events = Vec::new();
+
+    let Some(before) = before else {
+        let kind = match current_status {
+            "Coming Soon" => ChangeEventKind::ComingSoon,
+            "Sold" => ChangeEventKind::Sold,
+            _ => ChangeEventKind::NewListing,
+        };
+        events.push(ChangeEvent {
+            kind,
+            previous_status: None,
+            current_status,
+            previous_price: None,
+            current_price: after.list_price,
+        });
+        return events;
+    };
+
+    let previous_status = before.canonical_status();
+    if previous_status != current_status {
+        let kind = match (previous_status, current_status) {
+            (_, "Pending") => Some(ChangeEventKind::WentPending),
+            (_, "Contingent") => Some(ChangeEventKind::WentContingent),
+            (_, "Sold") => Some(ChangeEventKind::Sold),
+            (_, "Coming Soon") => Some(ChangeEventKind::ComingSoon),
+            (_, OFF_MARKET_STATUS) => Some(ChangeEventKind::Delisted),
+            // A coming-soon listing going live is when it's really listed.
+            ("Coming Soon", "Active") => Some(ChangeEventKind::NewListing),
+            (_, "Active") => Some(ChangeEventKind::BackOnMarket),
+            _ => None,
+        };
+        if let Some(kind) = kind {
+            events.push(ChangeEvent {
+                kind,
+                previous_status: Some(previous_status),
+                current_status,
+                previous_price: before.list_price,
+                current_price: after.list_price,
+            });
+        }
+    }
+
+    if let (Some(prev), Some(curr)) = (before.list_price, after.list_price) {
+        if prev != curr {
+            events.push(ChangeEvent {
+                kind: if curr < prev {
+                    ChangeEventKind::PriceDrop
+                } else {
+                    ChangeEventKind::PriceIncrease
+                },
+                previous_status: Some(previous_status),
+                current_status,
+                previous_price: Some(prev),
+                current_price: Some(curr),
+            });
+        }
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn active(price: i64) -> ListingState {
+        ListingState {
+            status: Some("for_sale".into()),
+            list_price: Some(price),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn transitions_carry_the_canonical_status_on_both_sides() {
+        let listed = events_between(None, &active(450_000));
+        assert_eq!(listed[0].kind, ChangeEventKind::NewListing);
+        assert_eq!(listed[0].current_status, "Active");
+
+        // The flag, not the raw status, is what makes it pending.
+        let pending = ListingState {
+            is_pending: true,
+            list_price: Some(439_000),
+            ..active(0)
+        };
+        let events = events_between(Some(&active(450_000)), &pending);
+        let kinds: Vec<_> = events.iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![ChangeEventKind::WentPending, ChangeEventKind::PriceDrop]
+        );
+        assert_eq!(events[0].previous_status, Some("Active"));
+        assert_eq!(events[0].current_status, "Pending");
+
+        let delisted = ListingState {
+            delisted: true,
+            ..pending.clone()
+        };
+        let back = events_between(Some(&delisted), &active(439_000));
+        assert_eq!(back[0].kind, ChangeEventKind::BackOnMarket);
+        assert_eq!(back[0].previous_status, Some(OFF_MARKET_STATUS));
+
+        assert!(events_between(Some(&active(1)), &active(1)).is_empty());
+    }
+}
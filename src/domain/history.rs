@@ -0,0 +1,264 @@
+// src/domain/history.rs
+
+use crate::domain::cycles::Observation;
+use crate::domain::events::ListingState;
+use crate::domain::property::TrackedProperty;
+use chrono::NaiveDateTime;
+
+/// History field for listings disappearing from and returning to the site.
+pub const MARKET_STATUS_FIELD: &str = "market_status";
+pub const ON_MARKET: &str = "on_market";
+pub const OFF_MARKET: &str = "off_market";
+pub const BACK_ON_MARKET: &str = "back_on_market";
+/// A delisted home that turned up again as sold.
+pub const SOLD: &str = "sold";
+
+/// One `property_history` row, as far as replaying it needs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryEntry {
+    pub observed_at: NaiveDateTime,
+    pub field_name: String,
+    pub current_value: String,
+}
+
+/// A property's tracked fields as they stood at some moment, rebuilt from
+/// its history rather than read from `properties`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropertySnapshot {
+    pub property: TrackedProperty,
+    /// Whether it had dropped off the site by then.
+    pub delisted: bool,
+    pub first_seen_at: NaiveDateTime,
+    /// When the last replayed value was observed.
+    pub last_changed_at: NaiveDateTime,
+}
+
+impl PropertySnapshot {
+    /// A property as first seen, before its initial values are applied.
+    pub fn new(property_id: i64, first_seen_at: NaiveDateTime) -> Self {
+        Self {
+            property: TrackedProperty {
+                id: property_id,
+                ..Default::default()
+            },
+            delisted: false,
+            first_seen_at,
+            last_changed_at: first_seen_at,
+        }
+    }
+
+    /// Applies one history row. Fields that aren't part of the snapshot,
+    /// like open houses, are skipped.
+    pub fn apply(&mut self, entry: &HistoryEntry) {
+        let applied = if entry.field_name == MARKET_STATUS_FIELD {
+            self.delisted = entry.current_value == OFF_MARKET;
+            true
+        } else {
+            self.property
+                .apply_history_value(&entry.field_name, &entry.current_value)
+        };
+        if applied {
+            self.last_changed_at = entry.observed_at;
+        }
+    }
+
+    pub fn listing_state(&self) -> ListingState {
+        ListingState::from_tracked(&self.property, self.delisted)
+    }
+}
+
+/// Replays a property's history, in the order it was written, up to and
+/// including `as_of`. `None` if the property hadn't been seen by then.
+pub fn replay(
+    property_id: i64,
+    entries: &[HistoryEntry],
+    as_of: NaiveDateTime,
+) -> Option<PropertySnapshot> {
+    let mut entries = entries.iter().filter(|e| e.observed_at <= as_of).peekable();
+    let mut snapshot = PropertySnapshot::new(property_id, entries.peek()?.observed_at);
+    for entry in entries {
+        snapshot.apply(entry);
+    }
+    Some(snapshot)
+}
+
+/// The property's canonical status and list price after each observation,
+/// oldest first. Rows written at the same moment are one observation.
+pub fn observations(property_id: i64, entries: &[HistoryEntry]) -> Vec<Observation> {
+    let mut observations: Vec<Observation> = Vec::new();
+    let Some(first) = entries.first() else {
+        return observations;
+    };
+    let mut snapshot = PropertySnapshot::new(property_id, first.observed_at);
+    for (i, entry) in entries.iter().enumerate() {
+        snapshot.apply(entry);
+        let last_of_observation = entries
+            .get(i + 1)
+            .is_none_or(|next| next.observed_at != entry.observed_at);
+        if last_of_observation {
+            let state = snapshot.listing_state();
+            observations.push(Observation {
+                observed_at: entry.observed_at,
+                status: state.canonical_status(),
+                list_price: state.list_price,
+            });
+        }
+    }
+    observations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::property::{ScrapedProperty, REALTOR_SITE};
+    use chrono::{Duration, NaiveDate};
+
+    fn listing(list_price: i64) -> ScrapedProperty {
+        ScrapedProperty {
+            source_name: "test".to_string(),
+            source_listing_id: "123".to_string(),
+            site: REALTOR_SITE,
+            address_line: "123 Main".to_string(),
+            city: "Anytown".to_string(),
+            postal_code: "12345".to_string(),
+            state_abbr: Some("UT".to_string()),
+            county_name: None,
+            status: Some("for_sale".to_string()),
+            list_price: Some(list_price),
+            sold_price: None,
+            sold_date: None,
+            is_pending: Some(false),
+            is_contingent: Some(false),
+            is_new_listing: Some(true),
+            is_foreclosure: Some(false),
+            is_price_reduced: None,
+            is_coming_soon: Some(false),
+            beds: Some(3),
+            baths: Some(2.0),
+            sqft: Some(1850),
+            lot_sqft: None,
+            property_type: Some("single_family".to_string()),
+            year_built: Some(1998),
+            garage: Some(2),
+            stories: Some(1),
+            is_new_construction: Some(false),
+            subdivision: None,
+            latitude: None,
+            longitude: None,
+            county_fips: None,
+            open_houses: None,
+        }
+    }
+
+    /// What `properties` holds once `prop` has been saved.
+    fn stored(id: i64, prop: &ScrapedProperty) -> TrackedProperty {
+        TrackedProperty {
+            id,
+            status: prop.status.clone(),
+            list_price: prop.list_price,
+            sold_price: prop.sold_price,
+            sold_date: prop.sold_date,
+            is_pending: prop.is_pending,
+            is_contingent: prop.is_contingent,
+            is_new_listing: prop.is_new_listing,
+            is_foreclosure: prop.is_foreclosure,
+            is_price_reduced: prop.is_price_reduced,
+            is_coming_soon: prop.is_coming_soon,
+            beds: prop.beds,
+            baths: prop.baths,
+            sqft: prop.sqft,
+            lot_sqft: prop.lot_sqft,
+            property_type: prop.property_type.clone(),
+            year_built: prop.year_built,
+            garage: prop.garage,
+            stories: prop.stories,
+            is_new_construction: prop.is_new_construction,
+        }
+    }
+
+    #[test]
+    fn replaying_history_rebuilds_what_diff_recorded() {
+        let start = NaiveDate::from_ymd_opt(2025, 3, 1)
+            .unwrap()
+            .and_hms_opt(9, 30, 0)
+            .unwrap();
+        let sold_at = NaiveDate::from_ymd_opt(2025, 4, 18)
+            .unwrap()
+            .and_hms_milli_opt(0, 0, 0, 250)
+            .unwrap();
+
+        let pending = ScrapedProperty {
+            list_price: Some(439_000),
+            is_pending: Some(true),
+            is_new_listing: Some(false),
+            is_price_reduced: Some(true),
+            ..listing(450_000)
+        };
+        let sold = ScrapedProperty {
+            status: Some("sold".to_string()),
+            sold_price: Some(432_500),
+            sold_date: Some(sold_at),
+            is_pending: None,
+            beds: Some(4),
+            ..pending.clone()
+        };
+        let states = [listing(450_000), pending, sold];
+
+        // Write history the way saving does: the reported values on first
+        // sight, then each diff against what was stored.
+        let mut history = Vec::new();
+        let mut expected: Vec<(NaiveDateTime, TrackedProperty)> = Vec::new();
+        for (day, prop) in states.iter().enumerate() {
+            let observed_at = start + Duration::days(7 * day as i64);
+            match expected.last() {
+                None => {
+                    history.extend(
+                        prop.reported_values()
+                            .into_iter()
+                            .map(|(f, v)| HistoryEntry {
+                                observed_at,
+                                field_name: f.to_string(),
+                                current_value: v,
+                            }),
+                    )
+                }
+                Some((_, before)) => {
+                    let changes = before.diff(prop);
+                    assert!(!changes.is_empty());
+                    history.extend(changes.into_iter().map(|c| HistoryEntry {
+                        observed_at,
+                        field_name: c.field_name,
+                        current_value: c.current_value,
+                    }))
+                }
+            }
+            expected.push((observed_at, stored(7, prop)));
+        }
+        // Not part of the snapshot, and mustn't upset it.
+        history.push(HistoryEntry {
+            observed_at: start + Duration::days(20),
+            field_name: "open_house".to_string(),
+            current_value: "2025-03-22 12:00-14:00".to_string(),
+        });
+
+        assert_eq!(replay(7, &history, start - Duration::seconds(1)), None);
+        for (observed_at, tracked) in &expected {
+            for as_of in [*observed_at, *observed_at + Duration::days(3)] {
+                let snapshot = replay(7, &history, as_of).unwrap();
+                assert_eq!(&snapshot.property, tracked, "as of {as_of}");
+                assert_eq!(snapshot.first_seen_at, start);
+                assert_eq!(snapshot.last_changed_at, *observed_at);
+            }
+        }
+
+        let (_, last) = expected.last().unwrap();
+        history.push(HistoryEntry {
+            observed_at: start + Duration::days(30),
+            field_name: MARKET_STATUS_FIELD.to_string(),
+            current_value: OFF_MARKET.to_string(),
+        });
+        let snapshot = replay(7, &history, start + Duration::days(30)).unwrap();
+        assert_eq!(&snapshot.property, last);
+        assert_eq!(snapshot.listing_state().canonical_status(), "Off Market");
+    }
+}
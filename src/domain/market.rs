@@ -0,0 +1,125 @@
+// src/domain/market.rs
+
+/// One day's market figures for an area, as stored in `market_stats`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MarketStats {
+    /// Listings on the market (canonical status "Active") at the end of the day.
+    pub active_inventory: i64,
+    /// Listing cycles that started that day, relists included.
+    pub new_listings: i64,
+    /// Listings that went under contract that day.
+    pub pendings: i64,
+    /// Properties whose sale closed that day.
+    pub solds: i64,
+    /// Over the active inventory.
+    pub median_list_price: Option<i64>,
+    /// Over that day's sales.
+    pub median_sold_price: Option<i64>,
+    /// Over that day's sales we saw listed.
+    pub median_days_on_market: Option<i64>,
+    /// Share of the active inventory cut in price since it was listed, 0 to 1.
+    pub price_cut_share: Option<f64>,
+    /// Mean of sold price over last list price across that day's sales.
+    pub sale_to_list_ratio: Option<f64>,
+}
+
+/// Collects the listings and sales in one area on one day.
+#[derive(Debug, Default)]
+pub struct MarketStatsBuilder {
+    stats: MarketStats,
+    list_prices: Vec<i64>,
+    cut: i64,
+    sold_prices: Vec<i64>,
+    days_on_market: Vec<i64>,
+    ratios: Vec<f64>,
+}
+
+impl MarketStatsBuilder {
+    /// A listing on the market at the end of the day.
+    pub fn add_active(&mut self, list_price: Option<i64>, price_cut: bool) {
+        self.stats.active_inventory += 1;
+        self.list_prices.extend(list_price);
+        if price_cut {
+            self.cut += 1;
+        }
+    }
+
+    pub fn add_new_listing(&mut self) {
+        self.stats.new_listings += 1;
+    }
+
+    pub fn add_pending(&mut self) {
+        self.stats.pendings += 1;
+    }
+
+    pub fn add_sale(
+        &mut self,
+        sold_price: Option<i64>,
+        list_price: Option<i64>,
+        days_on_market: Option<i64>,
+    ) {
+        self.stats.solds += 1;
+        self.sold_prices.extend(sold_price);
+        self.days_on_market.extend(days_on_market);
+        if let (Some(sold), Some(list)) = (sold_price, list_price.filter(|p| *p > 0)) {
+            self.ratios.push(sold as f64 / list as f64);
+        }
+    }
+
+    pub fn build(mut self) -> MarketStats {
+        let active = self.stats.active_inventory;
+        self.stats.median_list_price = median(&mut self.list_prices);
+        self.stats.median_sold_price = median(&mut self.sold_prices);
+        self.stats.median_days_on_market = median(&mut self.days_on_market);
+        self.stats.price_cut_share = (active > 0).then(|| self.cut as f64 / active as f64);
+        self.stats.sale_to_list_ratio = (!self.ratios.is_empty())
+            .then(|| self.ratios.iter().sum::<f64>() / self.ratios.len() as f64);
+        self.stats
+    }
+}
+
+/// The middle value, or the mean of the middle two rounded down.
+fn median(values: &mut [i64]) -> Option<i64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_unstable();
+    let mid = values.len() / 2;
+    Some(if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2
+    } else {
+        values[mid]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builder_takes_medians_and_shares_over_what_was_added() {
+        let mut builder = MarketStatsBuilder::default();
+        builder.add_active(Some(400_000), true);
+        builder.add_active(Some(500_000), false);
+        builder.add_active(Some(450_000), false);
+        builder.add_active(None, true);
+        builder.add_new_listing();
+        builder.add_pending();
+        builder.add_sale(Some(490_000), Some(500_000), Some(30));
+        builder.add_sale(Some(310_000), Some(300_000), None);
+
+        let stats = builder.build();
+        assert_eq!(stats.active_inventory, 4);
+        assert_eq!(stats.median_list_price, Some(450_000));
+        assert_eq!(stats.price_cut_share, Some(0.5));
+        assert_eq!(stats.solds, 2);
+        assert_eq!(stats.median_sold_price, Some(400_000));
+        assert_eq!(stats.median_days_on_market, Some(30));
+        let ratio = stats.sale_to_list_ratio.unwrap();
+        assert!((ratio - (0.98 + 310.0 / 300.0) / 2.0).abs() < 1e-9);
+
+        let empty = MarketStatsBuilder::default().build();
+        assert_eq!(empty.price_cut_share, None);
+        assert_eq!(empty.sale_to_list_ratio, None);
+    }
+}
@@ -1,5 +1,12 @@
+pub mod address;
 pub mod campaign;
 pub mod changes;
+pub mod cycles;
+pub mod details;
+pub mod events;
+pub mod history;
 pub mod logic;
 pub mod mailing;
+pub mod market;
 pub mod property;
+pub mod property_page;
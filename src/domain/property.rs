@@ -1,7 +1,18 @@
 // src/domain/property.rs
 
+use crate::domain::address::address_key;
 use crate::scraper::models::Property as ScraperProperty;
-use chrono::{DateTime, NaiveDateTime, Utc};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+
+/// Realtor.com, our primary source. It sends every tracked field on each
+/// listing, so a missing value there means the value was cleared. Other
+/// sites only send what their search results show, and a missing value just
+/// means "not reported".
+pub const REALTOR_SITE: &str = "realtor";
+
+/// Raw status of rental listings. Their `list_price` is a monthly rent, so
+/// they're stored in `rental_listings`, never in `properties`.
+pub const RENTAL_STATUS: &str = "for_rent";
 
 /// Represents a property as scraped, flattened, and normalized, ready for comparison.
 /// This acts as an anti-corruption layer between the raw scrape and our database models.
@@ -10,6 +21,9 @@ pub struct ScrapedProperty {
     // Source identifier
     pub source_name: String,
     pub source_listing_id: String,
+    /// The site the listing was scraped from, e.g. "realtor" or "zillow".
+    /// `source_name` is the MLS or feed behind it.
+    pub site: &'static str,
 
     // Address fields (used for unique identification)
     pub address_line: String,
@@ -29,9 +43,135 @@ pub struct ScrapedProperty {
     pub is_foreclosure: Option<bool>,
     pub is_price_reduced: Option<bool>,
     pub is_coming_soon: Option<bool>,
+    pub beds: Option<i64>,
+    pub baths: Option<f64>,
+    pub sqft: Option<i64>,
+    pub lot_sqft: Option<i64>,
+    pub property_type: Option<String>,
+    pub year_built: Option<i64>,
+    pub garage: Option<i64>,
+    pub stories: Option<i64>,
+    pub is_new_construction: Option<bool>,
+
+    // Descriptive fields, stored but not change-tracked
+    pub subdivision: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub county_fips: Option<i64>,
+
+    /// Scheduled open houses. `None` when the site doesn't report them;
+    /// Realtor always does, so an empty list from it means none.
+    pub open_houses: Option<Vec<OpenHouseSlot>>,
+}
+
+/// One scheduled open house, in local time at the property.
+#[derive(Debug, PartialEq, Clone)]
+pub struct OpenHouseSlot {
+    pub starts_at: NaiveDateTime,
+    pub ends_at: Option<NaiveDateTime>,
+    /// As the site gives it, e.g. "MST".
+    pub time_zone: Option<String>,
+}
+
+impl OpenHouseSlot {
+    /// Display form used in `property_history`, e.g. "2025-10-18 12:00-14:00".
+    pub fn label(&self) -> String {
+        match self.ends_at {
+            Some(end) if end.date() == self.starts_at.date() => format!(
+                "{}-{}",
+                self.starts_at.format("%Y-%m-%d %H:%M"),
+                end.format("%H:%M")
+            ),
+            Some(end) => format!(
+                "{} to {}",
+                self.starts_at.format("%Y-%m-%d %H:%M"),
+                end.format("%Y-%m-%d %H:%M")
+            ),
+            None => self.starts_at.format("%Y-%m-%d %H:%M").to_string(),
+        }
+    }
 }
 
 impl ScrapedProperty {
+    /// The normalized key this property is matched on. The scraped address
+    /// fields are kept as-is for display.
+    pub fn address_key(&self) -> String {
+        address_key(&self.address_line, &self.city, &self.postal_code)
+    }
+
+    /// Whether this is a rental listing rather than a home for sale.
+    pub fn is_rental(&self) -> bool {
+        self.status.as_deref() == Some(RENTAL_STATUS)
+    }
+
+    /// Tracked fields this listing reports a value for, formatted as they
+    /// are stored in `property_history`.
+    pub fn reported_values(&self) -> Vec<(&'static str, String)> {
+        let mut values = Vec::new();
+        macro_rules! report {
+            ($field:ident) => {
+                if let Some(value) = &self.$field {
+                    values.push((stringify!($field), value.to_string()));
+                }
+            };
+        }
+        report!(status);
+        report!(list_price);
+        report!(sold_price);
+        report!(sold_date);
+        report!(is_pending);
+        report!(is_contingent);
+        report!(is_new_listing);
+        report!(is_foreclosure);
+        report!(is_price_reduced);
+        report!(is_coming_soon);
+        report!(beds);
+        report!(baths);
+        report!(sqft);
+        report!(lot_sqft);
+        report!(property_type);
+        report!(year_built);
+        report!(garage);
+        report!(stories);
+        report!(is_new_construction);
+        values
+    }
+
+    /// Takes the stored value for every tracked field this listing leaves
+    /// out, so a partial source doesn't read as clearing them.
+    pub fn fill_unreported(&mut self, current: &TrackedProperty) {
+        macro_rules! fill {
+            ($($field:ident),*) => {
+                $(
+                    if self.$field.is_none() {
+                        self.$field = current.$field.clone();
+                    }
+                )*
+            };
+        }
+        fill!(
+            status,
+            list_price,
+            sold_price,
+            sold_date,
+            is_pending,
+            is_contingent,
+            is_new_listing,
+            is_foreclosure,
+            is_price_reduced,
+            is_coming_soon,
+            beds,
+            baths,
+            sqft,
+            lot_sqft,
+            property_type,
+            year_built,
+            garage,
+            stories,
+            is_new_construction
+        );
+    }
+
     /// Creates a flattened, clean `ScrapedProperty` from the raw nested scraper model.
     /// It validates that essential fields required for identification exist.
     pub fn from_scraper_property(prop: &ScraperProperty) -> Result<Self, String> {
@@ -70,27 +210,72 @@ impl ScrapedProperty {
             .ok_or("Missing or empty source listing id")?
             .to_string();
 
-        // Helper to parse optional date strings from the scrape into NaiveDateTime
+        // Helper to parse optional date strings from the scrape into NaiveDateTime.
+        // Sold searches give plain dates ("2025-09-30").
         let parse_date = |date_str: Option<&str>| {
-            date_str
-                .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
-                .map(|dt| dt.with_timezone(&Utc).naive_utc())
+            date_str.and_then(|s| match DateTime::parse_from_rfc3339(s) {
+                Ok(dt) => Some(dt.with_timezone(&Utc).naive_utc()),
+                Err(_) => NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                    .ok()
+                    .and_then(|d| d.and_hms_opt(0, 0, 0)),
+            })
         };
 
         let description = prop.description.as_ref();
-        let sold_date = parse_date(description.and_then(|d| d.sold_date.as_deref()));
+        let sold = prop.status.as_deref() == Some("sold");
+        let sold_price = prop.sold_price.or_else(|| {
+            description
+                .and_then(|d| d.sold_price)
+                .or(prop.last_sold_price)
+                .filter(|_| sold)
+        });
+        let sold_date = parse_date(
+            description
+                .and_then(|d| d.sold_date.as_deref())
+                .or(prop.last_sold_date.as_deref().filter(|_| sold)),
+        );
+        let coordinate = prop
+            .location
+            .coordinate
+            .as_ref()
+            .or(address.coordinate.as_ref());
+
+        // Open house times are local to the property, without an offset.
+        let parse_local = |s: Option<&str>| {
+            let s = s?;
+            NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S")
+                .ok()
+                .or_else(|| {
+                    DateTime::parse_from_rfc3339(s)
+                        .ok()
+                        .map(|dt| dt.naive_local())
+                })
+        };
+        let open_houses = prop
+            .open_houses
+            .iter()
+            .flatten()
+            .filter_map(|oh| {
+                Some(OpenHouseSlot {
+                    starts_at: parse_local(oh.start_date.as_deref())?,
+                    ends_at: parse_local(oh.end_date.as_deref()),
+                    time_zone: oh.time_zone.clone(),
+                })
+            })
+            .collect();
 
         Ok(ScrapedProperty {
             source_name: prop.source.name.as_deref().unwrap_or("unknown").to_string(),
             source_listing_id,
+            site: REALTOR_SITE,
             address_line,
             city,
             postal_code,
             state_abbr: address.state_code.clone(),
             county_name: prop.location.county.as_ref().and_then(|c| c.name.clone()),
             status: prop.status.clone(),
-            list_price: prop.list_price,
-            sold_price: prop.sold_price,
+            list_price: prop.list_price.or(prop.list_price_min),
+            sold_price,
             sold_date,
             is_pending: prop.flags.as_ref().and_then(|f| f.is_pending),
             is_contingent: prop.flags.as_ref().and_then(|f| f.is_contingent),
@@ -98,12 +283,26 @@ impl ScrapedProperty {
             is_foreclosure: prop.flags.as_ref().and_then(|f| f.is_foreclosure),
             is_price_reduced: prop.flags.as_ref().and_then(|f| f.is_price_reduced),
             is_coming_soon: prop.flags.as_ref().and_then(|f| f.is_coming_soon),
+            beds: description.and_then(|d| d.beds),
+            baths: description.and_then(|d| d.baths),
+            sqft: description.and_then(|d| d.sqft),
+            lot_sqft: description.and_then(|d| d.lot_sqft),
+            property_type: description.and_then(|d| d.property_type.clone()),
+            year_built: description.and_then(|d| d.year_built),
+            garage: description.and_then(|d| d.garage),
+            stories: description.and_then(|d| d.stories),
+            is_new_construction: prop.flags.as_ref().and_then(|f| f.is_new_construction),
+            subdivision: description.and_then(|d| d.subdivision.clone()),
+            latitude: coordinate.and_then(|c| c.lat),
+            longitude: coordinate.and_then(|c| c.lon),
+            county_fips: prop.location.county.as_ref().and_then(|c| c.fips_code),
+            open_houses: Some(open_houses),
         })
     }
 }
 
 /// Represents the current state of a property as stored in our `properties` table.
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, Default, PartialEq, Clone)]
 pub struct TrackedProperty {
     pub id: i64,
     pub status: Option<String>,
@@ -116,6 +315,15 @@ pub struct TrackedProperty {
     pub is_foreclosure: Option<bool>,
     pub is_price_reduced: Option<bool>,
     pub is_coming_soon: Option<bool>,
+    pub beds: Option<i64>,
+    pub baths: Option<f64>,
+    pub sqft: Option<i64>,
+    pub lot_sqft: Option<i64>,
+    pub property_type: Option<String>,
+    pub year_built: Option<i64>,
+    pub garage: Option<i64>,
+    pub stories: Option<i64>,
+    pub is_new_construction: Option<bool>,
 }
 
 /// Represents a single change to a tracked field, to be stored in `property_history`.
@@ -162,9 +370,93 @@ impl TrackedProperty {
         compare_and_log!(is_foreclosure, "is_foreclosure");
         compare_and_log!(is_price_reduced, "is_price_reduced");
         compare_and_log!(is_coming_soon, "is_coming_soon");
+        compare_and_log!(beds, "beds");
+        compare_and_log!(baths, "baths");
+        compare_and_log!(sqft, "sqft");
+        compare_and_log!(lot_sqft, "lot_sqft");
+        compare_and_log!(property_type, "property_type");
+        compare_and_log!(year_built, "year_built");
+        compare_and_log!(garage, "garage");
+        compare_and_log!(stories, "stories");
+        compare_and_log!(is_new_construction, "is_new_construction");
 
         changes
     }
+
+    /// Sets a tracked field from a `property_history` value, undoing the
+    /// formatting `diff` applied. An empty value is a cleared field. Returns
+    /// false for fields this struct doesn't track.
+    pub fn apply_history_value(&mut self, field_name: &str, value: &str) -> bool {
+        macro_rules! apply {
+            ($($field:ident),*) => {
+                match field_name {
+                    $(stringify!($field) => {
+                        self.$field = Some(value)
+                            .filter(|v| !v.is_empty())
+                            .and_then(HistoryValue::parse_history)
+                    })*
+                    _ => return false,
+                }
+            };
+        }
+        apply!(
+            status,
+            list_price,
+            sold_price,
+            sold_date,
+            is_pending,
+            is_contingent,
+            is_new_listing,
+            is_foreclosure,
+            is_price_reduced,
+            is_coming_soon,
+            beds,
+            baths,
+            sqft,
+            lot_sqft,
+            property_type,
+            year_built,
+            garage,
+            stories,
+            is_new_construction
+        );
+        true
+    }
+}
+
+/// Reads back a value `diff` wrote with `to_string()`.
+trait HistoryValue: Sized {
+    fn parse_history(value: &str) -> Option<Self>;
+}
+
+impl HistoryValue for String {
+    fn parse_history(value: &str) -> Option<Self> {
+        Some(value.to_string())
+    }
+}
+
+impl HistoryValue for i64 {
+    fn parse_history(value: &str) -> Option<Self> {
+        value.parse().ok()
+    }
+}
+
+impl HistoryValue for f64 {
+    fn parse_history(value: &str) -> Option<Self> {
+        value.parse().ok()
+    }
+}
+
+impl HistoryValue for bool {
+    fn parse_history(value: &str) -> Option<Self> {
+        value.parse().ok()
+    }
+}
+
+impl HistoryValue for NaiveDateTime {
+    fn parse_history(value: &str) -> Option<Self> {
+        NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f").ok()
+    }
 }
 
 #[cfg(test)]
@@ -192,6 +484,15 @@ mod tests {
             is_foreclosure: Some(false),
             is_price_reduced: Some(false),
             is_coming_soon: Some(false),
+            beds: Some(3),
+            baths: Some(2.0),
+            sqft: Some(1850),
+            lot_sqft: Some(6534),
+            property_type: Some("single_family".to_string()),
+            year_built: Some(1998),
+            garage: Some(2),
+            stories: Some(1),
+            is_new_construction: Some(false),
         };
 
         // This represents the new data we just scraped for the same property.
@@ -199,6 +500,7 @@ mod tests {
             // Address fields are not used in the diff, so they can be dummy values.
             source_name: "test".to_string(),
             source_listing_id: "123".to_string(),
+            site: REALTOR_SITE,
             address_line: "123 Main".to_string(),
             city: "Anytown".to_string(),
             postal_code: "12345".to_string(),
@@ -216,13 +518,29 @@ mod tests {
             is_foreclosure: Some(true),             // Changed from false to true
             is_price_reduced: Some(true),           // Changed from false to true
             is_coming_soon: Some(true),             // Changed from false to true
+            beds: Some(4),                          // Changed (basement finished)
+            baths: Some(2.0),
+            sqft: Some(1850),
+            lot_sqft: Some(6534),
+            property_type: Some("single_family".to_string()),
+            year_built: Some(1998),
+            garage: Some(2),
+            stories: Some(1),
+            is_new_construction: Some(false),
+
+            // Descriptive fields are never diffed.
+            subdivision: Some("Old Mill".to_string()),
+            latitude: Some(40.6),
+            longitude: Some(-111.9),
+            county_fips: Some(49035),
+            open_houses: None,
         };
 
         // Get the list of changes.
         let changes = before.diff(&after);
 
-        // We expect exactly 9 fields to have changed.
-        assert_eq!(changes.len(), 9);
+        // We expect exactly 10 fields to have changed.
+        assert_eq!(changes.len(), 10);
 
         // Helper to find a specific change in the vector for easier assertions.
         let find_change = |name: &str| {
@@ -275,5 +593,15 @@ mod tests {
         let coming_soon_change = find_change("is_coming_soon");
         assert_eq!(coming_soon_change.previous_value, Some("false".to_string()));
         assert_eq!(coming_soon_change.current_value, "true".to_string());
+
+        let beds_change = find_change("beds");
+        assert_eq!(beds_change.previous_value, Some("3".to_string()));
+        assert_eq!(beds_change.current_value, "4".to_string());
+
+        // A partial source that leaves `is_contingent` out isn't clearing it.
+        let mut partial = after.clone();
+        partial.fill_unreported(&before);
+        assert_eq!(partial.is_contingent, Some(true));
+        assert_eq!(before.diff(&partial).len(), 9);
     }
 }
@@ -0,0 +1,72 @@
+// src/domain/property_page.rs
+
+use crate::domain::property::TrackedProperty;
+use chrono::NaiveDateTime;
+
+/// Everything the single-property page shows.
+#[derive(Debug)]
+pub struct PropertyPageViewModel {
+    // Address
+    pub address_line: String,
+    pub city: String,
+    pub state_abbr: Option<String>,
+    pub postal_code: String,
+    pub county_name: Option<String>,
+    pub subdivision: Option<String>,
+
+    /// Current tracked fields, as stored in `properties`.
+    pub tracked: TrackedProperty,
+    pub canonical_status: &'static str,
+
+    // Lifecycle
+    pub first_seen_at: NaiveDateTime,
+    pub last_seen_at: NaiveDateTime,
+    pub delisted_at: Option<NaiveDateTime>,
+
+    // Detail page enrichment, if it was fetched
+    pub listing_agent: Option<String>,
+    pub brokerage: Option<String>,
+    pub detail_url: Option<String>,
+
+    /// Every listing linked to the property, oldest first.
+    pub sources: Vec<SourceLink>,
+    /// Every `property_history` row, oldest first.
+    pub timeline: Vec<TimelineEntry>,
+}
+
+/// A `property_sources` row.
+#[derive(Debug)]
+pub struct SourceLink {
+    pub source_name: String,
+    pub source_listing_id: String,
+    pub first_seen_at: NaiveDateTime,
+    pub last_seen_at: NaiveDateTime,
+}
+
+/// A `property_history` row, with the sites that reported it.
+#[derive(Debug)]
+pub struct TimelineEntry {
+    pub observed_at: NaiveDateTime,
+    pub field_name: String,
+    pub previous_value: Option<String>,
+    pub current_value: String,
+    /// Comma-separated, e.g. "realtor, zillow". Empty when no site is credited.
+    pub reported_by: String,
+}
+
+impl PropertyPageViewModel {
+    /// List prices from the timeline, oldest first. Cleared prices are skipped.
+    pub fn price_points(&self) -> Vec<(NaiveDateTime, i64)> {
+        self.timeline
+            .iter()
+            .filter(|e| e.field_name == "list_price")
+            .filter_map(|e| Some((e.observed_at, e.current_value.parse().ok()?)))
+            .collect()
+    }
+
+    /// Where the price chart ends: when the property dropped off the site,
+    /// or when it was last seen.
+    pub fn price_points_until(&self) -> NaiveDateTime {
+        self.delisted_at.unwrap_or(self.last_seen_at)
+    }
+}
@@ -10,6 +10,9 @@ use std::fmt;
 pub enum ServerError {
     NotFound,
     BadRequest(String),
+    /// The request clashes with work already in progress, like a scrape
+    /// that's already running.
+    Conflict(String),
     Unauthorized(String),
     DbError(String),
     InternalError,
@@ -21,6 +24,7 @@ impl fmt::Display for ServerError {
         match self {
             ServerError::NotFound => write!(f, "Not Found"),
             ServerError::BadRequest(msg) => write!(f, "Bad Request: {msg}"),
+            ServerError::Conflict(msg) => write!(f, "Conflict: {msg}"),
             ServerError::Unauthorized(msg) => write!(f, "Unauthorized: {msg}"),
             ServerError::DbError(msg) => write!(f, "Database Error: {msg}"),
             ServerError::InternalError => write!(f, "Internal Server Error"),
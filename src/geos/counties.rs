@@ -0,0 +1,119 @@
+use crate::errors::ServerError;
+use crate::geos::states::state_fips;
+use rusqlite::{params, Connection};
+
+/// A county from the Census reference data, keyed by its 5-digit GEOID (SSCCC).
+#[derive(Debug, Clone, PartialEq)]
+pub struct County {
+    pub geoid: String,
+    pub state_fips: String,
+    pub county_fips: String,
+    pub name: String,
+}
+
+/// Parses the Census Gazetteer county file (`*_Gaz_counties_national.txt`).
+///
+/// The file is tab separated with a header row; only `GEOID` and `NAME` are used.
+pub fn parse_gazetteer_counties(text: &str) -> Result<Vec<County>, String> {
+    let mut lines = text.lines();
+    let header: Vec<&str> = lines
+        .next()
+        .ok_or("empty gazetteer file")?
+        .split('\t')
+        .map(str::trim)
+        .collect();
+
+    let col = |name: &str| {
+        header
+            .iter()
+            .position(|h| *h == name)
+            .ok_or(format!("gazetteer file has no {name} column"))
+    };
+    let geoid_col = col("GEOID")?;
+    let name_col = col("NAME")?;
+
+    let mut counties = Vec::new();
+    for line in lines.filter(|l| !l.trim().is_empty()) {
+        let fields: Vec<&str> = line.split('\t').map(str::trim).collect();
+        let (Some(geoid), Some(name)) = (fields.get(geoid_col), fields.get(name_col)) else {
+            continue;
+        };
+        if geoid.len() != 5 {
+            continue;
+        }
+
+        counties.push(County {
+            geoid: geoid.to_string(),
+            state_fips: geoid[..2].to_string(),
+            county_fips: geoid[2..].to_string(),
+            name: name.to_string(),
+        });
+    }
+    Ok(counties)
+}
+
+/// Loads a Gazetteer county file into the `county` table. Returns rows written.
+pub fn load_counties(conn: &mut Connection, path: &str) -> Result<usize, ServerError> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| ServerError::DbError(format!("Failed to read {path}: {e}")))?;
+    let counties = parse_gazetteer_counties(&text).map_err(ServerError::DbError)?;
+
+    let tx = conn.transaction()?;
+    {
+        let mut stmt = tx.prepare(
+            "INSERT OR REPLACE INTO county (geoid, state_fips, county_fips, name) VALUES (?1, ?2, ?3, ?4)",
+        )?;
+        for c in &counties {
+            stmt.execute(params![c.geoid, c.state_fips, c.county_fips, c.name])?;
+        }
+    }
+    tx.commit()?;
+    Ok(counties.len())
+}
+
+/// County names for a state from the Census reference table, e.g.
+/// "Salt Lake County".
+///
+/// Errors if the table has no counties for the state: the counties seen on
+/// scraped properties would leave out any we've never found a listing in.
+pub fn counties_for_state(conn: &Connection, state_abbr: &str) -> Result<Vec<String>, ServerError> {
+    let fips = state_fips(state_abbr)
+        .ok_or_else(|| ServerError::BadRequest(format!("Unknown state {state_abbr}")))?;
+
+    let mut stmt = conn.prepare("SELECT name FROM county WHERE state_fips = ?1 ORDER BY name")?;
+    let rows = stmt.query_map(params![fips], |r| r.get(0))?;
+    let mut names = Vec::new();
+    for r in rows {
+        names.push(r?);
+    }
+    if names.is_empty() {
+        return Err(ServerError::BadRequest(format!(
+            "No Census counties loaded for {state_abbr}; set GEO_DATA_DIR (see README)"
+        )));
+    }
+    Ok(names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_gazetteer_rows_by_header() {
+        let text = "USPS\tGEOID\tANSICODE\tNAME\tALAND\n\
+                    UT\t49035\t01448031\tSalt Lake County\t1922306046\n\
+                    UT\t49049\t01448039\tUtah County\t5194834562\n";
+
+        let counties = parse_gazetteer_counties(text).unwrap();
+        assert_eq!(counties.len(), 2);
+        assert_eq!(
+            counties[0],
+            County {
+                geoid: "49035".into(),
+                state_fips: "49".into(),
+                county_fips: "035".into(),
+                name: "Salt Lake County".into(),
+            }
+        );
+    }
+}
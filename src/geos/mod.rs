@@ -3,3 +3,59 @@ pub mod states;
 pub mod zips;
 
 pub use states::US_STATES;
+
+use crate::db::connection::Database;
+use crate::errors::ServerError;
+use rusqlite::Connection;
+
+/// Creates the county / ZCTA reference tables.
+pub fn init_geo_schema(conn: &Connection) -> Result<(), ServerError> {
+    conn.execute_batch(include_str!("geo_schema.sql"))
+        .map_err(|e| ServerError::DbError(format!("Failed to apply geo schema: {e}")))
+}
+
+/// Loads the Census reference files from `dir`: `counties.txt` (Gazetteer
+/// counties) and `zcta_county.txt` (ZCTA-to-county relationship file). See
+/// the README for where to get them. Both are required, and neither may be
+/// empty.
+pub fn load_reference_data(db: &Database, dir: &str) -> Result<(), ServerError> {
+    let counties_path = format!("{dir}/counties.txt");
+    let zcta_path = format!("{dir}/zcta_county.txt");
+    for path in [&counties_path, &zcta_path] {
+        if !std::path::Path::new(path).exists() {
+            return Err(ServerError::DbError(format!(
+                "{path} not found; GEO_DATA_DIR needs counties.txt and zcta_county.txt (see README)"
+            )));
+        }
+    }
+
+    db.with_conn(|conn| {
+        let n = counties::load_counties(conn, &counties_path)?;
+        if n == 0 {
+            return Err(ServerError::DbError(format!(
+                "{counties_path} has no counties"
+            )));
+        }
+        println!("✅ Loaded {n} counties from {counties_path}");
+
+        let n = zips::load_zcta_counties(conn, &zcta_path)?;
+        if n == 0 {
+            return Err(ServerError::DbError(format!(
+                "{zcta_path} has no ZCTA-county links"
+            )));
+        }
+        println!("✅ Loaded {n} ZCTA-county links from {zcta_path}");
+        Ok(())
+    })
+}
+
+/// Whether the county and ZCTA tables have been loaded, i.e. whether county
+/// and ZIP shards can be planned.
+pub fn reference_data_loaded(conn: &Connection) -> Result<bool, ServerError> {
+    conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM county) AND EXISTS(SELECT 1 FROM zcta_county)",
+        [],
+        |row| row.get(0),
+    )
+    .map_err(|e| ServerError::DbError(format!("Failed to check geo reference data: {e}")))
+}
@@ -50,3 +50,71 @@ pub const US_STATES: &[(&str, &str)] = &[
     ("WI", "Wisconsin"),
     ("WY", "Wyoming"),
 ];
+
+/// Census state FIPS codes, keyed by USPS abbreviation.
+pub const STATE_FIPS: &[(&str, &str)] = &[
+    ("AL", "01"),
+    ("AK", "02"),
+    ("AZ", "04"),
+    ("AR", "05"),
+    ("CA", "06"),
+    ("CO", "08"),
+    ("CT", "09"),
+    ("DE", "10"),
+    ("FL", "12"),
+    ("GA", "13"),
+    ("HI", "15"),
+    ("ID", "16"),
+    ("IL", "17"),
+    ("IN", "18"),
+    ("IA", "19"),
+    ("KS", "20"),
+    ("KY", "21"),
+    ("LA", "22"),
+    ("ME", "23"),
+    ("MD", "24"),
+    ("MA", "25"),
+    ("MI", "26"),
+    ("MN", "27"),
+    ("MS", "28"),
+    ("MO", "29"),
+    ("MT", "30"),
+    ("NE", "31"),
+    ("NV", "32"),
+    ("NH", "33"),
+    ("NJ", "34"),
+    ("NM", "35"),
+    ("NY", "36"),
+    ("NC", "37"),
+    ("ND", "38"),
+    ("OH", "39"),
+    ("OK", "40"),
+    ("OR", "41"),
+    ("PA", "42"),
+    ("RI", "44"),
+    ("SC", "45"),
+    ("SD", "46"),
+    ("TN", "47"),
+    ("TX", "48"),
+    ("UT", "49"),
+    ("VT", "50"),
+    ("VA", "51"),
+    ("WA", "53"),
+    ("WV", "54"),
+    ("WI", "55"),
+    ("WY", "56"),
+];
+
+pub fn state_name(abbr: &str) -> Option<&'static str> {
+    US_STATES
+        .iter()
+        .find(|(a, _)| a.eq_ignore_ascii_case(abbr))
+        .map(|(_, name)| *name)
+}
+
+pub fn state_fips(abbr: &str) -> Option<&'static str> {
+    STATE_FIPS
+        .iter()
+        .find(|(a, _)| a.eq_ignore_ascii_case(abbr))
+        .map(|(_, fips)| *fips)
+}
@@ -0,0 +1,115 @@
+use crate::errors::ServerError;
+use crate::geos::states::state_fips;
+use rusqlite::{params, Connection};
+
+/// Parses the Census ZCTA-to-county relationship file
+/// (`tab20_zcta520_county20_natl.txt`) into `(zcta, county_geoid)` pairs.
+///
+/// The file is pipe delimited with a header row. Rows for counties with no
+/// ZCTA (water, unpopulated areas) are skipped.
+pub fn parse_zcta_county_relationships(text: &str) -> Result<Vec<(String, String)>, String> {
+    let mut lines = text.lines();
+    let header: Vec<&str> = lines
+        .next()
+        .ok_or("empty relationship file")?
+        .trim_start_matches('\u{feff}')
+        .split('|')
+        .map(str::trim)
+        .collect();
+
+    let col = |name: &str| {
+        header
+            .iter()
+            .position(|h| *h == name)
+            .ok_or(format!("relationship file has no {name} column"))
+    };
+    let zcta_col = col("GEOID_ZCTA5_20")?;
+    let county_col = col("GEOID_COUNTY_20")?;
+
+    let mut pairs = Vec::new();
+    for line in lines {
+        let fields: Vec<&str> = line.split('|').map(str::trim).collect();
+        match (fields.get(zcta_col), fields.get(county_col)) {
+            (Some(zcta), Some(county)) if zcta.len() == 5 && county.len() == 5 => {
+                pairs.push((zcta.to_string(), county.to_string()));
+            }
+            _ => continue,
+        }
+    }
+    Ok(pairs)
+}
+
+/// Loads the ZCTA-county relationship file into `zcta` and `zcta_county`.
+/// Counties must already be loaded. Returns rows written.
+pub fn load_zcta_counties(conn: &mut Connection, path: &str) -> Result<usize, ServerError> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| ServerError::DbError(format!("Failed to read {path}: {e}")))?;
+    let pairs = parse_zcta_county_relationships(&text).map_err(ServerError::DbError)?;
+
+    let tx = conn.transaction()?;
+    {
+        let mut zcta_stmt = tx.prepare("INSERT OR IGNORE INTO zcta (zcta) VALUES (?1)")?;
+        let mut link_stmt = tx.prepare(
+            "INSERT OR IGNORE INTO zcta_county (zcta, county_geoid)
+             SELECT ?1, geoid FROM county WHERE geoid = ?2",
+        )?;
+        for (zcta, county) in &pairs {
+            zcta_stmt.execute(params![zcta])?;
+            link_stmt.execute(params![zcta, county])?;
+        }
+    }
+    tx.commit()?;
+    Ok(pairs.len())
+}
+
+/// ZIP codes (ZCTAs) that fall in a state, from the Census reference tables.
+///
+/// Errors if the tables have no ZCTAs for the state: the postal codes seen on
+/// scraped properties would leave out any we've never found a listing in.
+pub fn zips_for_state(conn: &Connection, state_abbr: &str) -> Result<Vec<String>, ServerError> {
+    let fips = state_fips(state_abbr)
+        .ok_or_else(|| ServerError::BadRequest(format!("Unknown state {state_abbr}")))?;
+
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT DISTINCT zc.zcta
+        FROM zcta_county zc
+        JOIN county c ON c.geoid = zc.county_geoid
+        WHERE c.state_fips = ?1
+        ORDER BY zc.zcta
+        "#,
+    )?;
+    let rows = stmt.query_map(params![fips], |r| r.get(0))?;
+    let mut zips = Vec::new();
+    for r in rows {
+        zips.push(r?);
+    }
+    if zips.is_empty() {
+        return Err(ServerError::BadRequest(format!(
+            "No Census ZCTAs loaded for {state_abbr}; set GEO_DATA_DIR (see README)"
+        )));
+    }
+    Ok(zips)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_relationship_rows_and_skips_blank_zctas() {
+        let text = "OID_ZCTA5_20|GEOID_ZCTA5_20|NAMELSAD_ZCTA5_20|OID_COUNTY_20|GEOID_COUNTY_20\n\
+                    1|84101|ZCTA5 84101|9|49035\n\
+                    ||||49035\n\
+                    2|84604|ZCTA5 84604|10|49049\n";
+
+        let pairs = parse_zcta_county_relationships(text).unwrap();
+        assert_eq!(
+            pairs,
+            vec![
+                ("84101".to_string(), "49035".to_string()),
+                ("84604".to_string(), "49049".to_string()),
+            ]
+        );
+    }
+}
@@ -4,6 +4,7 @@ use astra::Server;
 use std::net::SocketAddr;
 
 mod auth;
+mod calendar;
 mod db;
 mod domain;
 mod errors;
@@ -29,6 +30,86 @@ fn main() {
         std::process::exit(1);
     }
 
+    // Census county / ZCTA reference data, needed for county and ZIP shards
+    match std::env::var("GEO_DATA_DIR") {
+        Ok(dir) => {
+            if let Err(e) = geos::load_reference_data(&db, &dir) {
+                eprintln!("❌ Failed to load geo reference data: {e}");
+                std::process::exit(1);
+            }
+        }
+        Err(_) => eprintln!("⚠️ GEO_DATA_DIR not set; county and ZIP shards are unavailable"),
+    }
+
+    // `reprocess [FROM_RUN_ID]`: rebuild properties from the page archive and exit
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("reprocess") {
+        let from_run = args.get(2).and_then(|s| s.parse().ok());
+        match scraper::RealtorScraper::reprocess_archive(&db, from_run) {
+            Ok(s) => println!(
+                "✅ Re-processed {} archived pages: {} new, {} changed, {} quarantined",
+                s.pages, s.new, s.changed, s.quarantined
+            ),
+            Err(e) => {
+                eprintln!("❌ Re-process failed: {e}");
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    // `import SITE FILE...`: save listings from saved search pages and exit
+    if args.get(1).map(String::as_str) == Some("import") {
+        let Some(adapter) = args
+            .get(2)
+            .and_then(|site| scraper::sources::adapter_for(site))
+        else {
+            eprintln!("❌ Usage: import zillow FILE...");
+            std::process::exit(1);
+        };
+        for path in &args[3..] {
+            let result = std::fs::read_to_string(path)
+                .map_err(|e| e.to_string())
+                .and_then(|html| {
+                    scraper::sources::import_search_page(
+                        &db,
+                        adapter.as_ref(),
+                        &html,
+                        chrono::Utc::now().naive_utc(),
+                    )
+                    .map_err(|e| e.to_string())
+                });
+            match result {
+                Ok(s) => println!(
+                    "✅ {path}: {} new, {} changed, {} unchanged, {} rejected (search total {})",
+                    s.new,
+                    s.changed,
+                    s.unchanged,
+                    s.rejected,
+                    s.total.map_or("unknown".to_string(), |n| n.to_string())
+                ),
+                Err(e) => eprintln!("❌ {path}: {e}"),
+            }
+        }
+        return;
+    }
+
+    // Runs live on threads in this process, so anything still open is dead.
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    match db.with_conn(|conn| db::scrapes::fail_interrupted_runs(conn, now)) {
+        Ok(0) => {}
+        Ok(n) => println!("⚠️ Marked {n} interrupted scrape run(s) as failed"),
+        Err(e) => eprintln!("❌ Failed to close interrupted scrape runs: {e}"),
+    }
+
+    // Launch scheduled scrapes as they come due
+    scraper::scheduler::start_scheduler(&db);
+    // Fill in each day's market stats once it's over
+    scraper::scheduler::start_market_stats_job(&db);
+
     // 3️⃣ Start the server
     let host = std::env::var("HOST").unwrap_or_else(|_| "127.0.0.1".to_string());
     let port = std::env::var("PORT").unwrap_or_else(|_| "3000".to_string());
@@ -0,0 +1,19 @@
+// responses/ics.rs
+use crate::errors::ServerError;
+use crate::responses::ResultResp;
+use astra::{Body, ResponseBuilder};
+
+/// Return an iCalendar feed as HTTP response
+pub fn ics_response(calendar: String, filename: &str) -> ResultResp {
+    let resp = ResponseBuilder::new()
+        .status(200)
+        .header("Content-Type", "text/calendar; charset=utf-8")
+        .header(
+            "Content-Disposition",
+            format!("inline; filename=\"{filename}\""),
+        )
+        .body(Body::from(calendar))
+        .map_err(|_| ServerError::InternalError)?;
+
+    Ok(resp)
+}
@@ -1,5 +1,6 @@
 pub mod errors;
 pub mod html;
+pub mod ics;
 pub mod xlsx;
 
 // These two *are* in responses/errors.rs
@@ -7,4 +8,5 @@ pub use errors::{html_error_response, ResultResp};
 
 // Normal HTML response
 pub use html::html_response;
+pub use ics::ics_response;
 pub use xlsx::xlsx_response;
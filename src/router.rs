@@ -2,9 +2,12 @@ use crate::db::connection::Database;
 
 use crate::errors::ServerError;
 use crate::mailer::BrevoMailer;
-use crate::responses::{html_response, ResultResp};
+use crate::responses::{html_response, ics_response, ResultResp};
+use crate::scraper::config::ConfigOverrides;
+use crate::scraper::scraper::{ScrapeMode, SearchKind};
+use crate::scraper::shards::ShardBy;
 use crate::scraper::RealtorScraper;
-use crate::spreadsheets::export_changes_xlsx;
+use crate::spreadsheets::{export_as_of_xlsx, export_changes_xlsx};
 
 use crate::templates;
 use crate::templates::pages::admin::AdminVm;
@@ -72,6 +75,25 @@ fn current_user(
         .map_err(|e| ServerError::DbError(e.to_string()))
 }
 
+/// The signed-in user's id if they're an admin. Admin routes call this
+/// before doing anything else.
+fn require_admin(req: &Request, db: &Database, now: i64) -> Result<i64, ServerError> {
+    let Some((user_id, _)) = current_user(req, db, now)? else {
+        return Err(ServerError::Unauthorized("Login required".into()));
+    };
+    let is_admin = db.with_conn(|conn| crate::db::users::is_user_admin(conn, user_id))?;
+    if !is_admin {
+        return Err(ServerError::Unauthorized("Admin access required".into()));
+    }
+    Ok(user_id)
+}
+
+/// Where the site is served from, for links that leave it: emails, exports
+/// and calendar feeds.
+fn base_url() -> String {
+    std::env::var("BASE_URL").unwrap_or_else(|_| "http://localhost:3000".to_string())
+}
+
 fn query_param(req: &Request, key: &str) -> Option<String> {
     req.uri().query().and_then(|q| {
         for (k, v) in url::form_urlencoded::parse(q.as_bytes()) {
@@ -96,6 +118,70 @@ fn form_first(pairs: &[(String, String)], key: &str) -> Option<String> {
     pairs.iter().find(|(k, _)| k == key).map(|(_, v)| v.clone())
 }
 
+/// Reads and validates the admin schedule form.
+fn parse_schedule_form(
+    pairs: &[(String, String)],
+) -> Result<crate::db::schedules::ScheduleForm, ServerError> {
+    let state = form_first(pairs, "state")
+        .filter(|s| crate::geos::states::state_name(s).is_some())
+        .ok_or_else(|| ServerError::BadRequest("Invalid state".into()))?;
+    let shard_by = form_first(pairs, "shard_by").unwrap_or_else(|| "state".into());
+    if ShardBy::parse(&shard_by).is_none() {
+        return Err(ServerError::BadRequest("Invalid shard_by".into()));
+    }
+    let mode = form_first(pairs, "mode").unwrap_or_else(|| "full".into());
+    let stop_after = form_first(pairs, "stop_after").and_then(|s| s.parse::<i64>().ok());
+    if ScrapeMode::parse(&mode, None).is_none() {
+        return Err(ServerError::BadRequest("Invalid mode".into()));
+    }
+    let search_kind = form_first(pairs, "search_kind").unwrap_or_else(|| "for_sale".into());
+    if SearchKind::parse(&search_kind).is_none() {
+        return Err(ServerError::BadRequest("Invalid search_kind".into()));
+    }
+    let cadence_hours = form_first(pairs, "cadence_hours")
+        .and_then(|s| s.parse::<i64>().ok())
+        .filter(|h| *h >= 1)
+        .ok_or_else(|| ServerError::BadRequest("cadence_hours must be at least 1".into()))?;
+
+    Ok(crate::db::schedules::ScheduleForm {
+        state,
+        stop_after: if mode == "incremental" {
+            stop_after
+        } else {
+            None
+        },
+        shard_by,
+        mode,
+        search_kind,
+        cadence_hours,
+    })
+}
+
+/// Reads the admin scrape form's run settings. Blank fields are left unset.
+fn parse_config_overrides(pairs: &[(String, String)]) -> Result<ConfigOverrides, ServerError> {
+    fn field<T: std::str::FromStr>(
+        pairs: &[(String, String)],
+        key: &str,
+    ) -> Result<Option<T>, ServerError> {
+        form_first(pairs, key)
+            .filter(|v| !v.trim().is_empty())
+            .map(|v| {
+                v.trim()
+                    .parse()
+                    .map_err(|_| ServerError::BadRequest(format!("Invalid {key}")))
+            })
+            .transpose()
+    }
+
+    Ok(ConfigOverrides {
+        page_cap: field(pairs, "page_cap")?,
+        page_delay_secs: field(pairs, "page_delay_secs")?,
+        max_page_failures: field(pairs, "max_page_failures")?,
+        enrich_details: field(pairs, "enrich_details")?,
+        proxy_country: field(pairs, "proxy_country")?,
+    })
+}
+
 pub fn handle(mut req: Request, db: &Database) -> ResultResp {
     // Clone path parts to avoid borrow checker issues with mutable body reading
     let method = req.method().as_str().to_string();
@@ -136,11 +222,22 @@ pub fn handle(mut req: Request, db: &Database) -> ResultResp {
                 db.with_conn(|conn| crate::db::users::get_all_users_with_stats(conn, now))?;
             let plans = db.with_conn(|conn| crate::db::plans::get_all_plans(conn))?;
             let scrapes = db.with_conn(|conn| crate::db::scrapes::get_recent_scrapes(conn))?;
+            let schedules = db.with_conn(|conn| crate::db::schedules::list_schedules(conn))?;
+            let quarantined =
+                db.with_conn(|conn| crate::db::quarantine::count_open_quarantine(conn))?;
+            let duplicates =
+                db.with_conn(|conn| crate::db::duplicates::count_open_candidates(conn))?;
+            let geo_data_loaded = db.with_conn(|conn| crate::geos::reference_data_loaded(conn))?;
 
             html_response(templates::pages::admin_page(&AdminVm {
                 users,
                 plans,
                 scrapes,
+                schedules,
+                jobs: crate::scraper::jobs::registry().snapshot(),
+                quarantined,
+                duplicates,
+                geo_data_loaded,
             }))
         }
 
@@ -171,18 +268,266 @@ pub fn handle(mut req: Request, db: &Database) -> ResultResp {
             let state_abbr = form_first(&pairs, "state")
                 .ok_or_else(|| ServerError::BadRequest("state is required".into()))?;
 
-            // 4. Lookup Full Name
-            let state_name = crate::geos::US_STATES
-                .iter()
-                .find(|(abbr, _)| *abbr == state_abbr)
-                .map(|(_, name)| name.to_string())
-                .ok_or_else(|| ServerError::BadRequest("Invalid state".into()))?;
+            // 4. Whole state unless split by county / ZIP
+            let shard_by = form_first(&pairs, "shard_by")
+                .map(|s| {
+                    ShardBy::parse(&s)
+                        .ok_or_else(|| ServerError::BadRequest("Invalid shard_by".into()))
+                })
+                .transpose()?
+                .unwrap_or(ShardBy::State);
+
+            // 5. Full sweep or incremental (newest first, stop at known listings)
+            let stop_after = form_first(&pairs, "stop_after").and_then(|s| s.parse().ok());
+            let mode = ScrapeMode::parse(
+                form_first(&pairs, "mode").as_deref().unwrap_or("full"),
+                stop_after,
+            )
+            .ok_or_else(|| ServerError::BadRequest("Invalid mode".into()))?;
+
+            // 6. For-sale, recently sold or rental inventory
+            let kind = form_first(&pairs, "search_kind")
+                .map(|s| {
+                    SearchKind::parse(&s)
+                        .ok_or_else(|| ServerError::BadRequest("Invalid search_kind".into()))
+                })
+                .transpose()?
+                .unwrap_or(SearchKind::ForSale);
+
+            // 7. Settings for this run only; blank keeps the configured value
+            let overrides = parse_config_overrides(&pairs)?;
+
+            // 8. Start Scrape
+            RealtorScraper::start_state_scrape(
+                db,
+                crate::scraper::jobs::registry(),
+                &state_abbr,
+                shard_by,
+                mode,
+                kind,
+                &overrides,
+            )?;
+
+            Ok(ResponseBuilder::new()
+                .status(302)
+                .header("Location", "/admin")
+                .body(Body::empty())
+                .unwrap())
+        }
+
+        ("POST", path) if path.starts_with("/admin/scrapes/") && path.ends_with("/resume") => {
+            require_admin(&req, db, now)?;
+
+            let parts: Vec<&str> = path.split('/').collect();
+            let run_id = parts
+                .get(3)
+                .and_then(|s| s.parse::<i64>().ok())
+                .ok_or(ServerError::BadRequest("Invalid run id".into()))?;
+
+            RealtorScraper::resume_realtor_scrape(db, run_id)?;
+
+            Ok(ResponseBuilder::new()
+                .status(302)
+                .header("Location", "/admin")
+                .body(Body::empty())
+                .unwrap())
+        }
+
+        ("GET", "/admin/jobs") => {
+            require_admin(&req, db, now)?;
+
+            html_response(templates::pages::admin::jobs_table(
+                &crate::scraper::jobs::registry().snapshot(),
+            ))
+        }
+
+        ("POST", path) if path.starts_with("/admin/jobs/") && path.ends_with("/cancel") => {
+            require_admin(&req, db, now)?;
+
+            let parts: Vec<&str> = path.split('/').collect();
+            let job_id = parts
+                .get(3)
+                .and_then(|s| s.parse::<u64>().ok())
+                .ok_or(ServerError::BadRequest("Invalid job id".into()))?;
+
+            if !crate::scraper::jobs::registry().cancel(job_id) {
+                return Err(ServerError::NotFound);
+            }
+
+            Ok(ResponseBuilder::new()
+                .status(302)
+                .header("Location", "/admin")
+                .body(Body::empty())
+                .unwrap())
+        }
+
+        ("GET", "/admin/quarantine") => {
+            require_admin(&req, db, now)?;
+
+            let records =
+                db.with_conn(|conn| crate::db::quarantine::list_open_quarantine(conn, 200))?;
+            let open = db.with_conn(|conn| crate::db::quarantine::count_open_quarantine(conn))?;
+
+            html_response(templates::pages::admin::quarantine_page(&records, open))
+        }
+
+        ("POST", "/admin/quarantine/reprocess") => {
+            require_admin(&req, db, now)?;
+
+            crate::db::quarantine::reprocess_all_quarantined(db, now)?;
+
+            Ok(ResponseBuilder::new()
+                .status(302)
+                .header("Location", "/admin/quarantine")
+                .body(Body::empty())
+                .unwrap())
+        }
+
+        ("POST", path)
+            if path.starts_with("/admin/quarantine/") && path.ends_with("/reprocess") =>
+        {
+            require_admin(&req, db, now)?;
+
+            let parts: Vec<&str> = path.split('/').collect();
+            let id = parts
+                .get(3)
+                .and_then(|s| s.parse::<i64>().ok())
+                .ok_or(ServerError::BadRequest("Invalid quarantine id".into()))?;
+
+            crate::db::quarantine::reprocess_quarantined(db, id, now)?;
+
+            Ok(ResponseBuilder::new()
+                .status(302)
+                .header("Location", "/admin/quarantine")
+                .body(Body::empty())
+                .unwrap())
+        }
+
+        ("GET", "/admin/duplicates") => {
+            require_admin(&req, db, now)?;
+
+            let candidates =
+                db.with_conn(|conn| crate::db::duplicates::list_open_candidates(conn, 100))?;
+            let open = db.with_conn(|conn| crate::db::duplicates::count_open_candidates(conn))?;
+            let merges =
+                db.with_conn(|conn| crate::db::duplicates::list_recent_merges(conn, 20))?;
+
+            html_response(templates::pages::admin::duplicates_page(
+                &candidates,
+                open,
+                &merges,
+            ))
+        }
+
+        ("POST", "/admin/duplicates/scan") => {
+            require_admin(&req, db, now)?;
+
+            db.with_conn(|conn| crate::db::duplicates::detect_duplicates(conn, now))?;
+
+            Ok(ResponseBuilder::new()
+                .status(302)
+                .header("Location", "/admin/duplicates")
+                .body(Body::empty())
+                .unwrap())
+        }
+
+        ("POST", path) if path.starts_with("/admin/duplicates/") && path.ends_with("/merge") => {
+            let user_id = require_admin(&req, db, now)?;
+
+            let parts: Vec<&str> = path.split('/').collect();
+            let id = parts
+                .get(3)
+                .and_then(|s| s.parse::<i64>().ok())
+                .ok_or(ServerError::BadRequest("Invalid candidate id".into()))?;
+
+            let body_bytes = body_to_bytes(&mut req)?;
+            let keep_id = form_urlencoded::parse(&body_bytes)
+                .find(|(k, _)| k == "keep")
+                .and_then(|(_, v)| v.parse::<i64>().ok())
+                .ok_or(ServerError::BadRequest(
+                    "Choose the property to keep".into(),
+                ))?;
+
+            db.with_conn(|conn| {
+                crate::db::duplicates::merge_candidate(conn, id, keep_id, Some(user_id), now)
+            })?;
+
+            Ok(ResponseBuilder::new()
+                .status(302)
+                .header("Location", "/admin/duplicates")
+                .body(Body::empty())
+                .unwrap())
+        }
+
+        ("POST", path) if path.starts_with("/admin/duplicates/") && path.ends_with("/dismiss") => {
+            require_admin(&req, db, now)?;
+
+            let parts: Vec<&str> = path.split('/').collect();
+            let id = parts
+                .get(3)
+                .and_then(|s| s.parse::<i64>().ok())
+                .ok_or(ServerError::BadRequest("Invalid candidate id".into()))?;
+
+            db.with_conn(|conn| crate::db::duplicates::dismiss_candidate(conn, id, now))?;
+
+            Ok(ResponseBuilder::new()
+                .status(302)
+                .header("Location", "/admin/duplicates")
+                .body(Body::empty())
+                .unwrap())
+        }
+
+        ("POST", "/admin/schedules") => {
+            require_admin(&req, db, now)?;
+
+            let body_bytes = body_to_bytes(&mut req)?;
+            let pairs: Vec<(String, String)> =
+                form_urlencoded::parse(&body_bytes).into_owned().collect();
+            let form = parse_schedule_form(&pairs)?;
 
-            // 5. Start Scrape
-            let db_clone = db.clone();
-            std::thread::spawn(move || {
-                RealtorScraper::run_realtor_scrape(&db_clone, state_name, state_abbr);
-            });
+            // First run is due straight away; the scheduler picks it up on its next tick.
+            db.with_conn(|conn| crate::db::schedules::create_schedule(conn, &form, now, now))?;
+
+            Ok(ResponseBuilder::new()
+                .status(302)
+                .header("Location", "/admin")
+                .body(Body::empty())
+                .unwrap())
+        }
+
+        ("POST", path) if path.starts_with("/admin/schedules/") => {
+            require_admin(&req, db, now)?;
+
+            // /admin/schedules/{id} or /admin/schedules/{id}/toggle
+            let parts: Vec<&str> = path.split('/').collect();
+            let schedule_id = parts
+                .get(3)
+                .and_then(|s| s.parse::<i64>().ok())
+                .ok_or(ServerError::BadRequest("Invalid schedule id".into()))?;
+
+            match parts.get(4) {
+                None => {
+                    let body_bytes = body_to_bytes(&mut req)?;
+                    let pairs: Vec<(String, String)> =
+                        form_urlencoded::parse(&body_bytes).into_owned().collect();
+                    let form = parse_schedule_form(&pairs)?;
+                    db.with_conn(|conn| {
+                        crate::db::schedules::update_schedule(conn, schedule_id, &form)
+                    })?;
+                }
+                Some(&"toggle") => {
+                    db.with_conn(|conn| {
+                        let schedule = crate::db::schedules::get_schedule(conn, schedule_id)?
+                            .ok_or(ServerError::NotFound)?;
+                        crate::db::schedules::set_schedule_enabled(
+                            conn,
+                            schedule_id,
+                            !schedule.enabled,
+                        )
+                    })?;
+                }
+                Some(_) => return Err(ServerError::NotFound),
+            }
 
             Ok(ResponseBuilder::new()
                 .status(302)
@@ -578,12 +923,46 @@ pub fn handle(mut req: Request, db: &Database) -> ResultResp {
             let events = db.with_conn(|conn| {
                 crate::db::properties::get_change_events_for_export(conn, &state, year)
             })?;
+            let counties =
+                db.with_conn(|conn| crate::db::cycles::county_cycle_summary(conn, &state, year))?;
+            let market = db.with_conn(|conn| {
+                crate::db::market_stats::market_stats_for_year(conn, &state, year)
+            })?;
 
             // 4. Record Download Event
             db.with_conn(|conn| crate::db::downloads::record_download(conn, user_id, &state, now))?;
 
             // 5. Generate and return spreadsheet
-            export_changes_xlsx(&events, &state, year)
+            let base_url = base_url();
+            export_changes_xlsx(&events, &counties, &market, &state, year, &base_url)
+        }
+        // Every property in a state as it stood at the end of a given day.
+        ("GET", "/export/as-of") => {
+            let user = current_user(&req, db, now)?;
+            let Some((user_id, _)) = user else {
+                return Ok(ResponseBuilder::new()
+                    .status(302)
+                    .header("Location", "/login")
+                    .body(Body::empty())
+                    .unwrap());
+            };
+
+            let state = query_param(&req, "state")
+                .ok_or_else(|| ServerError::BadRequest("state is required".into()))?
+                .to_uppercase();
+            let date = query_param(&req, "date")
+                .ok_or_else(|| ServerError::BadRequest("date is required".into()))?;
+            let as_of = chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+                .ok()
+                .and_then(|d| d.and_hms_opt(23, 59, 59))
+                .ok_or_else(|| ServerError::BadRequest("Invalid date".into()))?;
+
+            let properties =
+                db.with_conn(|conn| crate::db::history::state_as_of(conn, &state, as_of))?;
+
+            db.with_conn(|conn| crate::db::downloads::record_download(conn, user_id, &state, now))?;
+
+            export_as_of_xlsx(&properties, &state, as_of)
         }
         ("GET", "/dashboard") => {
             let user = current_user(&req, db, now)?;
@@ -606,6 +985,71 @@ pub fn handle(mut req: Request, db: &Database) -> ResultResp {
             html_response(templates::pages::dashboard_page(&changes, &years))
         }
 
+        ("GET", path) if path.starts_with("/properties/") => {
+            let user = current_user(&req, db, now)?;
+            let Some((_user_id, _)) = user else {
+                return Ok(ResponseBuilder::new()
+                    .status(302)
+                    .header("Location", "/login")
+                    .body(Body::empty())
+                    .unwrap());
+            };
+
+            let parts: Vec<&str> = path.split('/').collect();
+            let property_id = parts
+                .get(2)
+                .and_then(|s| s.parse::<i64>().ok())
+                .ok_or(ServerError::NotFound)?;
+
+            let page = db
+                .with_conn(|conn| crate::db::properties::get_property_page(conn, property_id))?
+                .ok_or(ServerError::NotFound)?;
+
+            html_response(templates::pages::property_page(&page))
+        }
+
+        // Daily market stats for a state, county or ZIP.
+        ("GET", "/market") => {
+            let user = current_user(&req, db, now)?;
+            let Some((_user_id, _)) = user else {
+                return Ok(ResponseBuilder::new()
+                    .status(302)
+                    .header("Location", "/login")
+                    .body(Body::empty())
+                    .unwrap());
+            };
+
+            let non_empty = |key: &str| query_param(&req, key).filter(|v| !v.trim().is_empty());
+            let date = |key: &str| {
+                non_empty(key)
+                    .map(|d| {
+                        chrono::NaiveDate::parse_from_str(&d, "%Y-%m-%d")
+                            .map_err(|_| ServerError::BadRequest(format!("Invalid {key} date")))
+                    })
+                    .transpose()
+            };
+            let filter = crate::db::market_stats::MarketStatsFilter {
+                state: non_empty("state").unwrap_or_default().to_uppercase(),
+                county: non_empty("county").map(|c| c.trim().to_string()),
+                postal_code: non_empty("zip").map(|z| z.trim().to_string()),
+                from: date("from")?,
+                to: date("to")?,
+            };
+
+            let (counties, rows) = if filter.state.is_empty() {
+                (Vec::new(), Vec::new())
+            } else {
+                db.with_conn(|conn| {
+                    Ok((
+                        crate::db::market_stats::market_stats_counties(conn, &filter.state)?,
+                        crate::db::market_stats::get_market_stats(conn, &filter)?,
+                    ))
+                })?
+            };
+
+            html_response(templates::pages::market_page(&filter, &counties, &rows))
+        }
+
         ("GET", "/dashboard/preview") => {
             let user = current_user(&req, db, now)?;
             let Some((user_id, _)) = user else {
@@ -638,6 +1082,87 @@ pub fn handle(mut req: Request, db: &Database) -> ResultResp {
             html_response(preview_table(&changes, total_count, is_paid))
         }
 
+        ("GET", "/calendar") => {
+            let user = current_user(&req, db, now)?;
+            if user.is_none() {
+                return Ok(ResponseBuilder::new()
+                    .status(302)
+                    .header("Location", "/login")
+                    .body(Body::empty())
+                    .unwrap());
+            }
+
+            let from = crate::db::open_houses::earliest_local_date(chrono::Utc::now().naive_utc());
+            let mut upcoming = db.with_conn(|conn| {
+                crate::db::open_houses::upcoming_open_houses(conn, &Default::default(), from)
+            })?;
+            upcoming.truncate(20);
+
+            html_response(templates::pages::calendar_page(&upcoming, None))
+        }
+
+        ("POST", "/calendar/feed") => {
+            let user = current_user(&req, db, now)?;
+            let Some((user_id, _)) = user else {
+                return Ok(ResponseBuilder::new()
+                    .status(302)
+                    .header("Location", "/login")
+                    .body(Body::empty())
+                    .unwrap());
+            };
+
+            let body_bytes = body_to_bytes(&mut req)?;
+            let pairs: Vec<(String, String)> =
+                form_urlencoded::parse(&body_bytes).into_owned().collect();
+
+            // Each link is for one area, stored with its token.
+            let mut query = form_urlencoded::Serializer::new(String::new());
+            for key in ["state", "county", "zip"] {
+                if let Some(value) = form_first(&pairs, key).filter(|v| !v.trim().is_empty()) {
+                    query.append_pair(key, value.trim());
+                }
+            }
+            let query = query.finish();
+
+            let token = db.with_conn(|conn| {
+                crate::db::open_houses::rotate_feed_token(conn, user_id, &query, now)
+            })?;
+            let base_url = base_url();
+            let feed_url = format!("{base_url}/calendar/{token}.ics");
+
+            let from = crate::db::open_houses::earliest_local_date(chrono::Utc::now().naive_utc());
+            let mut upcoming = db.with_conn(|conn| {
+                crate::db::open_houses::upcoming_open_houses(conn, &Default::default(), from)
+            })?;
+            upcoming.truncate(20);
+
+            html_response(templates::pages::calendar_page(&upcoming, Some(&feed_url)))
+        }
+
+        // Calendar apps can't log in, so the feed is authorized by its token.
+        ("GET", path) if path.starts_with("/calendar/") && path.ends_with(".ics") => {
+            let token = path
+                .trim_start_matches("/calendar/")
+                .trim_end_matches(".ics");
+            // The area comes from the token, whatever the query string says.
+            let feed = db.with_conn(|conn| crate::db::open_houses::feed_for_token(conn, token))?;
+            let Some((_, filter)) = feed else {
+                return Err(ServerError::NotFound);
+            };
+            // Today's open houses stay on the calendar until the day is over
+            // everywhere.
+            let generated_at = chrono::Utc::now().naive_utc();
+            let from = crate::db::open_houses::earliest_local_date(generated_at);
+            let events = db.with_conn(|conn| {
+                crate::db::open_houses::upcoming_open_houses(conn, &filter, from)
+            })?;
+
+            ics_response(
+                crate::calendar::open_houses_ics(&events, generated_at),
+                "open-houses.ics",
+            )
+        }
+
         ("GET", "/auth/magic") => {
             let token = query_param(&req, "token")
                 .ok_or_else(|| ServerError::BadRequest("missing token".into()))?;
@@ -677,8 +1202,7 @@ pub fn handle(mut req: Request, db: &Database) -> ResultResp {
                 let sender_name =
                     std::env::var("SENDER_NAME").unwrap_or_else(|_| "Scraper Simple".to_string());
 
-                let base_url = std::env::var("BASE_URL")
-                    .unwrap_or_else(|_| "http://localhost:3000".to_string());
+                let base_url = base_url();
                 let full_link = format!("{}{}", base_url, issued.link);
 
                 let recipient_email = issued.email.clone();
@@ -717,8 +1241,7 @@ pub fn handle(mut req: Request, db: &Database) -> ResultResp {
                 eprintln!("Missing STRIPE_PRICE_ID");
                 ServerError::InternalError
             })?;
-            let base_url =
-                std::env::var("BASE_URL").unwrap_or_else(|_| "http://localhost:3000".to_string());
+            let base_url = base_url();
 
             let client = reqwest::blocking::Client::new();
             let params = [
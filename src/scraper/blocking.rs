@@ -0,0 +1,113 @@
+// blocking.rs
+//
+// Recognises bot walls and captcha pages so they surface as
+// `ScraperError::Blocked` instead of a confusing `MissingNextData`.
+use crate::scraper::fetcher::FetchedPage;
+
+/// Markup seen on captcha / bot-wall pages (PerimeterX, Cloudflare, DataDome,
+/// Distil). Only checked when the page has no `__NEXT_DATA__`, since real
+/// result pages can embed recaptcha for their contact forms.
+const BLOCK_MARKERS: &[&str] = &[
+    "px-captcha",
+    "perimeterx",
+    "cf-challenge",
+    "challenge-platform",
+    "captcha-delivery",
+    "g-recaptcha",
+    "h-captcha",
+    "access to this page has been denied",
+    "pardon our interruption",
+    "verify you are a human",
+    "are you a robot",
+];
+
+/// A results page is hundreds of KB; anything this small without
+/// `__NEXT_DATA__` is an empty shell.
+const EMPTY_SHELL_BYTES: usize = 5_000;
+
+/// Returns why a page looks blocked, or `None` if it looks like real content.
+pub fn detect_block(page: &FetchedPage) -> Option<String> {
+    for status in std::iter::once(page.status).chain(original_status(page)) {
+        if status == 403 || status == 429 {
+            return Some(format!("HTTP {status}"));
+        }
+    }
+
+    // End of results, not a block.
+    if page.status == 404 || page.body.contains("__NEXT_DATA__") {
+        return None;
+    }
+
+    let body = page.body.to_ascii_lowercase();
+    if let Some(marker) = BLOCK_MARKERS.iter().find(|m| body.contains(*m)) {
+        return Some(format!("bot wall ({marker})"));
+    }
+
+    if page.is_success() && page.body.trim().len() < EMPTY_SHELL_BYTES {
+        return Some(format!("empty page shell ({} bytes)", page.body.len()));
+    }
+
+    None
+}
+
+/// The site's own status when a proxy reports it in a header
+/// (ZenRows: `Zr-Original-Status`).
+fn original_status(page: &FetchedPage) -> Option<u16> {
+    page.headers
+        .iter()
+        .find(|(k, _)| k.to_ascii_lowercase().contains("original-status"))
+        .and_then(|(_, v)| v.trim().parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(status: u16, headers: &[(&str, &str)], body: &str) -> FetchedPage {
+        FetchedPage {
+            url: "https://www.realtor.com/realestateandhomes-search/Utah".into(),
+            status,
+            headers: headers
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            body: body.into(),
+        }
+    }
+
+    #[test]
+    fn flags_status_codes_captchas_and_empty_shells() {
+        assert_eq!(detect_block(&page(429, &[], "")), Some("HTTP 429".into()));
+        assert_eq!(
+            detect_block(&page(
+                200,
+                &[("Zr-Original-Status", "403")],
+                "<html></html>"
+            )),
+            Some("HTTP 403".into())
+        );
+
+        let captcha = format!(
+            "<html><body><div id=\"px-captcha\"></div>{}</body></html>",
+            " ".repeat(EMPTY_SHELL_BYTES)
+        );
+        assert_eq!(
+            detect_block(&page(200, &[], &captcha)),
+            Some("bot wall (px-captcha)".into())
+        );
+
+        assert!(detect_block(&page(200, &[], "<html><body></body></html>"))
+            .unwrap()
+            .starts_with("empty page shell"));
+    }
+
+    #[test]
+    fn real_pages_and_end_of_results_are_not_blocks() {
+        let html = std::fs::read_to_string(
+            "src/tests/fixtures/realtor/realestateandhomes-search_Utah.html",
+        )
+        .unwrap();
+        assert_eq!(detect_block(&page(200, &[], &html)), None);
+        assert_eq!(detect_block(&page(404, &[], "")), None);
+    }
+}
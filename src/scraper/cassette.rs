@@ -0,0 +1,283 @@
+// cassette.rs
+//
+// A cassette is a directory holding every page fetched during a scrape:
+//
+//   <dir>/index.json   ordered list of CassetteEntry
+//   <dir>/0001.html    page bodies, one file per entry
+//
+// Recording wraps any live fetcher; replay serves the pages back in the same
+// order so the whole pipeline can be exercised without network access.
+// Each recording gets its own numbered cassette under the record directory
+// (`<record_dir>/0001/`, `<record_dir>/0002/`, ...), so runs sharing a
+// directory never overwrite each other.
+use crate::scraper::fetcher::{FetchedPage, PageFetcher};
+use crate::scraper::ScraperError;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const INDEX_FILE: &str = "index.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CassetteEntry {
+    pub seq: usize,
+    pub url: String,
+    /// 0 when the fetch failed.
+    #[serde(default)]
+    pub status: u16,
+    #[serde(default)]
+    pub headers: Vec<(String, String)>,
+    /// `None` when the fetch failed.
+    pub body_file: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<RecordedError>,
+}
+
+/// A failed fetch, kept so replay fails the same way. Only the errors the
+/// scraper treats differently keep their kind; the rest replay as network
+/// errors.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "message", rename_all = "snake_case")]
+pub enum RecordedError {
+    NotFound(String),
+    Blocked(String),
+    Other(String),
+}
+
+impl From<&ScraperError> for RecordedError {
+    fn from(e: &ScraperError) -> Self {
+        match e {
+            ScraperError::NotFound(url) => RecordedError::NotFound(url.clone()),
+            ScraperError::Blocked(reason) => RecordedError::Blocked(reason.clone()),
+            other => RecordedError::Other(other.to_string()),
+        }
+    }
+}
+
+impl From<RecordedError> for ScraperError {
+    fn from(e: RecordedError) -> Self {
+        match e {
+            RecordedError::NotFound(url) => ScraperError::NotFound(url),
+            RecordedError::Blocked(reason) => ScraperError::Blocked(reason),
+            RecordedError::Other(msg) => ScraperError::Network(msg),
+        }
+    }
+}
+
+pub fn read_index(dir: &Path) -> Result<Vec<CassetteEntry>, ScraperError> {
+    let path = dir.join(INDEX_FILE);
+    let text = std::fs::read_to_string(&path)
+        .map_err(|e| ScraperError::IoError(format!("{}: {e}", path.display())))?;
+    serde_json::from_str(&text).map_err(|e| ScraperError::JsonParse(e.to_string()))
+}
+
+fn write_index(dir: &Path, entries: &[CassetteEntry]) -> Result<(), ScraperError> {
+    let json = serde_json::to_string_pretty(entries)
+        .map_err(|e| ScraperError::JsonParse(e.to_string()))?;
+    std::fs::write(dir.join(INDEX_FILE), json).map_err(|e| ScraperError::IoError(e.to_string()))
+}
+
+/// Passes requests through to `inner` and writes every response, failed
+/// fetches included, to a new cassette under the record directory. The index
+/// is rewritten after each fetch so a run that dies halfway still leaves a
+/// usable cassette behind.
+pub struct RecordingFetcher {
+    inner: Box<dyn PageFetcher>,
+    dir: PathBuf,
+    entries: Mutex<Vec<CassetteEntry>>,
+}
+
+impl RecordingFetcher {
+    /// Starts a cassette in the next free numbered subdirectory of `root`.
+    pub fn new(
+        inner: Box<dyn PageFetcher>,
+        root: impl Into<PathBuf>,
+    ) -> Result<Self, ScraperError> {
+        let root = root.into();
+        std::fs::create_dir_all(&root).map_err(|e| ScraperError::IoError(e.to_string()))?;
+
+        let mut n = recording_dirs(&root)?
+            .last()
+            .and_then(|d| d.file_name()?.to_str()?.parse::<u32>().ok())
+            .unwrap_or(0);
+        // create_dir fails if another recorder took the number first.
+        let dir = loop {
+            n += 1;
+            let dir = root.join(format!("{n:04}"));
+            match std::fs::create_dir(&dir) {
+                Ok(()) => break dir,
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(ScraperError::IoError(format!("{}: {e}", dir.display()))),
+            }
+        };
+
+        Ok(Self {
+            inner,
+            dir,
+            entries: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// The cassette this recorder writes to.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+}
+
+/// Numbered cassettes under `root`, oldest first.
+fn recording_dirs(root: &Path) -> Result<Vec<PathBuf>, ScraperError> {
+    let read = std::fs::read_dir(root)
+        .map_err(|e| ScraperError::IoError(format!("{}: {e}", root.display())))?;
+    let mut dirs = Vec::new();
+    for entry in read {
+        let path = entry
+            .map_err(|e| ScraperError::IoError(e.to_string()))?
+            .path();
+        let numbered = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()));
+        if numbered && path.is_dir() {
+            dirs.push(path);
+        }
+    }
+    dirs.sort();
+    Ok(dirs)
+}
+
+impl PageFetcher for RecordingFetcher {
+    fn fetch(&self, url: &str) -> Result<FetchedPage, ScraperError> {
+        let result = self.inner.fetch(url);
+
+        let mut entries = self
+            .entries
+            .lock()
+            .map_err(|_| ScraperError::IoError("cassette lock poisoned".into()))?;
+
+        let seq = entries.len() + 1;
+        let entry = match &result {
+            Ok(page) => {
+                let body_file = format!("{seq:04}.html");
+                std::fs::write(self.dir.join(&body_file), &page.body)
+                    .map_err(|e| ScraperError::IoError(e.to_string()))?;
+                CassetteEntry {
+                    seq,
+                    url: page.url.clone(),
+                    status: page.status,
+                    headers: page.headers.clone(),
+                    body_file: Some(body_file),
+                    error: None,
+                }
+            }
+            Err(e) => CassetteEntry {
+                seq,
+                url: url.to_string(),
+                status: 0,
+                headers: Vec::new(),
+                body_file: None,
+                error: Some(e.into()),
+            },
+        };
+        entries.push(entry);
+        write_index(&self.dir, &entries)?;
+
+        result
+    }
+
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn is_remote(&self) -> bool {
+        self.inner.is_remote()
+    }
+
+    fn take_blocked_attempts(&self) -> u32 {
+        self.inner.take_blocked_attempts()
+    }
+}
+
+/// Serves a recorded cassette back, one entry per call, in recording order.
+///
+/// A request for a different URL than the next recorded one is an error: it
+/// means the code under test no longer walks pages the way it did when the
+/// cassette was made.
+pub struct ReplayFetcher {
+    dir: PathBuf,
+    entries: Vec<CassetteEntry>,
+    cursor: Mutex<usize>,
+}
+
+impl ReplayFetcher {
+    /// Opens a cassette, or a record directory holding exactly one. A record
+    /// directory shared by several runs has to name the one to replay.
+    pub fn open(dir: impl Into<PathBuf>) -> Result<Self, ScraperError> {
+        let mut dir = dir.into();
+        if !dir.join(INDEX_FILE).exists() {
+            let mut recordings = recording_dirs(&dir)?;
+            if recordings.len() != 1 {
+                let names: Vec<String> =
+                    recordings.iter().map(|d| d.display().to_string()).collect();
+                return Err(ScraperError::Config(format!(
+                    "{} holds {} recordings; replay one of: {}",
+                    dir.display(),
+                    recordings.len(),
+                    names.join(", ")
+                )));
+            }
+            dir = recordings.remove(0);
+        }
+        let entries = read_index(&dir)?;
+        Ok(Self {
+            dir,
+            entries,
+            cursor: Mutex::new(0),
+        })
+    }
+}
+
+impl PageFetcher for ReplayFetcher {
+    fn fetch(&self, url: &str) -> Result<FetchedPage, ScraperError> {
+        let mut cursor = self
+            .cursor
+            .lock()
+            .map_err(|_| ScraperError::IoError("cassette lock poisoned".into()))?;
+
+        let entry = self
+            .entries
+            .get(*cursor)
+            .ok_or_else(|| ScraperError::IoError(format!("cassette exhausted before {url}")))?;
+
+        if entry.url != url {
+            return Err(ScraperError::Config(format!(
+                "cassette entry {} is {}, but {} was requested",
+                entry.seq, entry.url, url
+            )));
+        }
+
+        *cursor += 1;
+        if let Some(error) = &entry.error {
+            return Err(error.clone().into());
+        }
+        let body_file = entry.body_file.as_ref().ok_or_else(|| {
+            ScraperError::IoError(format!("cassette entry {} has no body", entry.seq))
+        })?;
+        let body = std::fs::read_to_string(self.dir.join(body_file))
+            .map_err(|e| ScraperError::IoError(e.to_string()))?;
+
+        Ok(FetchedPage {
+            url: entry.url.clone(),
+            status: entry.status,
+            headers: entry.headers.clone(),
+            body,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "replay"
+    }
+
+    fn is_remote(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,266 @@
+// config.rs
+use crate::scraper::ScraperError;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Read when `SCRAPER_CONFIG` doesn't name another file. Optional.
+const DEFAULT_CONFIG_FILE: &str = "scraper.json";
+
+/// Everything about how a scrape fetches pages that we tune between runs.
+///
+/// Built from defaults, then `scraper.json` (or the file named by
+/// `SCRAPER_CONFIG`), then environment variables, then any per-run
+/// overrides from the admin scrape form. The result is saved on each
+/// `scrape_runs` row so a run's numbers can be read against its settings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScraperConfig {
+    /// Fetch backend, as in `SCRAPER_FETCHER`: `zenrows`, `direct`,
+    /// `file:<dir>` or `replay:<dir>`.
+    pub fetcher: String,
+    /// Also write every fetch to a cassette; each run gets its own numbered
+    /// subdirectory here.
+    pub record_dir: Option<String>,
+    pub user_agent: String,
+    /// Request timeout for the `direct` fetcher.
+    pub direct_timeout_secs: u64,
+    /// Realtor stops serving search results past roughly this many pages.
+    pub page_cap: usize,
+    /// Pause between requests to remote fetchers.
+    pub page_delay_secs: u64,
+    /// Failed pages in a row before the run is abandoned.
+    pub max_page_failures: usize,
+    /// Fetch the detail page of every new or changed listing. Costs a
+    /// request per listing.
+    pub enrich_details: bool,
+    pub zenrows: ZenRowsConfig,
+}
+
+/// ZenRows request and retry settings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ZenRowsConfig {
+    pub timeout_secs: u64,
+    pub max_attempts: u64,
+    pub max_backoff_secs: u64,
+    pub js_render: bool,
+    pub premium_proxy: bool,
+    /// Where requests come from; blocked retries may try elsewhere.
+    pub proxy_country: String,
+}
+
+impl Default for ScraperConfig {
+    fn default() -> Self {
+        Self {
+            fetcher: "zenrows".to_string(),
+            record_dir: None,
+            user_agent: "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/121.0 Safari/537.36".to_string(),
+            direct_timeout_secs: 60,
+            page_cap: 200,
+            page_delay_secs: 2,
+            max_page_failures: 3,
+            enrich_details: false,
+            zenrows: ZenRowsConfig::default(),
+        }
+    }
+}
+
+impl Default for ZenRowsConfig {
+    fn default() -> Self {
+        Self {
+            timeout_secs: 360,
+            max_attempts: 5,
+            max_backoff_secs: 10,
+            js_render: true,
+            premium_proxy: true,
+            proxy_country: "us".to_string(),
+        }
+    }
+}
+
+/// Settings an admin can change for a single run. `None` keeps the
+/// configured value.
+#[derive(Debug, Default)]
+pub struct ConfigOverrides {
+    pub page_cap: Option<usize>,
+    pub page_delay_secs: Option<u64>,
+    pub max_page_failures: Option<usize>,
+    pub enrich_details: Option<bool>,
+    pub proxy_country: Option<String>,
+}
+
+impl ScraperConfig {
+    /// Defaults, then the config file if there is one, then the environment.
+    pub fn load() -> Result<Self, ScraperError> {
+        let path = std::env::var("SCRAPER_CONFIG").ok();
+        let mut config = match &path {
+            Some(path) => Self::from_file(path)?,
+            None if std::path::Path::new(DEFAULT_CONFIG_FILE).exists() => {
+                Self::from_file(DEFAULT_CONFIG_FILE)?
+            }
+            None => Self::default(),
+        };
+        config.apply_env(|key| std::env::var(key).ok())?;
+        config.check_limits()?;
+        Ok(config)
+    }
+
+    /// Rejects a page cap or failure limit of 0, which would cap every
+    /// search after one page or abandon a run on its first failure.
+    fn check_limits(&self) -> Result<(), ScraperError> {
+        if self.page_cap == 0 {
+            return Err(ScraperError::Config("page_cap must be at least 1".into()));
+        }
+        if self.max_page_failures == 0 {
+            return Err(ScraperError::Config(
+                "max_page_failures must be at least 1".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Reads a JSON config file. Settings it leaves out keep their defaults.
+    pub fn from_file(path: &str) -> Result<Self, ScraperError> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| ScraperError::Config(format!("{path}: {e}")))?;
+        serde_json::from_str(&text).map_err(|e| ScraperError::Config(format!("{path}: {e}")))
+    }
+
+    /// Applies `SCRAPER_*` and `ZENROWS_*` variables, as read by `var`.
+    pub fn apply_env(&mut self, var: impl Fn(&str) -> Option<String>) -> Result<(), ScraperError> {
+        if let Some(v) = var("SCRAPER_FETCHER") {
+            self.fetcher = v;
+        }
+        if let Some(v) = var("SCRAPER_RECORD") {
+            self.record_dir = Some(v).filter(|d| !d.is_empty());
+        }
+        if let Some(v) = var("SCRAPER_USER_AGENT") {
+            self.user_agent = v;
+        }
+        parse_var(
+            &var,
+            "SCRAPER_DIRECT_TIMEOUT_SECS",
+            &mut self.direct_timeout_secs,
+        )?;
+        parse_var(&var, "SCRAPER_PAGE_CAP", &mut self.page_cap)?;
+        parse_var(&var, "SCRAPER_PAGE_DELAY_SECS", &mut self.page_delay_secs)?;
+        parse_var(
+            &var,
+            "SCRAPER_MAX_PAGE_FAILURES",
+            &mut self.max_page_failures,
+        )?;
+        if let Some(v) = var("SCRAPER_ENRICH_DETAILS") {
+            self.enrich_details = matches!(v.as_str(), "1" | "true");
+        }
+
+        let zenrows = &mut self.zenrows;
+        parse_var(&var, "ZENROWS_TIMEOUT_SECS", &mut zenrows.timeout_secs)?;
+        parse_var(&var, "ZENROWS_MAX_ATTEMPTS", &mut zenrows.max_attempts)?;
+        parse_var(
+            &var,
+            "ZENROWS_MAX_BACKOFF_SECS",
+            &mut zenrows.max_backoff_secs,
+        )?;
+        parse_var(&var, "ZENROWS_JS_RENDER", &mut zenrows.js_render)?;
+        parse_var(&var, "ZENROWS_PREMIUM_PROXY", &mut zenrows.premium_proxy)?;
+        if let Some(v) = var("ZENROWS_PROXY_COUNTRY") {
+            zenrows.proxy_country = v;
+        }
+        Ok(())
+    }
+
+    /// This config with a run's overrides applied.
+    pub fn with_overrides(mut self, overrides: &ConfigOverrides) -> Self {
+        if let Some(v) = overrides.page_cap {
+            self.page_cap = v.max(1);
+        }
+        if let Some(v) = overrides.page_delay_secs {
+            self.page_delay_secs = v;
+        }
+        if let Some(v) = overrides.max_page_failures {
+            self.max_page_failures = v.max(1);
+        }
+        if let Some(v) = overrides.enrich_details {
+            self.enrich_details = v;
+        }
+        if let Some(v) = &overrides.proxy_country {
+            self.zenrows.proxy_country = v.to_lowercase();
+        }
+        self
+    }
+
+    /// The config as stored on `scrape_runs`.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+fn parse_var<T: FromStr>(
+    var: &impl Fn(&str) -> Option<String>,
+    key: &str,
+    target: &mut T,
+) -> Result<(), ScraperError> {
+    if let Some(v) = var(key) {
+        *target = v
+            .parse()
+            .map_err(|_| ScraperError::Config(format!("Invalid {key} '{v}'")))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_settings_are_layered_under_env_and_run_overrides() {
+        let mut config: ScraperConfig =
+            serde_json::from_str(r#"{ "page_cap": 50, "zenrows": { "max_attempts": 2 } }"#)
+                .unwrap();
+        assert_eq!(config.page_delay_secs, 2, "unset keys keep their defaults");
+        assert_eq!(config.zenrows.timeout_secs, 360);
+
+        config
+            .apply_env(|key| match key {
+                "SCRAPER_PAGE_CAP" => Some("80".into()),
+                "ZENROWS_JS_RENDER" => Some("false".into()),
+                "SCRAPER_DIRECT_TIMEOUT_SECS" => Some("20".into()),
+                _ => None,
+            })
+            .unwrap();
+        let config = config.with_overrides(&ConfigOverrides {
+            page_delay_secs: Some(5),
+            ..Default::default()
+        });
+
+        assert_eq!(config.page_cap, 80);
+        assert_eq!(config.page_delay_secs, 5);
+        assert_eq!(config.direct_timeout_secs, 20);
+        assert_eq!(config.zenrows.max_attempts, 2);
+        assert!(!config.zenrows.js_render);
+
+        let bad = ScraperConfig::default()
+            .apply_env(|key| (key == "SCRAPER_PAGE_CAP").then(|| "lots".to_string()));
+        assert!(matches!(bad, Err(ScraperError::Config(_))));
+    }
+
+    #[test]
+    fn zero_page_cap_or_failure_limit_is_rejected() {
+        let from_file: ScraperConfig = serde_json::from_str(r#"{ "page_cap": 0 }"#).unwrap();
+        assert!(matches!(
+            from_file.check_limits(),
+            Err(ScraperError::Config(_))
+        ));
+
+        let mut from_env = ScraperConfig::default();
+        from_env
+            .apply_env(|key| (key == "SCRAPER_MAX_PAGE_FAILURES").then(|| "0".to_string()))
+            .unwrap();
+        assert!(matches!(
+            from_env.check_limits(),
+            Err(ScraperError::Config(_))
+        ));
+
+        assert!(ScraperConfig::default().check_limits().is_ok());
+    }
+}
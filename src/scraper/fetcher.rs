@@ -0,0 +1,249 @@
+// fetcher.rs
+use crate::scraper::cassette::{RecordingFetcher, ReplayFetcher};
+use crate::scraper::config::ScraperConfig;
+use crate::scraper::zenrows::ZenRowsFetcher;
+use crate::scraper::ScraperError;
+use reqwest::blocking::Client;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// A single page as returned by a fetch backend.
+///
+/// `status` and `headers` are whatever the backend received, so a page can be
+/// written to a cassette and replayed later without losing information.
+#[derive(Debug, Clone)]
+pub struct FetchedPage {
+    pub url: String,
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl FetchedPage {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+/// A backend that knows how to turn a search URL into page HTML.
+///
+/// `RealtorScraper` only talks to this trait, so the same pagination and
+/// parsing pipeline can run against ZenRows, a direct HTTP client, or pages
+/// saved on disk.
+pub trait PageFetcher: Send {
+    fn fetch(&self, url: &str) -> Result<FetchedPage, ScraperError>;
+
+    /// Short name used in logs.
+    fn name(&self) -> &'static str;
+
+    /// Whether this backend hits the network. Local backends skip the
+    /// politeness delays between pages.
+    fn is_remote(&self) -> bool {
+        true
+    }
+
+    /// Blocked responses the backend retried past (or gave up on) since the
+    /// last call, and resets the count. Backends that don't retry report 0.
+    fn take_blocked_attempts(&self) -> u32 {
+        0
+    }
+}
+
+/// Builds the fetch backend named by `config.fetcher`.
+///
+/// - `zenrows` → ZenRows (requires `ZENROWS_API_KEY`)
+/// - `direct` → plain reqwest client with a cookie jar
+/// - `file:<dir>` → pages previously saved under `<dir>`
+/// - `replay:<dir>` → pages from a recorded cassette, in order; `<dir>` is
+///   the cassette itself, or a record directory holding only one
+///
+/// If `config.record_dir` is set, the chosen backend is wrapped so every
+/// response it gives is also written to a new cassette there.
+pub fn fetcher_from_config(config: &ScraperConfig) -> Result<Box<dyn PageFetcher>, ScraperError> {
+    let fetcher = fetcher_from_spec(&config.fetcher, config)?;
+
+    match &config.record_dir {
+        Some(dir) => {
+            let recorder = RecordingFetcher::new(fetcher, dir)?;
+            eprintln!("📼 Recording to {}", recorder.dir().display());
+            Ok(Box::new(recorder))
+        }
+        None => Ok(fetcher),
+    }
+}
+
+pub fn fetcher_from_spec(
+    spec: &str,
+    config: &ScraperConfig,
+) -> Result<Box<dyn PageFetcher>, ScraperError> {
+    match spec.split_once(':') {
+        Some(("file", dir)) => Ok(Box::new(FileFetcher::new(dir))),
+        Some(("replay", dir)) => Ok(Box::new(ReplayFetcher::open(dir)?)),
+        _ => match spec {
+            "zenrows" => Ok(Box::new(ZenRowsFetcher::from_env(config)?)),
+            "direct" => Ok(Box::new(DirectFetcher::new(
+                &config.user_agent,
+                config.direct_timeout_secs,
+            )?)),
+            other => Err(ScraperError::Config(format!("Unknown fetcher '{other}'"))),
+        },
+    }
+}
+
+/// Fetches pages straight from the site with a persistent cookie jar.
+///
+/// Cheaper than ZenRows but gets blocked quickly without a residential IP.
+pub struct DirectFetcher {
+    client: Client,
+}
+
+impl DirectFetcher {
+    pub fn new(user_agent: &str, timeout_secs: u64) -> Result<Self, ScraperError> {
+        let client = Client::builder()
+            .user_agent(user_agent)
+            .cookie_store(true)
+            .timeout(Duration::from_secs(timeout_secs))
+            .build()
+            .map_err(|e| ScraperError::Network(e.to_string()))?;
+
+        Ok(Self { client })
+    }
+}
+
+impl PageFetcher for DirectFetcher {
+    fn fetch(&self, url: &str) -> Result<FetchedPage, ScraperError> {
+        use reqwest::header::{ACCEPT, ACCEPT_LANGUAGE, REFERER};
+
+        let resp = self
+            .client
+            .get(url)
+            .header(REFERER, "https://www.google.com/")
+            .header(ACCEPT, "text/html,application/xhtml+xml")
+            .header(ACCEPT_LANGUAGE, "en-US,en;q=0.9")
+            .send()
+            .map_err(|e| ScraperError::Network(e.to_string()))?;
+
+        let status = resp.status().as_u16();
+        let headers = header_pairs(resp.headers());
+        let body = resp
+            .text()
+            .map_err(|e| ScraperError::Network(e.to_string()))?;
+
+        Ok(FetchedPage {
+            url: url.to_string(),
+            status,
+            headers,
+            body,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "direct"
+    }
+}
+
+/// Serves pages from a directory of saved HTML files, one file per URL.
+///
+/// The file name is derived from the URL path, e.g.
+/// `https://www.realtor.com/realestateandhomes-search/Utah/pg-2` →
+/// `<root>/realestateandhomes-search_Utah_pg-2.html`. A missing file is
+/// served as a 404, which the scraper treats as the end of the results.
+pub struct FileFetcher {
+    root: PathBuf,
+}
+
+impl FileFetcher {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn path_for(&self, url: &str) -> PathBuf {
+        self.root.join(format!("{}.html", url_slug(url)))
+    }
+}
+
+impl PageFetcher for FileFetcher {
+    fn fetch(&self, url: &str) -> Result<FetchedPage, ScraperError> {
+        let path = self.path_for(url);
+        if !path.exists() {
+            return Ok(FetchedPage {
+                url: url.to_string(),
+                status: 404,
+                headers: Vec::new(),
+                body: String::new(),
+            });
+        }
+
+        let body = std::fs::read_to_string(&path)
+            .map_err(|e| ScraperError::IoError(format!("{}: {e}", path.display())))?;
+
+        Ok(FetchedPage {
+            url: url.to_string(),
+            status: 200,
+            headers: Vec::new(),
+            body,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "file"
+    }
+
+    fn is_remote(&self) -> bool {
+        false
+    }
+}
+
+/// Turns a URL into a filesystem-safe name, dropping the scheme and host.
+pub fn url_slug(url: &str) -> String {
+    let without_scheme = url.split_once("://").map(|(_, rest)| rest).unwrap_or(url);
+    let path = without_scheme
+        .split_once('/')
+        .map(|(_, rest)| rest)
+        .unwrap_or(without_scheme);
+
+    path.trim_matches('/')
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '.' => c,
+            _ => '_',
+        })
+        .collect()
+}
+
+pub(crate) fn header_pairs(headers: &reqwest::header::HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(k, v)| {
+            (
+                k.as_str().to_string(),
+                v.to_str().unwrap_or("?").to_string(),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn url_slug_strips_host_and_flattens_path() {
+        assert_eq!(
+            url_slug("https://www.realtor.com/realestateandhomes-search/Utah/pg-2"),
+            "realestateandhomes-search_Utah_pg-2"
+        );
+        assert_eq!(
+            url_slug("https://www.realtor.com/realestateandhomes-search/Utah"),
+            "realestateandhomes-search_Utah"
+        );
+    }
+
+    #[test]
+    fn unknown_fetcher_spec_is_config_error() {
+        assert!(matches!(
+            fetcher_from_spec("carrier-pigeon", &ScraperConfig::default()),
+            Err(ScraperError::Config(_))
+        ));
+    }
+}
@@ -0,0 +1,229 @@
+// jobs.rs
+//
+// In-process registry of running scrapes. Each scrape thread holds a
+// `JobHandle` for its lifetime; the handle reports progress, carries the
+// cancel flag, and removes the job from the registry when dropped.
+use crate::errors::ServerError;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+const DEFAULT_MAX_JOBS: usize = 2;
+
+/// A point-in-time copy of a job's progress, for display.
+#[derive(Debug, Clone)]
+pub struct JobSnapshot {
+    pub id: u64,
+    pub state: String,
+    pub started_at: i64,
+    pub run_id: Option<i64>,
+    pub shard: Option<String>,
+    pub page: usize,
+    pub properties_saved: usize,
+    pub last_error: Option<String>,
+    pub cancel_requested: bool,
+}
+
+#[derive(Default)]
+struct Progress {
+    run_id: Option<i64>,
+    shard: Option<String>,
+    page: usize,
+    properties_saved: usize,
+    last_error: Option<String>,
+}
+
+struct Job {
+    id: u64,
+    state: String,
+    started_at: i64,
+    cancel: AtomicBool,
+    progress: Mutex<Progress>,
+}
+
+pub struct JobRegistry {
+    jobs: Mutex<Vec<Arc<Job>>>,
+    next_id: AtomicU64,
+    max_jobs: usize,
+}
+
+/// The process-wide registry. `SCRAPER_MAX_JOBS` sets how many scrapes may
+/// run at once.
+pub fn registry() -> &'static JobRegistry {
+    static REGISTRY: OnceLock<JobRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        let max_jobs = std::env::var("SCRAPER_MAX_JOBS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_MAX_JOBS);
+        JobRegistry::new(max_jobs)
+    })
+}
+
+impl JobRegistry {
+    pub fn new(max_jobs: usize) -> Self {
+        Self {
+            jobs: Mutex::new(Vec::new()),
+            next_id: AtomicU64::new(1),
+            max_jobs: max_jobs.max(1),
+        }
+    }
+
+    /// Registers a job for `state`, refusing if the concurrency limit is
+    /// reached or the state already has a job.
+    pub fn start(&'static self, state: &str, now: i64) -> Result<JobHandle, ServerError> {
+        let mut jobs = self.jobs.lock().map_err(|_| ServerError::InternalError)?;
+
+        if jobs.iter().any(|j| j.state == state) {
+            return Err(ServerError::Conflict(format!(
+                "A scrape for {state} is already running"
+            )));
+        }
+        if jobs.len() >= self.max_jobs {
+            return Err(ServerError::Conflict(format!(
+                "{} scrapes are already running; wait for one to finish",
+                jobs.len()
+            )));
+        }
+
+        let job = Arc::new(Job {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            state: state.to_string(),
+            started_at: now,
+            cancel: AtomicBool::new(false),
+            progress: Mutex::new(Progress::default()),
+        });
+        jobs.push(job.clone());
+
+        Ok(JobHandle {
+            job,
+            registry: self,
+        })
+    }
+
+    pub fn snapshot(&self) -> Vec<JobSnapshot> {
+        let Ok(jobs) = self.jobs.lock() else {
+            return Vec::new();
+        };
+
+        jobs.iter()
+            .map(|job| {
+                let progress = job.progress.lock().unwrap_or_else(|e| e.into_inner());
+                JobSnapshot {
+                    id: job.id,
+                    state: job.state.clone(),
+                    started_at: job.started_at,
+                    run_id: progress.run_id,
+                    shard: progress.shard.clone(),
+                    page: progress.page,
+                    properties_saved: progress.properties_saved,
+                    last_error: progress.last_error.clone(),
+                    cancel_requested: job.cancel.load(Ordering::Relaxed),
+                }
+            })
+            .collect()
+    }
+
+    /// Asks a job to stop after its current page. Returns false if no such
+    /// job is running.
+    pub fn cancel(&self, id: u64) -> bool {
+        let Ok(jobs) = self.jobs.lock() else {
+            return false;
+        };
+        match jobs.iter().find(|j| j.id == id) {
+            Some(job) => {
+                job.cancel.store(true, Ordering::Relaxed);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Owned by the thread running a scrape. Dropping it unregisters the job.
+pub struct JobHandle {
+    job: Arc<Job>,
+    registry: &'static JobRegistry,
+}
+
+impl JobHandle {
+    pub fn is_cancelled(&self) -> bool {
+        self.job.cancel.load(Ordering::Relaxed)
+    }
+
+    /// Called when the job moves on to a new run (shard or price band).
+    pub fn set_run(&self, run_id: i64, shard: Option<&str>) {
+        self.update(|p| {
+            p.run_id = Some(run_id);
+            p.shard = shard.map(str::to_string);
+            p.page = 0;
+        });
+    }
+
+    pub fn page_saved(&self, page: usize, properties: usize) {
+        self.update(|p| {
+            p.page = page;
+            p.properties_saved += properties;
+        });
+    }
+
+    pub fn page_failed(&self, page: usize, error: &str) {
+        self.update(|p| {
+            p.page = page;
+            p.last_error = Some(error.to_string());
+        });
+    }
+
+    fn update(&self, f: impl FnOnce(&mut Progress)) {
+        let mut progress = self.job.progress.lock().unwrap_or_else(|e| e.into_inner());
+        f(&mut progress);
+    }
+}
+
+impl Drop for JobHandle {
+    fn drop(&mut self) {
+        let mut jobs = self.registry.jobs.lock().unwrap_or_else(|e| e.into_inner());
+        jobs.retain(|j| j.id != self.job.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_registry(max_jobs: usize) -> &'static JobRegistry {
+        Box::leak(Box::new(JobRegistry::new(max_jobs)))
+    }
+
+    #[test]
+    fn enforces_the_concurrency_cap_and_one_job_per_state() {
+        let registry = test_registry(2);
+
+        let ut = registry.start("UT", 0).unwrap();
+        assert!(registry.start("UT", 0).is_err());
+        let _id = registry.start("ID", 0).unwrap();
+        assert!(registry.start("NV", 0).is_err());
+
+        drop(ut);
+        assert!(registry.start("NV", 0).is_ok());
+    }
+
+    #[test]
+    fn reports_progress_and_cancellation() {
+        let registry = test_registry(1);
+        let job = registry.start("UT", 100).unwrap();
+
+        job.set_run(7, Some("county:Salt Lake County"));
+        job.page_saved(1, 42);
+        job.page_saved(2, 40);
+        job.page_failed(3, "HTTP 503");
+        assert!(registry.cancel(registry.snapshot()[0].id));
+        assert!(job.is_cancelled());
+
+        let snap = &registry.snapshot()[0];
+        assert_eq!(snap.run_id, Some(7));
+        assert_eq!(snap.page, 3);
+        assert_eq!(snap.properties_saved, 82);
+        assert_eq!(snap.last_error.as_deref(), Some("HTTP 503"));
+        assert!(snap.cancel_requested);
+    }
+}
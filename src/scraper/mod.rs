@@ -1,6 +1,15 @@
+pub mod blocking;
+pub mod cassette;
+pub mod config;
+pub mod fetcher;
+pub mod jobs;
 pub mod models;
-mod scraper;
+pub mod scheduler;
+pub mod scraper;
 mod scraper_error;
+pub mod shards;
+pub mod sources;
+mod zenrows;
 
 pub use models::Property;
 pub use scraper::RealtorScraper;
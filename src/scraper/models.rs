@@ -11,7 +11,8 @@ use serde::{Deserialize, Serialize};
 //  │    │    ├── city
 //  │    │    ├── state_code
 //  │    │    ├── postal_code
-//  │    │    └── country
+//  │    │    ├── country
+//  │    │    └── coordinate (search results nest it here)
 //  │    ├── county
 //  │    │    ├── name
 //  │    │    └── fips_code
@@ -44,6 +45,7 @@ where
 pub struct OpenHouse {
     pub start_date: Option<String>,
     pub end_date: Option<String>,
+    pub time_zone: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -53,10 +55,19 @@ pub struct Property {
     pub description: Option<Description>,
 
     pub status: Option<String>,
+    /// Path of the listing's detail page, under `realestateandhomes-detail/`.
+    pub permalink: Option<String>,
     pub list_price: Option<i64>,
     pub list_date: Option<String>,
     pub price_reduced: Option<i64>,
     pub sold_price: Option<i64>,
+    /// Sold searches report the sale here and in `description`. Active
+    /// listings carry the previous sale too, so these only count on a
+    /// `sold` listing.
+    pub last_sold_price: Option<i64>,
+    pub last_sold_date: Option<String>,
+    /// Rentals with several units quote a range instead of `list_price`.
+    pub list_price_min: Option<i64>,
     pub flags: Option<Flags>,
     pub currency: Option<String>,
     pub open_houses: Option<Vec<OpenHouse>>,
@@ -86,6 +97,7 @@ pub struct Address {
     #[serde(rename = "postal_code")]
     pub postal_code: Option<String>,
     pub country: Option<String>,
+    pub coordinate: Option<Coordinate>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -104,7 +116,8 @@ pub struct Coordinate {
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Description {
     pub beds: Option<i64>,
-    pub baths: Option<i64>,
+    pub baths: Option<f64>,
+    pub sqft: Option<i64>,
     #[serde(rename = "lot_sqft")]
     pub lot_sqft: Option<i64>,
     #[serde(rename = "type")]
@@ -115,6 +128,7 @@ pub struct Description {
     pub garage: Option<i64>,
     pub stories: Option<i64>,
     pub subdivision: Option<String>,
+    pub sold_price: Option<i64>,
     pub sold_date: Option<String>,
 }
 
@@ -130,3 +144,57 @@ pub struct Flags {
     // "is_price_reduced" appears both in flags and top-level JSON, optional
     pub is_price_reduced: Option<bool>,
 }
+
+// propertyDetails (detail page)
+//  ├── list_date
+//  ├── days_on_market
+//  ├── description
+//  │    └── text
+//  ├── advertisers[]
+//  │    ├── type ("seller" is the listing agent)
+//  │    ├── name
+//  │    └── office
+//  │         └── name
+//  ├── property_history[]
+//  │    ├── date, event_name, price
+//  │    └── source_name
+//  ├── photo_count
+//  └── photos[]
+
+/// The listing a detail page describes.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PropertyDetail {
+    pub list_date: Option<String>,
+    pub days_on_market: Option<i64>,
+    pub description: Option<DetailDescription>,
+    pub advertisers: Option<Vec<Advertiser>>,
+    pub property_history: Option<Vec<PriceHistoryEntry>>,
+    pub photo_count: Option<i64>,
+    pub photos: Option<Vec<serde_json::Value>>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct DetailDescription {
+    pub text: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Advertiser {
+    #[serde(rename = "type")]
+    pub advertiser_type: Option<String>,
+    pub name: Option<String>,
+    pub office: Option<Office>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Office {
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PriceHistoryEntry {
+    pub date: Option<String>,
+    pub event_name: Option<String>,
+    pub price: Option<i64>,
+    pub source_name: Option<String>,
+}
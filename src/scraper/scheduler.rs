@@ -0,0 +1,125 @@
+// scheduler.rs
+//
+// Background thread that launches scrapes from `scrape_schedules` when
+// they come due. A schedule whose state still has a run open, or that finds
+// every job slot taken, stays due and is tried again on a later tick. Any
+// other failure to start is recorded on the schedule, which then waits for
+// its next slot.
+//
+// A second thread keeps `market_stats` up to date, filling in yesterday's
+// figures shortly after midnight UTC. It runs whether or not any scrapes
+// are scheduled.
+use crate::db::connection::Database;
+use crate::db::market_stats;
+use crate::db::schedules::{self, ScrapeSchedule};
+use crate::errors::ServerError;
+use crate::scraper::config::ConfigOverrides;
+use crate::scraper::jobs::{self, JobRegistry};
+use crate::scraper::scraper::{ScrapeMode, SearchKind};
+use crate::scraper::shards::ShardBy;
+use crate::scraper::RealtorScraper;
+use std::time::Duration;
+
+const TICK: Duration = Duration::from_secs(60);
+const MARKET_STATS_TICK: Duration = Duration::from_secs(15 * 60);
+
+pub fn start_scheduler(db: &Database) {
+    let db = db.clone();
+
+    std::thread::spawn(move || loop {
+        if let Err(e) = run_due_schedules(&db, jobs::registry(), unix_now()) {
+            eprintln!("⚠️ Scheduler tick failed: {e}");
+        }
+        std::thread::sleep(TICK);
+    });
+}
+
+pub fn start_market_stats_job(db: &Database) {
+    let db = db.clone();
+
+    std::thread::spawn(move || loop {
+        let now = chrono::Utc::now().naive_utc();
+        match db.with_conn(|conn| market_stats::refresh_market_stats(conn, now)) {
+            Ok(0) => {}
+            Ok(rows) => eprintln!("📈 Market stats refreshed ({rows} rows)"),
+            Err(e) => eprintln!("⚠️ Market stats refresh failed: {e}"),
+        }
+        std::thread::sleep(MARKET_STATS_TICK);
+    });
+}
+
+/// Launches every schedule that is due, registering the scrapes in
+/// `registry`. Returns the ids launched.
+pub fn run_due_schedules(
+    db: &Database,
+    registry: &'static JobRegistry,
+    now: i64,
+) -> Result<Vec<i64>, ServerError> {
+    let due = db.with_conn(|conn| schedules::due_schedules(conn, now))?;
+
+    let mut launched = Vec::new();
+    for schedule in due {
+        let (shard_by, mode, kind) = match parse_schedule(&schedule) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                // Won't fix itself, so move it on rather than retry every minute.
+                eprintln!("⚠️ Schedule {} is invalid: {e}", schedule.id);
+                db.with_conn(|conn| {
+                    schedules::mark_schedule_failed(conn, schedule.id, &e.to_string(), now)
+                })?;
+                continue;
+            }
+        };
+
+        let started = RealtorScraper::start_state_scrape(
+            db,
+            registry,
+            &schedule.state,
+            shard_by,
+            mode,
+            kind,
+            &ConfigOverrides::default(),
+        );
+        match started {
+            Ok(()) => {
+                db.with_conn(|conn| schedules::mark_schedule_run(conn, schedule.id, now))?;
+                eprintln!(
+                    "⏰ Schedule {} started a {} {} scrape of {}",
+                    schedule.id, schedule.mode, schedule.search_kind, schedule.state
+                );
+                launched.push(schedule.id);
+            }
+            Err(e @ ServerError::Conflict(_)) => {
+                eprintln!("⚠️ Schedule {} not started, still due: {e}", schedule.id)
+            }
+            Err(e) => {
+                eprintln!("⚠️ Schedule {} failed to start: {e}", schedule.id);
+                db.with_conn(|conn| {
+                    schedules::mark_schedule_failed(conn, schedule.id, &e.to_string(), now)
+                })?;
+            }
+        }
+    }
+    Ok(launched)
+}
+
+fn parse_schedule(
+    schedule: &ScrapeSchedule,
+) -> Result<(ShardBy, ScrapeMode, SearchKind), ServerError> {
+    let shard_by = ShardBy::parse(&schedule.shard_by).ok_or_else(|| {
+        ServerError::BadRequest(format!("Invalid shard_by {}", schedule.shard_by))
+    })?;
+    let mode = ScrapeMode::parse(&schedule.mode, schedule.stop_after.map(|n| n as usize))
+        .ok_or_else(|| ServerError::BadRequest(format!("Invalid mode {}", schedule.mode)))?;
+    let kind = SearchKind::parse(&schedule.search_kind).ok_or_else(|| {
+        ServerError::BadRequest(format!("Invalid search kind {}", schedule.search_kind))
+    })?;
+    Ok((shard_by, mode, kind))
+}
+
+fn unix_now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
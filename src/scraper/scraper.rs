@@ -1,142 +1,811 @@
 // scraper.rs
+use crate::db::archive::{self, archive_page};
 use crate::db::connection::Database;
-use crate::db::properties::save_scraped_properties;
+use crate::db::details::save_listing_details;
+use crate::db::properties::{
+    mark_unseen_delisted, save_scraped_properties, save_scraped_properties_at, SaveOutcome,
+};
+use crate::db::quarantine::quarantine_record;
+use crate::db::scrapes::NewScrapeRun;
+use crate::domain::details::ListingDetails;
+use crate::domain::property::ScrapedProperty;
+use crate::errors::ServerError;
+use crate::scraper::blocking::detect_block;
+use crate::scraper::config::{ConfigOverrides, ScraperConfig};
+use crate::scraper::fetcher::{fetcher_from_config, PageFetcher};
+use crate::scraper::jobs::{self, JobHandle, JobRegistry};
+use crate::scraper::models::PropertyDetail;
+use crate::scraper::shards::{plan_shards, state_search_url, ScrapeShard, ShardBy, SEARCH_BASE};
+use crate::scraper::sources::{extract_next_data, realtor};
 use crate::scraper::Property;
 use crate::scraper::ScraperError;
-use rand::Rng;
-use reqwest::blocking::Client;
-use scraper::{Html, Selector};
+use chrono::Utc;
 use serde_json::Value;
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
 // use std::sync::Arc;
 use std::time::Duration;
 
-const USER_AGENT: &str =
-    "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/121.0 Safari/537.36";
-
 pub struct RealtorScraper {
-    client: Client,
+    fetcher: Box<dyn PageFetcher>,
+    config: ScraperConfig,
+    mode: ScrapeMode,
+    kind: SearchKind,
+    job: Option<JobHandle>,
+}
+
+/// Realtor's "Newest listings" sort, as a search path segment.
+const NEWEST_FIRST_SORT: &str = "sby-6";
+
+/// Path segment that switches a search to recently sold homes.
+const RECENTLY_SOLD: &str = "show-recently-sold";
+
+/// Rental searches live under their own path, e.g. `/apartments/Utah`.
+const RENTALS_BASE: &str = "https://www.realtor.com/apartments";
+
+/// How much of a search to walk.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScrapeMode {
+    /// Every page, in the site's default order.
+    Full,
+    /// Newest listings first, stopping after `stop_after` listings in a row
+    /// that are already known and unchanged.
+    Incremental { stop_after: usize },
+}
+
+impl ScrapeMode {
+    pub const DEFAULT_STOP_AFTER: usize = 50;
+
+    /// Stored in `scrape_runs.mode`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScrapeMode::Full => "full",
+            ScrapeMode::Incremental { .. } => "incremental",
+        }
+    }
+
+    pub fn parse(mode: &str, stop_after: Option<usize>) -> Option<Self> {
+        match mode {
+            "full" => Some(ScrapeMode::Full),
+            "incremental" => Some(ScrapeMode::Incremental {
+                stop_after: stop_after.unwrap_or(Self::DEFAULT_STOP_AFTER).max(1),
+            }),
+            _ => None,
+        }
+    }
+
+    /// The URL to paginate for a shard in this mode.
+    pub fn search_url(&self, shard: &ScrapeShard) -> String {
+        match self {
+            ScrapeMode::Full => shard.search_url.clone(),
+            ScrapeMode::Incremental { .. } => {
+                format!("{}/{NEWEST_FIRST_SORT}", shard.search_url)
+            }
+        }
+    }
+}
+
+/// Which inventory a search covers. Realtor serves each from its own URL.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SearchKind {
+    ForSale,
+    /// Recently sold homes, with the actual sale price and date.
+    Sold,
+    Rent,
+}
+
+impl SearchKind {
+    /// Stored in `scrape_runs.search_kind`; matches Realtor's status values.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SearchKind::ForSale => "for_sale",
+            SearchKind::Sold => "sold",
+            SearchKind::Rent => "for_rent",
+        }
+    }
+
+    pub fn parse(kind: &str) -> Option<Self> {
+        match kind {
+            "for_sale" => Some(SearchKind::ForSale),
+            "sold" => Some(SearchKind::Sold),
+            "for_rent" => Some(SearchKind::Rent),
+            _ => None,
+        }
+    }
+
+    /// Turns a for-sale search URL into this kind's. Price bands and sort
+    /// segments carry over unchanged.
+    pub fn search_url(&self, for_sale_url: &str) -> String {
+        match self {
+            SearchKind::ForSale => for_sale_url.to_string(),
+            SearchKind::Sold => format!("{for_sale_url}/{RECENTLY_SOLD}"),
+            SearchKind::Rent => for_sale_url.replacen(SEARCH_BASE, RENTALS_BASE, 1),
+        }
+    }
+
+    /// Whether a listed property with this canonical status turns up in this
+    /// kind of search. A sweep only says something about properties it would
+    /// have shown.
+    pub fn shows_status(&self, canonical_status: &str) -> bool {
+        match self {
+            SearchKind::ForSale => matches!(
+                canonical_status,
+                "Active" | "Coming Soon" | "Pending" | "Contingent"
+            ),
+            SearchKind::Sold => canonical_status == "Sold",
+            // Rentals are kept in `rental_listings`, not `properties`.
+            SearchKind::Rent => false,
+        }
+    }
+
+    /// The inverse of `search_url`, for splitting a resumed run's stored URL.
+    pub fn for_sale_url(&self, search_url: &str) -> String {
+        match self {
+            SearchKind::ForSale => search_url.to_string(),
+            SearchKind::Sold => search_url
+                .strip_suffix(&format!("/{RECENTLY_SOLD}"))
+                .unwrap_or(search_url)
+                .to_string(),
+            SearchKind::Rent => search_url.replacen(RENTALS_BASE, SEARCH_BASE, 1),
+        }
+    }
+}
+
+/// Returned from a page callback to keep paginating or stop.
+#[derive(Debug, PartialEq)]
+pub enum PageControl {
+    Continue,
+    Stop,
+}
+
+/// Where a paginated search picks up, with what the pages before it found
+/// so a resumed run is judged against the whole search.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SearchStart {
+    pub page: usize,
+    /// Listings found on the earlier pages.
+    pub seen_properties: usize,
+    /// The most listings any earlier page held.
+    pub page_size: usize,
+}
+
+/// A search from its first page.
+impl Default for SearchStart {
+    fn default() -> Self {
+        Self {
+            page: 1,
+            seen_properties: 0,
+            page_size: 0,
+        }
+    }
 }
 
-pub struct PaginatedResult {
-    pub properties: Vec<Value>,
-    pub pages_fetched: usize,
+/// What happened to a single search results page. `blocked` counts the
+/// captcha / bot-wall responses hit while fetching it.
+pub enum PageEvent {
+    Fetched {
+        page: usize,
+        url: String,
+        properties: Vec<Property>,
+        rejected: Vec<RejectedRecord>,
+        next_data: String,
+        blocked: u32,
+    },
+    Failed {
+        page: usize,
+        url: String,
+        error: String,
+        blocked: u32,
+    },
+}
+
+/// How a paginated search ended.
+#[derive(Debug, PartialEq)]
+pub enum SearchOutcome {
+    /// Every result the site had was paged through.
+    Complete { total: Option<usize> },
+    /// The search has more results than the site will page through.
+    Capped { total: Option<usize> },
+    /// The page callback asked to stop (incremental mode caught up).
+    Stopped,
+}
+
+/// A listing on a results page that didn't deserialize into a `Property`.
+#[derive(Debug)]
+pub struct RejectedRecord {
+    pub raw_json: String,
+    pub error: String,
+}
+
+/// One parsed search results page.
+pub struct SearchPage {
+    pub properties: Vec<Property>,
+    /// Listings that couldn't be parsed; the rest of the page still counts.
+    pub rejected: Vec<RejectedRecord>,
+    /// Result count for the whole search, from `__NEXT_DATA__`.
+    pub total: Option<usize>,
+    /// The page's raw `__NEXT_DATA__` JSON, kept for the archive.
+    pub next_data: String,
+}
+
+/// What replaying the page archive did.
+#[derive(Debug, Default, PartialEq)]
+pub struct ReprocessSummary {
+    pub pages: usize,
+    pub new: usize,
+    pub changed: usize,
+    pub quarantined: usize,
 }
 
 impl RealtorScraper {
-    pub fn new() -> Result<Self, ScraperError> {
-        let client = Client::builder()
-            .user_agent(USER_AGENT)
-            .timeout(Duration::from_secs(360))
-            .build()
-            .map_err(|e| ScraperError::Network(e.to_string()))?;
+    /// Builds a scraper using the fetch backend `config` names.
+    pub fn from_config(config: ScraperConfig) -> Result<Self, ScraperError> {
+        Ok(Self::with_fetcher(fetcher_from_config(&config)?).with_config(config))
+    }
+
+    pub fn with_fetcher(fetcher: Box<dyn PageFetcher>) -> Self {
+        Self {
+            fetcher,
+            config: ScraperConfig::default(),
+            mode: ScrapeMode::Full,
+            kind: SearchKind::ForSale,
+            job: None,
+        }
+    }
+
+    /// Paging, retry and enrichment settings. The fetcher is left as is.
+    pub fn with_config(mut self, config: ScraperConfig) -> Self {
+        self.config = config;
+        self
+    }
 
-        Ok(Self { client })
+    /// Reports progress to, and takes cancellation from, a registered job.
+    pub fn with_job(mut self, job: JobHandle) -> Self {
+        self.job = Some(job);
+        self
     }
 
-    pub fn run_realtor_scrape(db: &Database, state_name: String, state_abbr: String) {
+    fn is_cancelled(&self) -> bool {
+        self.job.as_ref().is_some_and(|j| j.is_cancelled())
+    }
+
+    pub fn with_mode(mut self, mode: ScrapeMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn with_kind(mut self, kind: SearchKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// The URL to paginate for a shard, for this scraper's kind and mode.
+    fn search_url(&self, shard: &ScrapeShard) -> String {
+        self.kind.search_url(&self.mode.search_url(shard))
+    }
+
+    /// Plans and starts a scrape of a whole state, refusing if one is already
+    /// running for it or `registry` is full. `overrides` adjust the
+    /// loaded config for this scrape only.
+    pub fn start_state_scrape(
+        db: &Database,
+        registry: &'static JobRegistry,
+        state_abbr: &str,
+        shard_by: ShardBy,
+        mode: ScrapeMode,
+        kind: SearchKind,
+        overrides: &ConfigOverrides,
+    ) -> Result<(), ServerError> {
+        let config = ScraperConfig::load()
+            .map_err(|e| ServerError::BadRequest(e.to_string()))?
+            .with_overrides(overrides);
+        let shards = db.with_conn(|conn| {
+            if crate::db::scrapes::state_has_running_scrape(conn, state_abbr)? {
+                return Err(ServerError::Conflict(format!(
+                    "A scrape for {state_abbr} is already running"
+                )));
+            }
+            plan_shards(conn, state_abbr, shard_by)
+        })?;
+        let job = registry.start(state_abbr, unix_now())?;
+
+        Self::run_realtor_scrape(db, shards, mode, kind, config, job);
+        Ok(())
+    }
+
+    /// Scrapes each shard in turn on one background thread. Every shard gets
+    /// its own `scrape_runs` row so a failed shard can be resumed on its own.
+    pub fn run_realtor_scrape(
+        db: &Database,
+        shards: Vec<ScrapeShard>,
+        mode: ScrapeMode,
+        kind: SearchKind,
+        config: ScraperConfig,
+        job: JobHandle,
+    ) {
         let db = db.clone(); // cheap clone (path only)
 
         std::thread::spawn(move || {
-            let now_start = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs() as i64;
-
-            // Record start
-            let run_id = db
-                .with_conn(|conn| {
-                    crate::db::scrapes::start_scrape_run(conn, &state_abbr, now_start)
-                })
-                .unwrap_or(0);
+            eprintln!("🧵 Scraper thread started for {} shard(s)", shards.len());
 
-            eprintln!("🧵 Scraper thread started for {}", state_name);
-
-            let scraper = match RealtorScraper::new() {
-                Ok(s) => s,
+            match RealtorScraper::from_config(config.clone()) {
+                Ok(scraper) => {
+                    let scraper = scraper.with_mode(mode).with_kind(kind).with_job(job);
+                    eprintln!("🌐 Using {} fetcher", scraper.fetcher.name());
+                    scraper.run_sweep(&db, shards);
+                }
                 Err(e) => {
-                    eprintln!("Scraper init failed: {e}");
-                    // Could update DB here with error
-                    return;
+                    for shard in shards {
+                        let search_url = kind.search_url(&mode.search_url(&shard));
+                        match start_run(&db, &shard, &search_url, None, mode, kind, &config) {
+                            Ok(run_id) => Self::fail_run(&db, run_id, &e),
+                            Err(db_err) => eprintln!("❌ Failed to record failed run: {db_err}"),
+                        }
+                    }
                 }
-            };
+            }
+        });
+    }
 
-            let base_url = format!(
-                "https://www.realtor.com/realestateandhomes-search/{}",
-                state_name
-            );
+    /// Picks a failed run back up after its last successful page.
+    ///
+    /// The run keeps its id, so `scrape_run_pages` ends up holding the complete
+    /// page log across every attempt. It also keeps the config it started
+    /// with; runs from before that was recorded use the current one.
+    pub fn resume_realtor_scrape(db: &Database, run_id: i64) -> Result<(), ServerError> {
+        let run = db
+            .with_conn(|conn| crate::db::scrapes::get_scrape_run(conn, run_id))?
+            .ok_or(ServerError::NotFound)?;
 
-            let mut total_props = 0;
-            let mut pages = 0;
+        if run.finished_at.is_none() || run.success == Some(true) {
+            return Err(ServerError::BadRequest(
+                "Only failed runs can be resumed".into(),
+            ));
+        }
+
+        let search_url = match run.search_url {
+            Some(url) => url,
+            None => crate::geos::states::state_name(&run.state)
+                .map(state_search_url)
+                .ok_or_else(|| ServerError::BadRequest("Run has an unknown state".into()))?,
+        };
+        let mode = ScrapeMode::parse(
+            run.mode.as_deref().unwrap_or("full"),
+            run.stop_after.map(|n| n as usize),
+        )
+        .unwrap_or(ScrapeMode::Full);
+        let kind = run
+            .search_kind
+            .as_deref()
+            .and_then(SearchKind::parse)
+            .unwrap_or(SearchKind::ForSale);
+        let config = match run.config.as_deref() {
+            Some(json) => serde_json::from_str(json).map_err(|e| {
+                ServerError::BadRequest(format!("Run has an unreadable config: {e}"))
+            })?,
+            None => ScraperConfig::load().map_err(|e| ServerError::BadRequest(e.to_string()))?,
+        };
+        let shard = ScrapeShard {
+            state_abbr: run.state,
+            label: run.shard,
+            search_url: kind.for_sale_url(&search_url),
+        };
+
+        let job = jobs::registry().start(&shard.state_abbr, unix_now())?;
+        let start_page = db.with_conn(|conn| {
+            let last = crate::db::scrapes::last_successful_page(conn, run_id)?;
+            crate::db::scrapes::reopen_scrape_run(conn, run_id)?;
+            Ok(last.map(|p| p + 1).unwrap_or(1))
+        })?;
+
+        eprintln!("⏯️ Resuming run {run_id} ({search_url}) at page {start_page}");
+
+        let db = db.clone();
+        std::thread::spawn(move || match RealtorScraper::from_config(config) {
+            Ok(scraper) => {
+                // The stored URL already carries the kind and sort order.
+                let result = scraper
+                    .with_mode(mode)
+                    .with_kind(kind)
+                    .with_job(job)
+                    .run_shard(&db, run_id, &shard, &search_url, start_page);
+                if let Err(e) = result {
+                    eprintln!("❌ Resumed run {run_id} stopped: {e}");
+                }
+            }
+            Err(e) => Self::fail_run(&db, run_id, &e),
+        });
+
+        Ok(())
+    }
+
+    fn fail_run(db: &Database, run_id: i64, e: &ScraperError) {
+        eprintln!("Scraper init failed: {e}");
+        let _ = db.with_conn(|conn| {
+            crate::db::scrapes::end_scrape_run(
+                conn,
+                run_id,
+                unix_now(),
+                0,
+                0,
+                false,
+                Some(e.to_string()),
+            )
+        });
+    }
+
+    /// Scrapes every shard of a state, then scans for duplicate properties.
+    /// If a full for-sale sweep covered the whole state, properties it didn't
+    /// see are marked off market.
+    pub(crate) fn run_sweep(&self, db: &Database, shards: Vec<ScrapeShard>) {
+        let started_at = Utc::now().naive_utc();
+        let Some(state_abbr) = shards.first().map(|s| s.state_abbr.clone()) else {
+            return;
+        };
+
+        let run_ids = match self.run_shards(db, shards, None) {
+            Ok(run_ids) => run_ids,
+            Err(e) => {
+                eprintln!("❌ {state_abbr} sweep stopped: {e}");
+                return;
+            }
+        };
+        if self.is_cancelled() {
+            return;
+        }
+
+        match db.with_conn(|conn| crate::db::duplicates::detect_duplicates(conn, unix_now())) {
+            Ok(0) => {}
+            Ok(n) => eprintln!("🔁 {n} new possible duplicate properties"),
+            Err(e) => eprintln!("❌ Duplicate scan failed: {e}"),
+        }
+        // Only a complete for-sale sweep says what is still on the market.
+        if self.mode != ScrapeMode::Full || self.kind != SearchKind::ForSale {
+            return;
+        }
+
+        let result = db.with_conn(|conn| {
+            if !crate::db::scrapes::sweep_is_complete(conn, &run_ids)? {
+                eprintln!("⚠️ {state_abbr} sweep incomplete, skipping delisting check");
+                return Ok(0);
+            }
+            mark_unseen_delisted(
+                conn,
+                &state_abbr,
+                self.kind,
+                started_at,
+                Utc::now().naive_utc(),
+            )
+        });
+        match result {
+            Ok(0) => {}
+            Ok(n) => eprintln!("📉 {n} {state_abbr} properties no longer listed"),
+            Err(e) => eprintln!("❌ Delisting check failed: {e}"),
+        }
+    }
+
+    /// Starts a run for each shard and scrapes them one after another.
+    /// Returns the ids of the runs started, or stops at the first run that
+    /// couldn't be recorded.
+    pub(crate) fn run_shards(
+        &self,
+        db: &Database,
+        shards: Vec<ScrapeShard>,
+        parent_run_id: Option<i64>,
+    ) -> Result<Vec<i64>, ServerError> {
+        let mut run_ids = Vec::new();
+        for shard in shards {
+            if self.is_cancelled() {
+                break;
+            }
+            let search_url = self.search_url(&shard);
+            let run_id = start_run(
+                db,
+                &shard,
+                &search_url,
+                parent_run_id,
+                self.mode,
+                self.kind,
+                &self.config,
+            )?;
+            self.run_shard(db, run_id, &shard, &search_url, 1)?;
+            run_ids.push(run_id);
+        }
+        Ok(run_ids)
+    }
+
+    /// Scrapes one shard. If the search hits the page cap it's split into
+    /// price bands, each scraped as a child run, until every band fits.
+    fn run_shard(
+        &self,
+        db: &Database,
+        run_id: i64,
+        shard: &ScrapeShard,
+        search_url: &str,
+        start_page: usize,
+    ) -> Result<(), ServerError> {
+        if let Some(job) = &self.job {
+            job.set_run(run_id, shard.label.as_deref());
+        }
 
-            let result = scraper.fetch_all_properties_paginated(&base_url, |properties| {
-                total_props += properties.len();
-                pages += 1;
-                // 🧠 DB LOGIC LIVES HERE
-                save_scraped_properties(&db, &properties)
+        let outcome = self.execute_run(db, run_id, search_url, start_page);
+        let capped = matches!(outcome, Some(SearchOutcome::Capped { .. }));
+        if !capped || self.mode != ScrapeMode::Full {
+            return Ok(());
+        }
+
+        let Some(bands) = shard.split_by_price() else {
+            eprintln!("⚠️ {} is capped but too narrow to split", shard.search_url);
+            return Ok(());
+        };
+
+        eprintln!("✂️ Splitting {} into price bands", shard.search_url);
+        let _ = db.with_conn(|conn| crate::db::scrapes::set_split_into(conn, run_id, bands.len()));
+        self.run_shards(db, bands, Some(run_id))?;
+        let _ = db.with_conn(|conn| crate::db::scrapes::roll_up_coverage(conn, run_id));
+        Ok(())
+    }
+
+    /// Scrapes `base_url` from `start_page` onwards for an existing run,
+    /// logging every page outcome to `scrape_run_pages`.
+    ///
+    /// Returns `None` if the run failed.
+    pub(crate) fn execute_run(
+        &self,
+        db: &Database,
+        run_id: i64,
+        base_url: &str,
+        start_page: usize,
+    ) -> Option<SearchOutcome> {
+        // Incremental mode: known-and-unchanged listings seen in a row.
+        let mut known_streak = 0;
+
+        // A resumed run still has to account for the pages it already saved.
+        let (seen_properties, page_size) = db
+            .with_conn(|conn| crate::db::scrapes::get_page_totals_before(conn, run_id, start_page))
+            .unwrap_or((0, 0));
+        let search_start = SearchStart {
+            page: start_page,
+            seen_properties,
+            page_size,
+        };
+
+        let result =
+            self.fetch_all_properties_paginated(base_url, search_start, |event| match event {
+                PageEvent::Fetched {
+                    page,
+                    url,
+                    properties,
+                    rejected,
+                    next_data,
+                    blocked,
+                } => {
+                    // 🧠 DB LOGIC LIVES HERE
+                    let outcomes = save_scraped_properties(db, &properties)
+                        .map_err(|e| ScraperError::Network(e.to_string()))?;
+                    db.with_conn(|conn| {
+                        crate::db::scrapes::record_scrape_page(
+                            conn,
+                            run_id,
+                            page,
+                            &url,
+                            true,
+                            properties.len(),
+                            blocked,
+                        )?;
+                        archive_page(conn, run_id, page, &url, &next_data, unix_now())?;
+                        quarantine_page(conn, run_id, page, &properties, &outcomes, &rejected)
+                    })
                     .map_err(|e| ScraperError::Network(e.to_string()))?;
-                Ok(())
+
+                    if let Some(job) = &self.job {
+                        job.page_saved(page, properties.len());
+                    }
+                    if self.config.enrich_details {
+                        self.enrich_listings(db, &properties, &outcomes);
+                    }
+                    if self.is_cancelled() {
+                        return Err(ScraperError::Cancelled);
+                    }
+
+                    let ScrapeMode::Incremental { stop_after } = self.mode else {
+                        return Ok(PageControl::Continue);
+                    };
+                    for outcome in outcomes {
+                        match outcome {
+                            SaveOutcome::Unchanged => known_streak += 1,
+                            SaveOutcome::Skipped(_) => {}
+                            SaveOutcome::New | SaveOutcome::Changed => known_streak = 0,
+                        }
+                        if known_streak >= stop_after {
+                            eprintln!("🛑 {known_streak} known listings in a row, caught up");
+                            return Ok(PageControl::Stop);
+                        }
+                    }
+                    Ok(PageControl::Continue)
+                }
+                PageEvent::Failed {
+                    page,
+                    url,
+                    error,
+                    blocked,
+                } => {
+                    if let Some(job) = &self.job {
+                        job.page_failed(page, &error);
+                    }
+                    db.with_conn(|conn| {
+                        crate::db::scrapes::record_scrape_page(
+                            conn, run_id, page, &url, false, 0, blocked,
+                        )
+                    })
+                    .map_err(|e| ScraperError::Network(e.to_string()))?;
+
+                    if self.is_cancelled() {
+                        return Err(ScraperError::Cancelled);
+                    }
+                    Ok(PageControl::Continue)
+                }
             });
 
-            let now_end = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs() as i64;
+        // Totals come from the page log so resumed runs count earlier attempts.
+        let (pages, total_props) = db
+            .with_conn(|conn| crate::db::scrapes::get_page_totals(conn, run_id))
+            .unwrap_or((0, 0));
 
-            if let Err(e) = result {
-                eprintln!("Scrape failed: {e}");
-                let _ = db.with_conn(|conn| {
-                    crate::db::scrapes::end_scrape_run(
-                        conn,
-                        run_id,
-                        now_end,
-                        pages,
-                        total_props,
-                        false,
-                        Some(e.to_string()),
-                    )
-                });
-            } else {
+        let error = match &result {
+            Ok(SearchOutcome::Complete { .. }) => {
                 eprintln!("✅ Scrape complete");
-                let _ = db.with_conn(|conn| {
-                    crate::db::scrapes::end_scrape_run(
-                        conn,
-                        run_id,
-                        now_end,
-                        pages,
-                        total_props,
-                        true,
-                        None,
-                    )
-                });
+                None
+            }
+            Ok(SearchOutcome::Capped { total }) => {
+                eprintln!("📏 Search hit the page cap ({total:?} results)");
+                None
+            }
+            Ok(SearchOutcome::Stopped) => {
+                eprintln!("✅ Incremental scrape caught up");
+                None
+            }
+            Err(e) => {
+                eprintln!("Scrape failed: {e}");
+                Some(e.to_string())
+            }
+        };
+
+        let _ = db.with_conn(|conn| {
+            crate::db::scrapes::end_scrape_run(
+                conn,
+                run_id,
+                unix_now(),
+                pages,
+                total_props,
+                result.is_ok(),
+                error,
+            )?;
+            crate::db::scrapes::set_block_stats(conn, run_id)?;
+            if let Ok(SearchOutcome::Complete { total } | SearchOutcome::Capped { total }) = &result
+            {
+                crate::db::scrapes::set_expected_total(conn, run_id, *total)?;
             }
+            if let Ok(SearchOutcome::Capped { .. }) = &result {
+                crate::db::scrapes::set_capped(conn, run_id)?;
+            }
+            Ok(())
         });
+
+        result.ok()
+    }
+
+    /// Replays archived pages, oldest run first, through parsing and the
+    /// change tracker, with history stamped at each page's original fetch
+    /// time. Lets a newly tracked field or a parsing fix be backfilled
+    /// without scraping again. Records that still fail go to quarantine.
+    /// A page older than what's already known about a property leaves that
+    /// property alone, so replaying into a live database rolls nothing back.
+    pub fn reprocess_archive(
+        db: &Database,
+        from_run: Option<i64>,
+    ) -> Result<ReprocessSummary, ServerError> {
+        let keys = db.with_conn(|conn| archive::archived_page_keys(conn, from_run))?;
+        let mut summary = ReprocessSummary::default();
+
+        for (run_id, page) in keys {
+            let Some(archived) =
+                db.with_conn(|conn| archive::get_archived_page(conn, run_id, page))?
+            else {
+                continue;
+            };
+
+            let parsed = serde_json::from_str::<Value>(&archived.next_data)
+                .map_err(|e| ScraperError::JsonParse(e.to_string()))
+                .and_then(|data| realtor::extract_properties(&data));
+            let (properties, rejected) = match parsed {
+                Ok(parsed) => parsed,
+                Err(e) => {
+                    eprintln!(
+                        "⚠️ Skipping archived run {run_id} page {page} ({}): {e}",
+                        archived.page_url
+                    );
+                    continue;
+                }
+            };
+
+            let observed_at = chrono::DateTime::from_timestamp(archived.fetched_at, 0)
+                .map(|dt| dt.naive_utc())
+                .ok_or_else(|| ServerError::BadRequest("Invalid archive timestamp".into()))?;
+            let outcomes = save_scraped_properties_at(db, &properties, observed_at)?;
+            db.with_conn(|conn| {
+                quarantine_page(
+                    conn,
+                    run_id,
+                    page as usize,
+                    &properties,
+                    &outcomes,
+                    &rejected,
+                )
+            })?;
+
+            summary.pages += 1;
+            for outcome in &outcomes {
+                match outcome {
+                    SaveOutcome::New => summary.new += 1,
+                    SaveOutcome::Changed => summary.changed += 1,
+                    SaveOutcome::Unchanged => {}
+                    SaveOutcome::Skipped(_) => summary.quarantined += 1,
+                }
+            }
+            summary.quarantined += rejected.len();
+        }
+
+        Ok(summary)
     }
 
+    /// Walks search result pages starting at `start.page`, reporting each
+    /// page's outcome to `on_event`.
+    ///
+    /// Stops early with `Capped` once it's clear the site won't page through
+    /// the whole result set: the reported total needs more than `page_cap`
+    /// pages, or the last page allowed is still full. Incremental scrapes
+    /// only want the newest results, so they skip the cap check.
+    ///
+    /// Returns an error if the page limit of consecutive failures is hit, so
+    /// the run is recorded as failed and can be resumed later.
     pub fn fetch_all_properties_paginated<F>(
         &self,
         base_url: &str,
-        mut on_page: F,
-    ) -> Result<(), ScraperError>
+        start: SearchStart,
+        mut on_event: F,
+    ) -> Result<SearchOutcome, ScraperError>
     where
-        F: FnMut(Vec<Property>) -> Result<(), ScraperError>, // <- updated
+        F: FnMut(PageEvent) -> Result<PageControl, ScraperError>,
     {
-        let mut page = 1;
+        let mut page = start.page.max(1);
         let mut consecutive_failures = 0;
         let mut seen_pages = HashSet::new();
+        let mut total = None;
+        let mut page_size = start.page_size;
+        let mut seen_properties = start.seen_properties;
 
         loop {
-            let page_url = if page == 1 {
-                base_url.to_string()
-            } else {
-                format!("{base_url}/pg-{page}")
-            };
+            let page_url = page_url(base_url, page);
 
             eprintln!("📄 Scraping page {page}: {page_url}");
 
-            match self.fetch_properties_via_zenrows(&page_url) {
-                Ok(properties) => {
-                    if properties.is_empty() {
+            let result = self.fetch_properties(&page_url);
+            // A backend that retried past blocks counts them itself; a blocked
+            // page it handed back as-is was caught by `fetch_properties`.
+            let blocked = self
+                .fetcher
+                .take_blocked_attempts()
+                .max(matches!(result, Err(ScraperError::Blocked(_))) as u32);
+
+            match result {
+                Ok(SearchPage {
+                    properties,
+                    rejected,
+                    total: page_total,
+                    next_data,
+                }) => {
+                    let found = properties.len() + rejected.len();
+                    if found == 0 {
                         eprintln!("🏁 No properties found, stopping");
                         break;
                     }
@@ -146,183 +815,283 @@ impl RealtorScraper {
                         break;
                     }
 
-                    eprintln!("✅ Page {page} parsed ({} properties)", properties.len());
+                    eprintln!(
+                        "✅ Page {page} parsed ({} properties, {} rejected)",
+                        properties.len(),
+                        rejected.len()
+                    );
+
+                    total = page_total.or(total);
+                    page_size = page_size.max(found);
+                    seen_properties += found;
+                    let full_page = found == page_size;
+
+                    let control = on_event(PageEvent::Fetched {
+                        page,
+                        url: page_url,
+                        properties,
+                        rejected,
+                        next_data,
+                        blocked,
+                    })?;
+                    if control == PageControl::Stop {
+                        return Ok(SearchOutcome::Stopped);
+                    }
+
+                    let capped = match (self.mode, total) {
+                        (ScrapeMode::Full, Some(t)) => t > self.config.page_cap * page_size,
+                        _ => false,
+                    };
+                    if capped {
+                        return Ok(SearchOutcome::Capped { total });
+                    }
 
-                    on_page(properties)?; // now matches Vec<Property>
+                    if page >= self.config.page_cap {
+                        let truncated = match total {
+                            Some(t) => t > seen_properties,
+                            None => full_page,
+                        };
+                        return Ok(if truncated {
+                            SearchOutcome::Capped { total }
+                        } else {
+                            SearchOutcome::Complete { total }
+                        });
+                    }
 
                     page += 1;
                     consecutive_failures = 0;
-                    std::thread::sleep(Duration::from_secs(2));
+                    self.pause_between_pages();
                 }
 
                 Err(e) => {
                     consecutive_failures += 1;
                     eprintln!("⚠️ Page {page} failed (attempt {consecutive_failures}): {e}");
 
-                    if consecutive_failures >= 3 {
+                    if on_event(PageEvent::Failed {
+                        page,
+                        url: page_url,
+                        error: e.to_string(),
+                        blocked,
+                    })? == PageControl::Stop
+                    {
+                        return Ok(SearchOutcome::Stopped);
+                    }
+
+                    if consecutive_failures >= self.config.max_page_failures {
                         eprintln!("❌ Too many failures, aborting scrape");
-                        break;
+                        return Err(e);
                     }
 
-                    std::thread::sleep(Duration::from_secs(2));
+                    self.pause_between_pages();
                 }
             }
         }
 
-        Ok(())
+        Ok(SearchOutcome::Complete { total })
     }
 
-    pub fn fetch_properties_via_zenrows(&self, url: &str) -> Result<Vec<Property>, ScraperError> {
-        let html = self.fetch_html_via_zenrows(url)?;
-
-        #[cfg(debug_assertions)]
-        {
-            std::fs::write("realtor_debug.html", &html)
-                .map_err(|e| ScraperError::IoError(e.to_string()))?;
-        }
+    /// Fetches the detail page of each new or changed listing and stores
+    /// what it adds. The search results are already saved, so a failed
+    /// detail page is logged and skipped.
+    fn enrich_listings(&self, db: &Database, properties: &[Property], outcomes: &[SaveOutcome]) {
+        for (property, outcome) in properties.iter().zip(outcomes) {
+            if !matches!(outcome, SaveOutcome::New | SaveOutcome::Changed) {
+                continue;
+            }
+            let Some(permalink) = property.permalink.as_deref() else {
+                continue;
+            };
+            let Ok(listing) = ScrapedProperty::from_scraper_property(property) else {
+                continue;
+            };
+            // Detail pages are stored against the for-sale property.
+            if listing.is_rental() {
+                continue;
+            }
+            if self.is_cancelled() {
+                return;
+            }
 
-        let data = Self::extract_next_data(&html)?;
-        let properties = Self::extract_properties(&data)?; // returns Vec<Property>
+            self.pause_between_pages();
+            let url = realtor::detail_url(permalink);
+            let result = self.fetch_detail(&url);
+            // Blocks on detail pages shouldn't count against the next search page.
+            self.fetcher.take_blocked_attempts();
 
-        Ok(properties)
+            let fetched_at = Utc::now().naive_utc();
+            let saved = result.map_err(|e| e.to_string()).and_then(|detail| {
+                let details = ListingDetails::from_detail(&detail, fetched_at);
+                db.with_conn(|conn| {
+                    save_listing_details(conn, &listing.address_key(), &url, &details, fetched_at)
+                })
+                .map_err(|e| e.to_string())
+            });
+            match saved {
+                Ok(true) => eprintln!("🔎 Enriched {}", listing.address_line),
+                Ok(false) => eprintln!(
+                    "⏭️ Skipped detail page {url}: no property at {}",
+                    listing.address_line
+                ),
+                Err(e) => eprintln!("⚠️ Detail page {url} failed: {e}"),
+            }
+        }
     }
 
-    pub fn fetch_html_via_zenrows(&self, url: &str) -> Result<String, ScraperError> {
-        const MAX_ATTEMPTS: u64 = 5;
-        const MAX_BACKOFF_SECS: u64 = 10;
-        const JITTER_MAX_SECS: u64 = 2;
-
-        let mut last_err = None;
-
-        for attempt in 1..=MAX_ATTEMPTS {
-            let start = std::time::Instant::now();
-
-            match self.try_fetch_html_via_zenrows(url) {
-                Ok(html) => {
-                    eprintln!(
-                        "✅ ZenRows success attempt {attempt} in {:?}",
-                        start.elapsed()
-                    );
-                    return Ok(html);
-                }
-                Err(e) => {
-                    eprintln!(
-                        "⚠️ ZenRows attempt {attempt} failed in {:?}: {e}",
-                        start.elapsed()
-                    );
+    /// Fetches and parses a listing's detail page.
+    pub fn fetch_detail(&self, url: &str) -> Result<PropertyDetail, ScraperError> {
+        let page = self.fetcher.fetch(url)?;
+        if let Some(reason) = detect_block(&page) {
+            return Err(ScraperError::Blocked(reason));
+        }
+        if !page.is_success() {
+            return Err(ScraperError::Network(format!(
+                "{} fetcher got HTTP {} for {}",
+                self.fetcher.name(),
+                page.status,
+                page.url
+            )));
+        }
 
-                    last_err = Some(e);
+        let data: Value = serde_json::from_str(&extract_next_data(&page.body)?)
+            .map_err(|e| ScraperError::JsonParse(e.to_string()))?;
+        realtor::extract_detail(&data)
+    }
 
-                    // backoff
-                    let base = std::cmp::min(2 * attempt, MAX_BACKOFF_SECS);
-                    let jitter = rand::thread_rng().gen_range(0..=JITTER_MAX_SECS);
-                    std::thread::sleep(Duration::from_secs(base + jitter));
-                }
-            }
+    /// Local fetchers don't need to be throttled.
+    fn pause_between_pages(&self) {
+        if self.fetcher.is_remote() {
+            std::thread::sleep(Duration::from_secs(self.config.page_delay_secs));
         }
-
-        Err(last_err.unwrap_or_else(|| ScraperError::Network("ZenRows retry loop failed".into())))
     }
 
-    pub fn try_fetch_html_via_zenrows(&self, url: &str) -> Result<String, ScraperError> {
-        use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, ACCEPT_LANGUAGE, REFERER};
+    pub fn fetch_properties(&self, url: &str) -> Result<SearchPage, ScraperError> {
+        let past_the_end = || SearchPage {
+            properties: Vec::new(),
+            rejected: Vec::new(),
+            total: None,
+            next_data: String::new(),
+        };
+        // Past the last page of results. Retrying backends report it as an
+        // error; the others hand back the 404 page.
+        let page = match self.fetcher.fetch(url) {
+            Err(ScraperError::NotFound(_)) => return Ok(past_the_end()),
+            result => result?,
+        };
 
-        let api_key = std::env::var("ZENROWS_API_KEY").map_err(|_| {
-            ScraperError::Config("ZENROWS_API_KEY environment variable not set".into())
-        })?;
+        if let Some(reason) = detect_block(&page) {
+            return Err(ScraperError::Blocked(reason));
+        }
 
-        let mut headers = HeaderMap::new();
-        headers.insert(REFERER, HeaderValue::from_static("https://www.google.com/"));
-        // headers.insert(
-        //     ACCEPT,
-        //     HeaderValue::from_static("text/html,application/xhtml+xml"),
-        // );
-        // headers.insert(ACCEPT_LANGUAGE, HeaderValue::from_static("en-US,en;q=0.9"));
-
-        let mut params = HashMap::new();
-        //params.insert("custom_headers", "true".to_string());
-        params.insert("url", url.to_string());
-        params.insert("apikey", api_key);
-        params.insert("js_render", "true".to_string());
-        params.insert("premium_proxy", "true".to_string());
-        params.insert("proxy_country", "us".to_string());
-        // params.insert("wait_for", "script#__NEXT_DATA__".to_string());
-        // params.insert("wait", "5000".to_string()); // 5 second delay
-        // params.insert("wait_for", "script#__NEXT_DATA__".to_string());
-        params.insert("original_status", "true".to_string());
-        // params.insert("mode", "auto".to_string());
-
-        let resp = self
-            .client
-            .get("https://api.zenrows.com/v1/")
-            .headers(headers)
-            .query(&params)
-            .send()
-            .map_err(|e| ScraperError::Network(e.to_string()))?;
-
-        // 1️⃣ ZenRows HTTP status
-        let status = resp.status();
-
-        // 2️⃣ ORIGINAL STATUS (add THIS BLOCK)
-        let original_status = resp
-            .headers()
-            .iter()
-            .find(|(k, _)| k.as_str().to_ascii_lowercase().contains("original"))
-            .map(|(_, v)| v.to_str().unwrap_or("?").to_string())
-            .unwrap_or("<none>".to_string());
-
-        // 3️⃣ Now read the body
-        let text = resp
-            .text()
-            .map_err(|e| ScraperError::Network(e.to_string()))?;
-
-        if !status.is_success() {
+        if page.status == 404 {
+            return Ok(past_the_end());
+        }
+
+        if !page.is_success() {
             return Err(ScraperError::Network(format!(
-                "ZenRows HTTP {} ({}) : {}",
-                status, original_status, text
+                "{} fetcher got HTTP {} for {}",
+                self.fetcher.name(),
+                page.status,
+                page.url
             )));
         }
 
-        if text.starts_with('{') {
-            if let Ok(json) = serde_json::from_str::<serde_json::Value>(&text) {
-                if json.get("code").is_some() {
-                    return Err(ScraperError::Network(format!(
-                        "ZenRows API error ({}) : {}",
-                        original_status, text
-                    )));
-                }
-            }
+        #[cfg(all(debug_assertions, not(test)))]
+        {
+            std::fs::write("realtor_debug.html", &page.body)
+                .map_err(|e| ScraperError::IoError(e.to_string()))?;
         }
 
-        Ok(text)
-    }
-
-    fn extract_next_data(html: &str) -> Result<Value, ScraperError> {
-        let document = Html::parse_document(html);
-        let selector = Selector::parse(r#"script[id="__NEXT_DATA__"]"#)
-            .map_err(|e| ScraperError::HtmlParse(e.to_string()))?;
-
-        let element = document
-            .select(&selector)
-            .next()
-            .ok_or(ScraperError::MissingNextData)?;
-
-        let json_text = element.text().next().ok_or(ScraperError::MissingNextData)?;
+        let next_data = extract_next_data(&page.body)?;
         let data: Value =
-            serde_json::from_str(json_text).map_err(|e| ScraperError::JsonParse(e.to_string()))?;
-        Ok(data)
+            serde_json::from_str(&next_data).map_err(|e| ScraperError::JsonParse(e.to_string()))?;
+        let (properties, rejected) = realtor::extract_properties(&data)?;
+
+        Ok(SearchPage {
+            properties,
+            rejected,
+            total: realtor::extract_total(&data),
+            next_data,
+        })
     }
+}
 
-    fn extract_properties(data: &Value) -> Result<Vec<Property>, ScraperError> {
-        let arr = data["props"]["pageProps"]["properties"].as_array().ok_or(
-            ScraperError::UnexpectedShape("properties missing".to_string()),
-        )?;
+fn start_run(
+    db: &Database,
+    shard: &ScrapeShard,
+    search_url: &str,
+    parent_run_id: Option<i64>,
+    mode: ScrapeMode,
+    kind: SearchKind,
+    config: &ScraperConfig,
+) -> Result<i64, ServerError> {
+    let config = config.to_json();
+    let run = NewScrapeRun {
+        state_abbr: &shard.state_abbr,
+        shard: shard.label.as_deref(),
+        search_url,
+        parent_run_id,
+        mode: mode.as_str(),
+        stop_after: match mode {
+            ScrapeMode::Full => None,
+            ScrapeMode::Incremental { stop_after } => Some(stop_after),
+        },
+        search_kind: kind.as_str(),
+        config: Some(&config),
+    };
+    db.with_conn(|conn| crate::db::scrapes::start_scrape_run(conn, &run, unix_now()))
+}
 
-        let properties: Result<Vec<_>, _> = arr
-            .iter()
-            .map(|v| serde_json::from_value(v.clone()))
-            .collect();
+/// Stores the listings a page couldn't use: ones that didn't deserialize and
+/// ones that failed validation.
+fn quarantine_page(
+    conn: &rusqlite::Connection,
+    run_id: i64,
+    page: usize,
+    properties: &[Property],
+    outcomes: &[SaveOutcome],
+    rejected: &[RejectedRecord],
+) -> Result<(), ServerError> {
+    let now = unix_now();
+    for record in rejected {
+        quarantine_record(
+            conn,
+            Some(run_id),
+            Some(page),
+            "deserialize",
+            &record.raw_json,
+            &record.error,
+            now,
+        )?;
+    }
+    for (property, outcome) in properties.iter().zip(outcomes) {
+        if let SaveOutcome::Skipped(error) = outcome {
+            let raw_json =
+                serde_json::to_string(property).map_err(|_| ServerError::InternalError)?;
+            quarantine_record(
+                conn,
+                Some(run_id),
+                Some(page),
+                "validate",
+                &raw_json,
+                error,
+                now,
+            )?;
+        }
+    }
+    Ok(())
+}
 
-        properties.map_err(|e| ScraperError::Deserialize(e.to_string()))
+fn page_url(base_url: &str, page: usize) -> String {
+    if page == 1 {
+        base_url.to_string()
+    } else {
+        format!("{base_url}/pg-{page}")
     }
 }
+
+fn unix_now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
@@ -4,6 +4,9 @@ use std::fmt;
 #[derive(Debug)]
 pub enum ScraperError {
     Network(String),
+    /// The site has no such page, e.g. past the last page of results.
+    /// Not worth retrying.
+    NotFound(String),
     Blocked(String),
     HtmlParse(String),
     MissingNextData,
@@ -12,12 +15,14 @@ pub enum ScraperError {
     IoError(String),
     Config(String),
     Deserialize(String),
+    Cancelled,
 }
 
 impl fmt::Display for ScraperError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             ScraperError::Network(msg) => write!(f, "Network error: {msg}"),
+            ScraperError::NotFound(url) => write!(f, "Page not found: {url}"),
             ScraperError::Blocked(msg) => write!(f, "Blocked by site: {msg}"),
             ScraperError::HtmlParse(msg) => write!(f, "HTML parse error: {msg}"),
             ScraperError::MissingNextData => write!(f, "__NEXT_DATA__ not found"),
@@ -26,6 +31,7 @@ impl fmt::Display for ScraperError {
             ScraperError::IoError(msg) => write!(f, "I/O error: {msg}"),
             ScraperError::Config(msg) => write!(f, "Config error: {msg}"),
             ScraperError::Deserialize(msg) => write!(f, "Deserialization error: {msg}"),
+            ScraperError::Cancelled => write!(f, "Cancelled by admin"),
         }
     }
 }
@@ -0,0 +1,268 @@
+// shards.rs
+//
+// Realtor stops paging long before a large state is covered, so a state
+// scrape can be split into county or ZIP searches. Each shard is paginated on
+// its own and gets its own `scrape_runs` row; listings that show up in more
+// than one shard collapse onto the same `property_sources` row.
+use crate::errors::ServerError;
+use crate::geos::{counties, states, zips};
+use rusqlite::Connection;
+
+pub(crate) const SEARCH_BASE: &str = "https://www.realtor.com/realestateandhomes-search";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShardBy {
+    State,
+    County,
+    Zip,
+}
+
+impl ShardBy {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "state" => Some(Self::State),
+            "county" => Some(Self::County),
+            "zip" => Some(Self::Zip),
+            _ => None,
+        }
+    }
+}
+
+/// One search to paginate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScrapeShard {
+    pub state_abbr: String,
+    /// `None` for a whole-state search, otherwise e.g. `zip:84101`.
+    pub label: Option<String>,
+    pub search_url: String,
+}
+
+impl ScrapeShard {
+    pub fn state(state_abbr: &str, state_name: &str) -> Self {
+        Self {
+            state_abbr: state_abbr.to_string(),
+            label: None,
+            search_url: state_search_url(state_name),
+        }
+    }
+
+    pub fn county(state_abbr: &str, county_name: &str) -> Self {
+        Self {
+            state_abbr: state_abbr.to_string(),
+            label: Some(format!("county:{county_name}")),
+            search_url: county_search_url(county_name, state_abbr),
+        }
+    }
+
+    pub fn zip(state_abbr: &str, zip: &str) -> Self {
+        Self {
+            state_abbr: state_abbr.to_string(),
+            label: Some(format!("zip:{zip}")),
+            search_url: format!("{SEARCH_BASE}/{zip}"),
+        }
+    }
+}
+
+/// Lists the searches needed to cover a state.
+///
+/// County and ZIP lists come from the Census reference tables (see
+/// `geos::load_reference_data`); without them only whole-state shards work.
+pub fn plan_shards(
+    conn: &Connection,
+    state_abbr: &str,
+    by: ShardBy,
+) -> Result<Vec<ScrapeShard>, ServerError> {
+    let state_name = states::state_name(state_abbr)
+        .ok_or_else(|| ServerError::BadRequest("Invalid state".into()))?;
+
+    let shards: Vec<ScrapeShard> = match by {
+        ShardBy::State => vec![ScrapeShard::state(state_abbr, state_name)],
+        ShardBy::County => counties::counties_for_state(conn, state_abbr)?
+            .iter()
+            .map(|name| ScrapeShard::county(state_abbr, name))
+            .collect(),
+        ShardBy::Zip => zips::zips_for_state(conn, state_abbr)?
+            .iter()
+            .map(|zip| ScrapeShard::zip(state_abbr, zip))
+            .collect(),
+    };
+
+    Ok(shards)
+}
+
+/// A list price range. `max: None` is open-ended (`price-1000000-na`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PriceBand {
+    pub min: u64,
+    pub max: Option<u64>,
+}
+
+/// Bands narrower than this aren't split again; whatever they can't page
+/// through is reported as missing coverage instead.
+const MIN_BAND_WIDTH: u64 = 10_000;
+
+impl PriceBand {
+    pub const ALL: PriceBand = PriceBand { min: 0, max: None };
+
+    /// Parses the `price-0-250000` path segment.
+    pub fn parse(segment: &str) -> Option<Self> {
+        let (min, max) = segment.strip_prefix("price-")?.split_once('-')?;
+        let max = match max {
+            "na" => None,
+            n => Some(n.parse().ok()?),
+        };
+        Some(Self {
+            min: min.parse().ok()?,
+            max,
+        })
+    }
+
+    /// `0-250000`, or `1000000-na` for the open-ended band.
+    pub fn range(&self) -> String {
+        match self.max {
+            Some(max) => format!("{}-{}", self.min, max),
+            None => format!("{}-na", self.min),
+        }
+    }
+
+    pub fn segment(&self) -> String {
+        format!("price-{}", self.range())
+    }
+
+    /// Halves the band. The open-ended top band is split at $1M first and
+    /// then at double its floor.
+    pub fn split(&self) -> Option<(PriceBand, PriceBand)> {
+        let mid = match self.max {
+            None if self.min == 0 => 1_000_000,
+            None => self.min * 2,
+            Some(max) if max - self.min < MIN_BAND_WIDTH => return None,
+            Some(max) => (self.min + max) / 2 / 1_000 * 1_000,
+        };
+
+        Some((
+            PriceBand {
+                min: self.min,
+                max: Some(mid),
+            },
+            PriceBand {
+                min: mid + 1,
+                max: self.max,
+            },
+        ))
+    }
+}
+
+impl ScrapeShard {
+    /// Splits this search into two price bands, or `None` if its band is
+    /// already too narrow.
+    pub fn split_by_price(&self) -> Option<Vec<ScrapeShard>> {
+        let (base_url, band) = match self.search_url.rsplit_once('/') {
+            Some((base, last)) => match PriceBand::parse(last) {
+                Some(band) => (base, band),
+                None => (self.search_url.as_str(), PriceBand::ALL),
+            },
+            None => (self.search_url.as_str(), PriceBand::ALL),
+        };
+        let base_label = self
+            .label
+            .as_deref()
+            .map(|l| l.split(" price:").next().unwrap_or(l))
+            .filter(|l| !l.starts_with("price:"));
+
+        let (low, high) = band.split()?;
+        Some(
+            [low, high]
+                .iter()
+                .map(|b| ScrapeShard {
+                    state_abbr: self.state_abbr.clone(),
+                    label: Some(match base_label {
+                        Some(l) => format!("{l} price:{}", b.range()),
+                        None => format!("price:{}", b.range()),
+                    }),
+                    search_url: format!("{base_url}/{}", b.segment()),
+                })
+                .collect(),
+        )
+    }
+}
+
+pub fn state_search_url(state_name: &str) -> String {
+    format!("{SEARCH_BASE}/{state_name}")
+}
+
+/// `Salt Lake County`, `UT` → `.../Salt-Lake-County_UT`.
+///
+/// Names scraped from listings come without a suffix ("Salt Lake"), so
+/// "County" is added unless the name already carries one (Parish, Borough, ...).
+pub fn county_search_url(county_name: &str, state_abbr: &str) -> String {
+    const SUFFIXES: &[&str] = &[
+        "County",
+        "Parish",
+        "Borough",
+        "Census Area",
+        "Municipality",
+        "city",
+    ];
+
+    let name = county_name.trim();
+    let full = if SUFFIXES.iter().any(|s| name.ends_with(s)) {
+        name.to_string()
+    } else {
+        format!("{name} County")
+    };
+
+    let slug = full.split_whitespace().collect::<Vec<_>>().join("-");
+    format!("{SEARCH_BASE}/{slug}_{state_abbr}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn price_bands_split_until_too_narrow() {
+        let shard = ScrapeShard::county("UT", "Salt Lake County");
+
+        let bands = shard.split_by_price().unwrap();
+        assert_eq!(
+            bands[0].search_url,
+            "https://www.realtor.com/realestateandhomes-search/Salt-Lake-County_UT/price-0-1000000"
+        );
+        assert_eq!(
+            bands[1].label.as_deref(),
+            Some("county:Salt Lake County price:1000001-na")
+        );
+
+        let low = bands[0].split_by_price().unwrap();
+        assert_eq!(
+            low[1].search_url,
+            "https://www.realtor.com/realestateandhomes-search/Salt-Lake-County_UT/price-500001-1000000"
+        );
+        assert_eq!(
+            low[1].label.as_deref(),
+            Some("county:Salt Lake County price:500001-1000000")
+        );
+
+        let narrow = PriceBand {
+            min: 250_000,
+            max: Some(255_000),
+        };
+        assert_eq!(narrow.split(), None);
+    }
+
+    #[test]
+    fn county_urls_add_county_suffix_only_when_missing() {
+        assert_eq!(
+            county_search_url("Salt Lake County", "UT"),
+            "https://www.realtor.com/realestateandhomes-search/Salt-Lake-County_UT"
+        );
+        assert_eq!(
+            county_search_url("Salt Lake", "UT"),
+            "https://www.realtor.com/realestateandhomes-search/Salt-Lake-County_UT"
+        );
+        assert_eq!(
+            county_search_url("Orleans Parish", "LA"),
+            "https://www.realtor.com/realestateandhomes-search/Orleans-Parish_LA"
+        );
+    }
+}
@@ -0,0 +1,104 @@
+//! Source adapters: one per listing site, each turning that site's search
+//! results page into `ScrapedProperty` values for the shared change tracker.
+//! Adapters are for importing saved pages only. Realtor has none: its pages
+//! come in through `RealtorScraper`, which also archives them and quarantines
+//! the records it can't use.
+
+pub mod realtor;
+pub mod zillow;
+
+use crate::db::connection::Database;
+use crate::db::properties::{save_listings_at, SaveOutcome};
+use crate::domain::property::ScrapedProperty;
+use crate::errors::ServerError;
+use crate::scraper::scraper::RejectedRecord;
+use crate::scraper::ScraperError;
+use chrono::NaiveDateTime;
+use scraper::{Html, Selector};
+
+/// Parses one listing site's search results pages.
+pub trait SourceAdapter {
+    /// Short site name recorded against every change the site reports.
+    fn site(&self) -> &'static str;
+
+    /// Turns a search results page into listings. A listing that can't be
+    /// read or is missing identifying fields is rejected on its own rather
+    /// than failing the page.
+    fn parse_search_page(&self, html: &str) -> Result<SourcePage, ScraperError>;
+}
+
+/// One search results page, parsed by a `SourceAdapter`.
+#[derive(Debug, Default)]
+pub struct SourcePage {
+    pub listings: Vec<ScrapedProperty>,
+    pub rejected: Vec<RejectedRecord>,
+    /// Result count for the whole search, when the page reports it.
+    pub total: Option<usize>,
+}
+
+/// Counts from importing one page.
+#[derive(Debug, Default, PartialEq)]
+pub struct ImportSummary {
+    pub new: usize,
+    pub changed: usize,
+    pub unchanged: usize,
+    pub rejected: usize,
+    /// Results the whole search reports, if the page says.
+    pub total: Option<usize>,
+}
+
+/// The adapter for a site name, as given on the command line.
+pub fn adapter_for(site: &str) -> Option<Box<dyn SourceAdapter>> {
+    match site {
+        "zillow" => Some(Box::new(zillow::ZillowAdapter)),
+        _ => None,
+    }
+}
+
+/// Parses a saved search page with `adapter` and saves its listings, with
+/// history stamped at `observed_at`. Rejected listings are counted and
+/// logged.
+pub fn import_search_page(
+    db: &Database,
+    adapter: &dyn SourceAdapter,
+    html: &str,
+    observed_at: NaiveDateTime,
+) -> Result<ImportSummary, ServerError> {
+    let page = adapter
+        .parse_search_page(html)
+        .map_err(|e| ServerError::BadRequest(e.to_string()))?;
+    for record in &page.rejected {
+        eprintln!("⚠️ {} listing rejected: {}", adapter.site(), record.error);
+    }
+
+    let mut summary = ImportSummary {
+        rejected: page.rejected.len(),
+        total: page.total,
+        ..Default::default()
+    };
+    for outcome in save_listings_at(db, &page.listings, observed_at)? {
+        match outcome {
+            SaveOutcome::New => summary.new += 1,
+            SaveOutcome::Changed => summary.changed += 1,
+            SaveOutcome::Unchanged => summary.unchanged += 1,
+            SaveOutcome::Skipped(_) => summary.rejected += 1,
+        }
+    }
+    Ok(summary)
+}
+
+/// The `__NEXT_DATA__` JSON text, exactly as served. Realtor and Zillow are
+/// both Next.js sites and embed their search state this way.
+pub fn extract_next_data(html: &str) -> Result<String, ScraperError> {
+    let document = Html::parse_document(html);
+    let selector = Selector::parse(r#"script[id="__NEXT_DATA__"]"#)
+        .map_err(|e| ScraperError::HtmlParse(e.to_string()))?;
+
+    let element = document
+        .select(&selector)
+        .next()
+        .ok_or(ScraperError::MissingNextData)?;
+
+    let json_text = element.text().next().ok_or(ScraperError::MissingNextData)?;
+    Ok(json_text.to_string())
+}
@@ -0,0 +1,60 @@
+// Realtor.com search and detail page parsing, used by `RealtorScraper`.
+use crate::scraper::models::PropertyDetail;
+use crate::scraper::scraper::RejectedRecord;
+use crate::scraper::{Property, ScraperError};
+use serde::Deserialize;
+use serde_json::Value;
+
+/// Listing detail pages live at `{DETAIL_BASE}/{permalink}`.
+const DETAIL_BASE: &str = "https://www.realtor.com/realestateandhomes-detail";
+
+pub fn detail_url(permalink: &str) -> String {
+    format!("{DETAIL_BASE}/{permalink}")
+}
+
+/// The listing on a detail page. Older pages keep it in the Redux state,
+/// newer ones straight under `pageProps`.
+pub(crate) fn extract_detail(data: &Value) -> Result<PropertyDetail, ScraperError> {
+    let page_props = &data["props"]["pageProps"];
+    let detail = match &page_props["initialReduxState"]["propertyDetails"] {
+        Value::Null => &page_props["property"],
+        v => v,
+    };
+    if detail.is_null() {
+        return Err(ScraperError::UnexpectedShape(
+            "propertyDetails missing".to_string(),
+        ));
+    }
+    PropertyDetail::deserialize(detail).map_err(|e| ScraperError::Deserialize(e.to_string()))
+}
+
+pub(crate) fn extract_total(data: &Value) -> Option<usize> {
+    let page_props = &data["props"]["pageProps"];
+    page_props["totalProperties"]
+        .as_u64()
+        .or_else(|| page_props["searchResults"]["home_search"]["total"].as_u64())
+        .map(|n| n as usize)
+}
+
+/// Deserializes listings one at a time so a single bad record doesn't
+/// cost the rest of the page.
+pub(crate) fn extract_properties(
+    data: &Value,
+) -> Result<(Vec<Property>, Vec<RejectedRecord>), ScraperError> {
+    let arr = data["props"]["pageProps"]["properties"].as_array().ok_or(
+        ScraperError::UnexpectedShape("properties missing".to_string()),
+    )?;
+
+    let mut properties = Vec::with_capacity(arr.len());
+    let mut rejected = Vec::new();
+    for v in arr {
+        match Property::deserialize(v) {
+            Ok(p) => properties.push(p),
+            Err(e) => rejected.push(RejectedRecord {
+                raw_json: v.to_string(),
+                error: ScraperError::Deserialize(e.to_string()).to_string(),
+            }),
+        }
+    }
+    Ok((properties, rejected))
+}
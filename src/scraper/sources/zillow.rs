@@ -0,0 +1,272 @@
+use crate::domain::property::ScrapedProperty;
+use crate::scraper::scraper::RejectedRecord;
+use crate::scraper::sources::{extract_next_data, SourceAdapter, SourcePage};
+use crate::scraper::ScraperError;
+use serde::Deserialize;
+use serde_json::Value;
+
+// listResults[]
+//  ├── zpid
+//  ├── statusType / statusText
+//  ├── unformattedPrice
+//  ├── addressStreet / addressCity / addressState / addressZipcode
+//  ├── beds / baths / area
+//  ├── latLong
+//  │    ├── latitude
+//  │    └── longitude
+//  └── hdpData
+//       └── homeInfo
+//            ├── homeStatus, homeType
+//            ├── price, bedrooms, bathrooms, livingArea
+//            ├── lotAreaValue, lotAreaUnit
+//            ├── yearBuilt
+//            ├── priceReduction
+//            └── listing_sub_type
+
+pub const ZILLOW_SITE: &str = "zillow";
+
+const SQFT_PER_ACRE: f64 = 43_560.0;
+
+/// Zillow search pages. Results come from the `__NEXT_DATA__` search state,
+/// under `searchPageState.cat1.searchResults.listResults`.
+pub struct ZillowAdapter;
+
+impl SourceAdapter for ZillowAdapter {
+    fn site(&self) -> &'static str {
+        ZILLOW_SITE
+    }
+
+    fn parse_search_page(&self, html: &str) -> Result<SourcePage, ScraperError> {
+        let data: Value = serde_json::from_str(&extract_next_data(html)?)
+            .map_err(|e| ScraperError::JsonParse(e.to_string()))?;
+        let cat1 = &data["props"]["pageProps"]["searchPageState"]["cat1"];
+        let results = cat1["searchResults"]["listResults"].as_array().ok_or(
+            ScraperError::UnexpectedShape("listResults missing".to_string()),
+        )?;
+
+        let mut page = SourcePage {
+            total: cat1["searchList"]["totalResultCount"]
+                .as_u64()
+                .map(|n| n as usize),
+            ..Default::default()
+        };
+        for v in results {
+            let parsed = ZillowListing::deserialize(v)
+                .map_err(|e| ScraperError::Deserialize(e.to_string()).to_string())
+                .and_then(|listing| listing.into_scraped());
+            match parsed {
+                Ok(listing) => page.listings.push(listing),
+                Err(error) => page.rejected.push(RejectedRecord {
+                    raw_json: v.to_string(),
+                    error,
+                }),
+            }
+        }
+        Ok(page)
+    }
+}
+
+fn string_or_number<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(match Option::<Value>::deserialize(deserializer)? {
+        Some(Value::String(s)) => Some(s),
+        Some(Value::Number(n)) => Some(n.to_string()),
+        _ => None,
+    })
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ZillowListing {
+    #[serde(default, deserialize_with = "string_or_number")]
+    zpid: Option<String>,
+    status_type: Option<String>,
+    unformatted_price: Option<i64>,
+    address_street: Option<String>,
+    address_city: Option<String>,
+    address_state: Option<String>,
+    address_zipcode: Option<String>,
+    beds: Option<i64>,
+    baths: Option<f64>,
+    area: Option<i64>,
+    lat_long: Option<LatLong>,
+    hdp_data: Option<HdpData>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LatLong {
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HdpData {
+    home_info: Option<HomeInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HomeInfo {
+    home_status: Option<String>,
+    home_type: Option<String>,
+    price: Option<i64>,
+    bedrooms: Option<i64>,
+    bathrooms: Option<f64>,
+    living_area: Option<i64>,
+    lot_area_value: Option<f64>,
+    lot_area_unit: Option<String>,
+    year_built: Option<i64>,
+    price_reduction: Option<String>,
+    #[serde(rename = "listing_sub_type")]
+    listing_sub_type: Option<ListingSubType>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ListingSubType {
+    #[serde(rename = "is_comingSoon")]
+    is_coming_soon: Option<bool>,
+    is_foreclosure: Option<bool>,
+    #[serde(rename = "is_newHome")]
+    is_new_home: Option<bool>,
+}
+
+impl ZillowListing {
+    /// Maps a search card onto our model. Flags Zillow only shows when set
+    /// (pending, price cut, ...) are `Some(true)` or left unreported, never
+    /// `Some(false)`, so they don't contradict Realtor.
+    fn into_scraped(self) -> Result<ScrapedProperty, String> {
+        let required = |value: Option<String>, what: &str| {
+            value
+                .filter(|s| !s.is_empty())
+                .ok_or(format!("Missing or empty {what}"))
+        };
+        let source_listing_id = required(self.zpid, "zpid")?;
+        let address_line = required(self.address_street, "address line")?;
+        let city = required(self.address_city, "city")?;
+        let postal_code = required(self.address_zipcode, "postal code")?;
+
+        let info = self.hdp_data.and_then(|h| h.home_info);
+        let info = info.as_ref();
+        let home_status = info
+            .and_then(|i| i.home_status.clone())
+            .or(self.status_type)
+            .unwrap_or_default();
+        let sub_type = info.and_then(|i| i.listing_sub_type.as_ref());
+        let flag = |set: bool| set.then_some(true);
+
+        let status = match home_status.as_str() {
+            "FOR_SALE" | "PENDING" | "COMING_SOON" | "FORECLOSED" | "PRE_FORECLOSURE" => {
+                Some("for_sale".to_string())
+            }
+            "SOLD" | "RECENTLY_SOLD" => Some("sold".to_string()),
+            "FOR_RENT" => Some("for_rent".to_string()),
+            "" => None,
+            other => Some(other.to_lowercase()),
+        };
+        let price = info.and_then(|i| i.price).or(self.unformatted_price);
+        let sold = status.as_deref() == Some("sold");
+        let lot_sqft = info.and_then(|i| {
+            let value = i.lot_area_value?;
+            match i.lot_area_unit.as_deref() {
+                Some("acres") => Some((value * SQFT_PER_ACRE).round() as i64),
+                _ => Some(value.round() as i64),
+            }
+        });
+
+        Ok(ScrapedProperty {
+            source_name: ZILLOW_SITE.to_string(),
+            source_listing_id,
+            site: ZILLOW_SITE,
+            address_line,
+            city,
+            postal_code,
+            state_abbr: self.address_state,
+            county_name: None,
+            status,
+            list_price: if sold { None } else { price },
+            sold_price: if sold { price } else { None },
+            sold_date: None,
+            is_pending: flag(home_status == "PENDING"),
+            is_contingent: None,
+            is_new_listing: None,
+            is_foreclosure: flag(
+                matches!(home_status.as_str(), "FORECLOSED" | "PRE_FORECLOSURE")
+                    || sub_type.and_then(|s| s.is_foreclosure) == Some(true),
+            ),
+            is_price_reduced: flag(
+                info.and_then(|i| i.price_reduction.as_deref())
+                    .is_some_and(|r| !r.is_empty()),
+            ),
+            is_coming_soon: flag(
+                home_status == "COMING_SOON"
+                    || sub_type.and_then(|s| s.is_coming_soon) == Some(true),
+            ),
+            beds: info.and_then(|i| i.bedrooms).or(self.beds),
+            baths: info.and_then(|i| i.bathrooms).or(self.baths),
+            sqft: info.and_then(|i| i.living_area).or(self.area),
+            lot_sqft,
+            property_type: info.and_then(|i| i.home_type.as_deref()).map(property_type),
+            year_built: info.and_then(|i| i.year_built),
+            garage: None,
+            stories: None,
+            is_new_construction: flag(sub_type.and_then(|s| s.is_new_home) == Some(true)),
+            subdivision: None,
+            latitude: self.lat_long.as_ref().and_then(|l| l.latitude),
+            longitude: self.lat_long.as_ref().and_then(|l| l.longitude),
+            county_fips: None,
+            open_houses: None,
+        })
+    }
+}
+
+/// Zillow's `homeType` in Realtor's vocabulary.
+fn property_type(home_type: &str) -> String {
+    match home_type {
+        "SINGLE_FAMILY" => "single_family",
+        "CONDO" => "condos",
+        "TOWNHOUSE" => "townhomes",
+        "MULTI_FAMILY" => "multi_family",
+        "MANUFACTURED" => "mobile",
+        "LOT" => "land",
+        "APARTMENT" => "apartment",
+        other => return other.to_lowercase(),
+    }
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_page_maps_to_realtor_vocabulary() {
+        let html = std::fs::read_to_string("src/tests/fixtures/zillow/search_UT.html").unwrap();
+        let page = ZillowAdapter.parse_search_page(&html).unwrap();
+
+        assert_eq!(page.total, Some(4));
+        assert_eq!(page.listings.len(), 3);
+        assert_eq!(page.rejected.len(), 1);
+        assert_eq!(page.rejected[0].error, "Missing or empty postal code");
+
+        let main_st = &page.listings[0];
+        assert_eq!(main_st.source_listing_id, "12345678");
+        assert_eq!(main_st.site, "zillow");
+        assert_eq!(main_st.status.as_deref(), Some("for_sale"));
+        assert_eq!(main_st.list_price, Some(445000));
+        assert_eq!(main_st.lot_sqft, Some(6534));
+        assert_eq!(main_st.property_type.as_deref(), Some("single_family"));
+        assert_eq!(main_st.is_price_reduced, Some(true));
+        assert_eq!(main_st.is_pending, None);
+
+        let condo = &page.listings[1];
+        assert_eq!(condo.is_pending, Some(true));
+        assert_eq!(condo.baths, Some(1.5));
+        assert_eq!(condo.property_type.as_deref(), Some("condos"));
+
+        assert_eq!(page.listings[2].is_coming_soon, Some(true));
+        assert_eq!(page.listings[2].lot_sqft, Some(7405));
+    }
+}
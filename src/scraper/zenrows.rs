@@ -0,0 +1,270 @@
+// zenrows.rs
+use crate::scraper::blocking::detect_block;
+use crate::scraper::config::{ScraperConfig, ZenRowsConfig};
+use crate::scraper::fetcher::{header_pairs, FetchedPage, PageFetcher};
+use crate::scraper::ScraperError;
+use rand::Rng;
+use reqwest::blocking::Client;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+/// Proxy settings for one attempt.
+struct Strategy {
+    /// `None` uses the configured country.
+    proxy_country: Option<&'static str>,
+    /// Pin a fresh ZenRows session so the retry comes from a new IP.
+    new_session: bool,
+    /// Route through residential IPs even if the config doesn't.
+    premium_proxy: bool,
+    /// Wait before trying this step, on top of the normal backoff.
+    cooldown_secs: u64,
+}
+
+/// Each blocked response moves one step down; other failures retry at the
+/// current step, except a 404, which is returned straight away.
+const ESCALATION: &[Strategy] = &[
+    Strategy {
+        proxy_country: None,
+        new_session: false,
+        premium_proxy: false,
+        cooldown_secs: 0,
+    },
+    Strategy {
+        proxy_country: None,
+        new_session: true,
+        premium_proxy: false,
+        cooldown_secs: 30,
+    },
+    Strategy {
+        proxy_country: Some("ca"),
+        new_session: true,
+        premium_proxy: true,
+        cooldown_secs: 60,
+    },
+    Strategy {
+        proxy_country: None,
+        new_session: true,
+        premium_proxy: true,
+        cooldown_secs: 180,
+    },
+];
+
+/// Fetches pages through the ZenRows rendering proxy.
+pub struct ZenRowsFetcher {
+    client: Client,
+    api_key: String,
+    config: ZenRowsConfig,
+    blocked_attempts: AtomicU32,
+}
+
+impl ZenRowsFetcher {
+    pub fn new(api_key: String, config: &ScraperConfig) -> Result<Self, ScraperError> {
+        let client = Client::builder()
+            .user_agent(&config.user_agent)
+            .timeout(Duration::from_secs(config.zenrows.timeout_secs))
+            .build()
+            .map_err(|e| ScraperError::Network(e.to_string()))?;
+
+        Ok(Self {
+            client,
+            api_key,
+            config: config.zenrows.clone(),
+            blocked_attempts: AtomicU32::new(0),
+        })
+    }
+
+    /// Reads `ZENROWS_API_KEY` once, at construction time.
+    pub fn from_env(config: &ScraperConfig) -> Result<Self, ScraperError> {
+        let api_key = std::env::var("ZENROWS_API_KEY").map_err(|_| {
+            ScraperError::Config("ZENROWS_API_KEY environment variable not set".into())
+        })?;
+        Self::new(api_key, config)
+    }
+
+    pub fn fetch_html_via_zenrows(&self, url: &str) -> Result<FetchedPage, ScraperError> {
+        const JITTER_MAX_SECS: u64 = 2;
+        let max_attempts = self.config.max_attempts.max(1);
+
+        let mut last_err = None;
+        let mut step = 0;
+
+        for attempt in 1..=max_attempts {
+            let start = std::time::Instant::now();
+
+            match self.try_fetch_html_via_zenrows(url, &ESCALATION[step]) {
+                Ok(page) => {
+                    eprintln!(
+                        "✅ ZenRows success attempt {attempt} in {:?}",
+                        start.elapsed()
+                    );
+                    return Ok(page);
+                }
+                Err(e) => {
+                    eprintln!(
+                        "⚠️ ZenRows attempt {attempt} failed in {:?}: {e}",
+                        start.elapsed()
+                    );
+
+                    if matches!(e, ScraperError::NotFound(_)) {
+                        return Err(e);
+                    }
+                    if matches!(e, ScraperError::Blocked(_)) {
+                        self.blocked_attempts.fetch_add(1, Ordering::Relaxed);
+                        step = (step + 1).min(ESCALATION.len() - 1);
+                        eprintln!(
+                            "🧱 Blocked, escalating to proxy_country={} new_session={} premium_proxy={}",
+                            self.proxy_country(&ESCALATION[step]),
+                            ESCALATION[step].new_session,
+                            self.premium_proxy(&ESCALATION[step])
+                        );
+                    }
+                    last_err = Some(e);
+
+                    if attempt == max_attempts {
+                        break;
+                    }
+
+                    // backoff
+                    let base = std::cmp::min(2 * attempt, self.config.max_backoff_secs);
+                    let jitter = rand::thread_rng().gen_range(0..=JITTER_MAX_SECS);
+                    let cooldown = ESCALATION[step].cooldown_secs;
+                    std::thread::sleep(Duration::from_secs(base + jitter + cooldown));
+                }
+            }
+        }
+
+        Err(last_err.unwrap_or_else(|| ScraperError::Network("ZenRows retry loop failed".into())))
+    }
+
+    fn proxy_country<'a>(&'a self, strategy: &Strategy) -> &'a str {
+        strategy.proxy_country.unwrap_or(&self.config.proxy_country)
+    }
+
+    fn premium_proxy(&self, strategy: &Strategy) -> bool {
+        strategy.premium_proxy || self.config.premium_proxy
+    }
+
+    fn try_fetch_html_via_zenrows(
+        &self,
+        url: &str,
+        strategy: &Strategy,
+    ) -> Result<FetchedPage, ScraperError> {
+        use reqwest::header::{HeaderMap, HeaderValue, REFERER};
+
+        let mut headers = HeaderMap::new();
+        headers.insert(REFERER, HeaderValue::from_static("https://www.google.com/"));
+
+        let mut params = HashMap::new();
+        params.insert("url", url.to_string());
+        params.insert("apikey", self.api_key.clone());
+        params.insert("js_render", self.config.js_render.to_string());
+        params.insert("premium_proxy", self.premium_proxy(strategy).to_string());
+        params.insert("proxy_country", self.proxy_country(strategy).to_string());
+        if strategy.new_session {
+            let session_id = rand::thread_rng().gen_range(1..=99_999);
+            params.insert("session_id", session_id.to_string());
+        }
+        // params.insert("wait_for", "script#__NEXT_DATA__".to_string());
+        params.insert("original_status", "true".to_string());
+
+        let resp = self
+            .client
+            .get("https://api.zenrows.com/v1/")
+            .headers(headers)
+            .query(&params)
+            .send()
+            .map_err(|e| ScraperError::Network(e.to_string()))?;
+
+        // 1️⃣ ZenRows HTTP status
+        let status = resp.status();
+        let response_headers = header_pairs(resp.headers());
+
+        // 2️⃣ ORIGINAL STATUS
+        let original_status = response_headers
+            .iter()
+            .find(|(k, _)| k.to_ascii_lowercase().contains("original"))
+            .map(|(_, v)| v.clone())
+            .unwrap_or("<none>".to_string());
+
+        // 3️⃣ Now read the body
+        let text = resp
+            .text()
+            .map_err(|e| ScraperError::Network(e.to_string()))?;
+
+        // ZenRows' own errors (bad key, out of credits) are JSON with a code;
+        // those aren't the site blocking us.
+        if text.starts_with('{') {
+            if let Ok(json) = serde_json::from_str::<serde_json::Value>(&text) {
+                if json.get("code").is_some() {
+                    return Err(ScraperError::Network(format!(
+                        "ZenRows API error ({}) : {}",
+                        original_status, text
+                    )));
+                }
+            }
+        }
+
+        let page = FetchedPage {
+            url: url.to_string(),
+            status: status.as_u16(),
+            headers: response_headers,
+            body: text,
+        };
+        if let Some(reason) = detect_block(&page) {
+            return Err(ScraperError::Blocked(reason));
+        }
+
+        // With original_status the status is the site's own, so a 404 is a
+        // page that doesn't exist rather than a proxy failure.
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Err(ScraperError::NotFound(url.to_string()));
+        }
+        if !status.is_success() {
+            return Err(ScraperError::Network(format!(
+                "ZenRows HTTP {} ({}) : {}",
+                status, original_status, page.body
+            )));
+        }
+
+        Ok(page)
+    }
+}
+
+impl PageFetcher for ZenRowsFetcher {
+    fn fetch(&self, url: &str) -> Result<FetchedPage, ScraperError> {
+        self.fetch_html_via_zenrows(url)
+    }
+
+    fn name(&self) -> &'static str {
+        "zenrows"
+    }
+
+    fn take_blocked_attempts(&self) -> u32 {
+        self.blocked_attempts.swap(0, Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escalation_moves_onto_premium_proxies() {
+        let config = ScraperConfig {
+            zenrows: ZenRowsConfig {
+                premium_proxy: false,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let fetcher = ZenRowsFetcher::new("key".into(), &config).unwrap();
+
+        assert!(!fetcher.premium_proxy(&ESCALATION[0]));
+        assert!(fetcher.premium_proxy(ESCALATION.last().unwrap()));
+        // ZenRows only honours proxy_country on premium proxies.
+        for strategy in ESCALATION.iter().filter(|s| s.proxy_country.is_some()) {
+            assert!(fetcher.premium_proxy(strategy));
+        }
+    }
+}
@@ -1,9 +1,13 @@
 // src/spreadsheets/export_xlsx.rs
 
+use crate::db::cycles::CountyCycleSummary;
+use crate::db::history::PropertyAsOf;
+use crate::db::market_stats::MarketStatsRow;
 use crate::domain::changes::ChangeViewModel;
 use crate::domain::property::TrackedProperty;
 use crate::errors::ServerError;
 use crate::responses::{xlsx_response, ResultResp};
+use chrono::NaiveDateTime;
 use rust_xlsxwriter::{Workbook, XlsxError};
 
 /// This is a placeholder for the old export function. It is no longer used by the
@@ -18,8 +22,17 @@ pub fn export_listings_xlsx(_listings: &[TrackedProperty], _state: &str) -> Resu
 
 /// Creates a spreadsheet from a list of property change events.
 /// This is the primary export function for the application, designed to be
-/// easily filterable and sortable by users in Excel.
-pub fn export_changes_xlsx(events: &[ChangeViewModel], state: &str, year: i32) -> ResultResp {
+/// easily filterable and sortable by users in Excel. Each row links to the
+/// property's page under `base_url`. A second sheet sums up the state's
+/// listing cycles per county, and a third has the year's daily market stats.
+pub fn export_changes_xlsx(
+    events: &[ChangeViewModel],
+    counties: &[CountyCycleSummary],
+    market: &[MarketStatsRow],
+    state: &str,
+    year: i32,
+    base_url: &str,
+) -> ResultResp {
     let mut workbook = Workbook::new();
     let worksheet = workbook.add_worksheet();
 
@@ -43,7 +56,27 @@ pub fn export_changes_xlsx(events: &[ChangeViewModel], state: &str, year: i32) -
         "Price Reduced Flag?",
         "Foreclosure?",
         "Ready to Build?",
-        // Note: Beds and SqFt are no longer tracked in the simplified schema.
+        "Property Type",
+        "Beds",
+        "Baths",
+        "SqFt",
+        "Lot SqFt",
+        "Year Built",
+        "Garage",
+        "Stories",
+        "Subdivision",
+        "New Construction?",
+        "Latitude",
+        "Longitude",
+        "County FIPS",
+        "Reported By",
+        "Listing Agent",
+        "Brokerage",
+        "Property Page",
+        "Listing Cycle",
+        "Days on Market",
+        "Days to Pending",
+        "Price Cuts",
     ];
 
     // Write headers to the first row.
@@ -57,7 +90,7 @@ pub fn export_changes_xlsx(events: &[ChangeViewModel], state: &str, year: i32) -
 
         worksheet.write_string(row, 0, &event.change_date.format("%Y-%m-%d").to_string())?;
         worksheet.write_string(row, 1, &event.change_date.format("%H:%M:%S").to_string())?;
-        worksheet.write_string(row, 2, &event.change_type)?;
+        worksheet.write_string(row, 2, event.kind.label())?;
         worksheet.write_string(row, 3, &event.previous_value)?;
         worksheet.write_string(row, 4, &event.current_value)?;
         worksheet.write_string(row, 5, &event.address_full)?;
@@ -80,6 +113,154 @@ pub fn export_changes_xlsx(events: &[ChangeViewModel], state: &str, year: i32) -
         worksheet.write_string(row, 15, if event.is_price_reduced { "Yes" } else { "No" })?;
         worksheet.write_string(row, 16, if event.is_foreclosure { "Yes" } else { "No" })?;
         worksheet.write_string(row, 17, if event.is_ready_to_build { "Yes" } else { "No" })?;
+
+        // Numeric attributes are written as numbers so Excel filters like
+        // "Beds >= 3" work; missing values are left blank.
+        worksheet.write_string(row, 18, event.property_type.as_deref().unwrap_or(""))?;
+        let numbers = [
+            (19, event.beds),
+            (21, event.sqft),
+            (22, event.lot_sqft),
+            (23, event.year_built),
+            (24, event.garage),
+            (25, event.stories),
+        ];
+        for (col, value) in numbers {
+            if let Some(v) = value {
+                worksheet.write_number(row, col, v as f64)?;
+            }
+        }
+        if let Some(baths) = event.baths {
+            worksheet.write_number(row, 20, baths)?;
+        }
+        worksheet.write_string(row, 26, event.subdivision.as_deref().unwrap_or(""))?;
+        worksheet.write_string(
+            row,
+            27,
+            if event.is_new_construction {
+                "Yes"
+            } else {
+                "No"
+            },
+        )?;
+        if let Some(lat) = event.latitude {
+            worksheet.write_number(row, 28, lat)?;
+        }
+        if let Some(lon) = event.longitude {
+            worksheet.write_number(row, 29, lon)?;
+        }
+        if let Some(fips) = event.county_fips {
+            worksheet.write_number(row, 30, fips as f64)?;
+        }
+        worksheet.write_string(row, 31, &event.reported_by)?;
+        worksheet.write_string(row, 32, event.listing_agent.as_deref().unwrap_or(""))?;
+        worksheet.write_string(row, 33, event.brokerage.as_deref().unwrap_or(""))?;
+        worksheet.write_url(
+            row,
+            34,
+            format!("{}/properties/{}", base_url, event.property_id).as_str(),
+        )?;
+        let cycle_numbers = [
+            (35, event.listing_cycle),
+            (36, event.days_on_market),
+            (37, event.days_to_pending),
+            (38, event.price_cuts),
+        ];
+        for (col, value) in cycle_numbers {
+            if let Some(v) = value {
+                worksheet.write_number(row, col, v as f64)?;
+            }
+        }
+    }
+
+    let summary = workbook.add_worksheet();
+    summary.set_name("County Summary")?;
+    let summary_headers = [
+        "County",
+        "Listing Cycles",
+        "On Market",
+        "Sold",
+        "Delisted",
+        "Avg Days on Market",
+        "Avg Days to Pending",
+        "Avg Price Cuts",
+        "Cycles With Price Cuts",
+    ];
+    for (col, header) in summary_headers.iter().enumerate() {
+        summary.write_string(0, col as u16, *header)?;
+    }
+    for (i, county) in counties.iter().enumerate() {
+        let row = (i + 1) as u32;
+        summary.write_string(row, 0, &county.county_name)?;
+        let counts = [
+            (1, county.cycles),
+            (2, county.open),
+            (3, county.sold),
+            (4, county.delisted),
+            (8, county.with_price_cuts),
+        ];
+        for (col, value) in counts {
+            summary.write_number(row, col, value as f64)?;
+        }
+        let averages = [
+            (5, county.avg_days_on_market),
+            (6, county.avg_days_to_pending),
+            (7, county.avg_price_cuts),
+        ];
+        for (col, value) in averages {
+            if let Some(v) = value {
+                summary.write_number(row, col, (v * 10.0).round() / 10.0)?;
+            }
+        }
+    }
+
+    let stats_sheet = workbook.add_worksheet();
+    stats_sheet.set_name("Market Stats")?;
+    let stats_headers = [
+        "Date",
+        "State",
+        "County",
+        "ZIP",
+        "Active Inventory",
+        "New Listings",
+        "Pendings",
+        "Solds",
+        "Median List Price",
+        "Median Sold Price",
+        "Median Days on Market",
+        "Price Cut Share",
+        "Sale-to-List Ratio",
+    ];
+    for (col, header) in stats_headers.iter().enumerate() {
+        stats_sheet.write_string(0, col as u16, *header)?;
+    }
+    for (i, day) in market.iter().enumerate() {
+        let row = (i + 1) as u32;
+        let stats = &day.stats;
+        stats_sheet.write_string(row, 0, day.stat_date.format("%Y-%m-%d").to_string())?;
+        stats_sheet.write_string(row, 1, &day.state_abbr)?;
+        stats_sheet.write_string(row, 2, &day.county_name)?;
+        stats_sheet.write_string(row, 3, &day.postal_code)?;
+        let counts = [
+            (4, Some(stats.active_inventory)),
+            (5, Some(stats.new_listings)),
+            (6, Some(stats.pendings)),
+            (7, Some(stats.solds)),
+            (8, stats.median_list_price),
+            (9, stats.median_sold_price),
+            (10, stats.median_days_on_market),
+        ];
+        for (col, value) in counts {
+            if let Some(v) = value {
+                stats_sheet.write_number(row, col, v as f64)?;
+            }
+        }
+        let ratios = [(11, stats.price_cut_share), (12, stats.sale_to_list_ratio)];
+        for (col, value) in ratios {
+            if let Some(v) = value {
+                stats_sheet.write_number(row, col, (v * 1000.0).round() / 1000.0)?;
+            }
+        }
     }
 
     let buffer = workbook.save_to_buffer()?;
@@ -87,3 +268,133 @@ pub fn export_changes_xlsx(events: &[ChangeViewModel], state: &str, year: i32) -
     let filename = format!("changes_{}_{}.xlsx", state, year);
     Ok(xlsx_response(buffer, &filename)?)
 }
+
+/// Creates a spreadsheet of every property in a state as it stood at
+/// `as_of`, one row per property.
+pub fn export_as_of_xlsx(
+    properties: &[PropertyAsOf],
+    state: &str,
+    as_of: NaiveDateTime,
+) -> ResultResp {
+    let mut workbook = Workbook::new();
+    let worksheet = workbook.add_worksheet();
+
+    let headers = [
+        "As Of",
+        "Address Line",
+        "City",
+        "State",
+        "Zip",
+        "County",
+        "Canonical Status",
+        "Status",
+        "List Price",
+        "Sold Price",
+        "Sold Date",
+        "Pending?",
+        "Contingent?",
+        "New Listing?",
+        "Price Reduced Flag?",
+        "Foreclosure?",
+        "Coming Soon?",
+        "Property Type",
+        "Beds",
+        "Baths",
+        "SqFt",
+        "Lot SqFt",
+        "Year Built",
+        "Garage",
+        "Stories",
+        "New Construction?",
+        "First Seen",
+        "Last Changed",
+    ];
+    for (col, header) in headers.iter().enumerate() {
+        worksheet.write_string(0, col as u16, *header)?;
+    }
+
+    // Flags the history never recorded are left blank rather than "No".
+    let flag = |value: Option<bool>| match value {
+        Some(true) => "Yes",
+        Some(false) => "No",
+        None => "",
+    };
+    let as_of_label = as_of.format("%Y-%m-%d %H:%M:%S").to_string();
+
+    for (i, entry) in properties.iter().enumerate() {
+        let row = (i + 1) as u32;
+        let property = &entry.snapshot.property;
+
+        worksheet.write_string(row, 0, &as_of_label)?;
+        worksheet.write_string(row, 1, &entry.address_line)?;
+        worksheet.write_string(row, 2, &entry.city)?;
+        worksheet.write_string(row, 3, entry.state_abbr.as_deref().unwrap_or(""))?;
+        worksheet.write_string(row, 4, &entry.postal_code)?;
+        worksheet.write_string(row, 5, entry.county_name.as_deref().unwrap_or(""))?;
+        worksheet.write_string(row, 6, entry.snapshot.listing_state().canonical_status())?;
+        worksheet.write_string(row, 7, property.status.as_deref().unwrap_or(""))?;
+        if let Some(price) = property.list_price {
+            worksheet.write_number(row, 8, price as f64)?;
+        }
+        if let Some(price) = property.sold_price {
+            worksheet.write_number(row, 9, price as f64)?;
+        }
+        if let Some(date) = property.sold_date {
+            worksheet.write_string(row, 10, date.format("%Y-%m-%d").to_string())?;
+        }
+
+        let flags = [
+            (11, property.is_pending),
+            (12, property.is_contingent),
+            (13, property.is_new_listing),
+            (14, property.is_price_reduced),
+            (15, property.is_foreclosure),
+            (16, property.is_coming_soon),
+        ];
+        for (col, value) in flags {
+            worksheet.write_string(row, col, flag(value))?;
+        }
+
+        worksheet.write_string(row, 17, property.property_type.as_deref().unwrap_or(""))?;
+        let numbers = [
+            (18, property.beds),
+            (20, property.sqft),
+            (21, property.lot_sqft),
+            (22, property.year_built),
+            (23, property.garage),
+            (24, property.stories),
+        ];
+        for (col, value) in numbers {
+            if let Some(v) = value {
+                worksheet.write_number(row, col, v as f64)?;
+            }
+        }
+        if let Some(baths) = property.baths {
+            worksheet.write_number(row, 19, baths)?;
+        }
+        worksheet.write_string(row, 25, flag(property.is_new_construction))?;
+        worksheet.write_string(
+            row,
+            26,
+            entry.snapshot.first_seen_at.format("%Y-%m-%d").to_string(),
+        )?;
+        worksheet.write_string(
+            row,
+            27,
+            entry
+                .snapshot
+                .last_changed_at
+                .format("%Y-%m-%d")
+                .to_string(),
+        )?;
+    }
+
+    let buffer = workbook.save_to_buffer()?;
+
+    let filename = format!(
+        "properties_{}_as_of_{}.xlsx",
+        state,
+        as_of.format("%Y-%m-%d")
+    );
+    xlsx_response(buffer, &filename)
+}
@@ -1,3 +1,4 @@
 pub mod export_xlsx;
+pub use export_xlsx::export_as_of_xlsx;
 pub use export_xlsx::export_changes_xlsx;
 pub use export_xlsx::export_listings_xlsx;
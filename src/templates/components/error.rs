@@ -8,6 +8,8 @@ pub fn html_error_response(err: ServerError) -> Response {
 
         ServerError::BadRequest(msg) => render_error(400, &msg),
 
+        ServerError::Conflict(msg) => render_error(409, &msg),
+
         ServerError::Unauthorized(msg) => render_error(401, &msg),
 
         ServerError::DbError(msg) => render_error(500, &format!("Database Error: {msg}")),
@@ -39,6 +39,8 @@ pub fn desktop_layout(title: &str, is_admin: bool, content: Markup) -> Markup {
                   nav {
                       ul {
                           li { a href="/" { "Home" } }
+                          li { a href="/calendar" { "Open Houses" } }
+                          li { a href="/market" { "Market Trends" } }
                           @if is_admin {
                               li { a href="/admin" { "Admin" } }
                               li { a href="/campaigns" { "Campaigns" } }
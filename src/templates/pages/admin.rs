@@ -5,6 +5,129 @@ pub struct AdminVm {
     pub users: Vec<crate::db::users::UserWithStats>,
     pub plans: Vec<crate::db::plans::PlanInfo>,
     pub scrapes: Vec<crate::db::scrapes::ScrapeRun>,
+    pub schedules: Vec<crate::db::schedules::ScrapeSchedule>,
+    pub jobs: Vec<crate::scraper::jobs::JobSnapshot>,
+    /// Unresolved rows in `scrape_quarantine`.
+    pub quarantined: i64,
+    /// Open pairs in `property_duplicate_candidates`.
+    pub duplicates: i64,
+    /// Whether the Census county / ZCTA files are loaded; county and ZIP
+    /// shards are unavailable without them.
+    pub geo_data_loaded: bool,
+}
+
+const CADENCES: &[(i64, &str)] = &[
+    (6, "Every 6 hours"),
+    (12, "Every 12 hours"),
+    (24, "Daily"),
+    (72, "Every 3 days"),
+    (168, "Weekly"),
+];
+
+fn cadence_label(hours: i64) -> String {
+    CADENCES
+        .iter()
+        .find(|(h, _)| *h == hours)
+        .map(|(_, label)| label.to_string())
+        .unwrap_or_else(|| format!("Every {hours} hours"))
+}
+
+/// Live job list, polled by the admin page every few seconds.
+pub fn jobs_table(jobs: &[crate::scraper::jobs::JobSnapshot]) -> Markup {
+    html! {
+        @if jobs.is_empty() {
+            p style="color: #6b7280; font-style: italic; margin: 0;" { "No scrapes running." }
+        } @else {
+            table style="width: 100%; border-collapse: collapse; font-size: 0.9em;" {
+                thead {
+                    tr {
+                        th style="padding: 8px; text-align: left; border-bottom: 2px solid #eee;" { "State" }
+                        th style="padding: 8px; text-align: left; border-bottom: 2px solid #eee;" { "Run" }
+                        th style="padding: 8px; text-align: left; border-bottom: 2px solid #eee;" { "Page" }
+                        th style="padding: 8px; text-align: left; border-bottom: 2px solid #eee;" { "Saved" }
+                        th style="padding: 8px; text-align: left; border-bottom: 2px solid #eee;" { "Started" }
+                        th style="padding: 8px; text-align: left; border-bottom: 2px solid #eee;" { "Last Error" }
+                        th style="padding: 8px; text-align: left; border-bottom: 2px solid #eee;" { "" }
+                    }
+                }
+                tbody {
+                    @for job in jobs {
+                        tr {
+                            td style="padding: 8px; border-bottom: 1px solid #f9f9f9;" { (job.state) }
+                            td style="padding: 8px; border-bottom: 1px solid #f9f9f9;" {
+                                @if let Some(run_id) = job.run_id { (run_id) " " }
+                                span style="font-size: 0.8em; color: #666;" { (job.shard.as_deref().unwrap_or("")) }
+                            }
+                            td style="padding: 8px; border-bottom: 1px solid #f9f9f9;" { (job.page) }
+                            td style="padding: 8px; border-bottom: 1px solid #f9f9f9;" { (job.properties_saved) }
+                            td style="padding: 8px; border-bottom: 1px solid #f9f9f9; color: #6b7280;" { (format_ts(job.started_at)) }
+                            td style="padding: 8px; border-bottom: 1px solid #f9f9f9; font-size: 0.8em; color: #dc2626;" { (job.last_error.as_deref().unwrap_or("")) }
+                            td style="padding: 8px; border-bottom: 1px solid #f9f9f9;" {
+                                @if job.cancel_requested {
+                                    span style="color: #6b7280; font-style: italic;" { "Stopping..." }
+                                } @else {
+                                    form action=(format!("/admin/jobs/{}/cancel", job.id)) method="post" onsubmit="return confirm('Stop this scrape after the current page?');" style="margin: 0;" {
+                                        button type="submit" style="padding: 4px 8px; background: #dc2626; color: white; border: none; border-radius: 4px; cursor: pointer;" { "Cancel" }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn format_ts(ts: i64) -> String {
+    chrono::DateTime::from_timestamp(ts, 0)
+        .map(|dt| dt.format("%Y-%m-%d %H:%M UTC").to_string())
+        .unwrap_or_else(|| ts.to_string())
+}
+
+/// State / split / kind / mode / cadence inputs shared by the create and edit forms.
+fn schedule_fields(
+    state: Option<&str>,
+    shard_by: &str,
+    search_kind: &str,
+    mode: &str,
+    stop_after: Option<i64>,
+    cadence_hours: i64,
+    geo_data_loaded: bool,
+) -> Markup {
+    html! {
+        select name="state" required style="padding: 6px; border-radius: 4px; border: 1px solid #ccc;" {
+            @if state.is_none() {
+                option value="" disabled selected { "State..." }
+            }
+            @for (abbr, name) in crate::geos::US_STATES {
+                option value=(abbr) selected[state == Some(*abbr)] { (name) }
+            }
+        }
+        select name="shard_by" style="padding: 6px; border-radius: 4px; border: 1px solid #ccc;" {
+            option value="state" selected[shard_by == "state"] { "Whole state" }
+            option value="county" selected[shard_by == "county"] disabled[!geo_data_loaded && shard_by != "county"] { "By county" }
+            option value="zip" selected[shard_by == "zip"] disabled[!geo_data_loaded && shard_by != "zip"] { "By ZIP" }
+        }
+        select name="search_kind" style="padding: 6px; border-radius: 4px; border: 1px solid #ccc;" {
+            option value="for_sale" selected[search_kind == "for_sale"] { "For sale" }
+            option value="sold" selected[search_kind == "sold"] { "Recently sold" }
+            option value="for_rent" selected[search_kind == "for_rent"] { "For rent" }
+        }
+        select name="mode" style="padding: 6px; border-radius: 4px; border: 1px solid #ccc;" {
+            option value="full" selected[mode == "full"] { "Full sweep" }
+            option value="incremental" selected[mode == "incremental"] { "Incremental" }
+        }
+        input type="number" name="stop_after" min="1" placeholder="stop after" value=[stop_after] style="padding: 6px; width: 90px; border: 1px solid #ccc; border-radius: 4px;";
+        select name="cadence_hours" style="padding: 6px; border-radius: 4px; border: 1px solid #ccc;" {
+            @for (hours, label) in CADENCES {
+                option value=(hours) selected[*hours == cadence_hours] { (label) }
+            }
+            @if !CADENCES.iter().any(|(h, _)| *h == cadence_hours) {
+                option value=(cadence_hours) selected { (cadence_label(cadence_hours)) }
+            }
+        }
+    }
 }
 
 pub fn admin_page(vm: &AdminVm) -> Markup {
@@ -64,20 +187,138 @@ pub fn admin_page(vm: &AdminVm) -> Markup {
                                 option value=(abbr) { (name) }
                             }
                         }
+                        select name="shard_by" style="padding: 8px; border-radius: 4px; border: 1px solid #ccc;" {
+                            option value="state" selected { "Whole state" }
+                            option value="county" disabled[!vm.geo_data_loaded] { "Split by county" }
+                            option value="zip" disabled[!vm.geo_data_loaded] { "Split by ZIP" }
+                        }
+                        select name="search_kind" style="padding: 8px; border-radius: 4px; border: 1px solid #ccc;" {
+                            option value="for_sale" selected { "For sale" }
+                            option value="sold" { "Recently sold" }
+                            option value="for_rent" { "For rent" }
+                        }
+                        select name="mode" style="padding: 8px; border-radius: 4px; border: 1px solid #ccc;" {
+                            option value="full" selected { "Full sweep" }
+                            option value="incremental" { "Incremental (newest first)" }
+                        }
+                        label style="display: flex; gap: 6px; align-items: center; font-size: 0.9em;" {
+                            "Stop after"
+                            input type="number" name="stop_after" min="1" value=(crate::scraper::scraper::ScrapeMode::DEFAULT_STOP_AFTER) style="padding: 6px; width: 70px; border: 1px solid #ccc; border-radius: 4px;";
+                            "known"
+                        }
+                        details style="font-size: 0.9em;" {
+                            summary style="cursor: pointer;" { "Run settings" }
+                            div style="display: flex; gap: 8px; align-items: center; flex-wrap: wrap; margin-top: 6px;" {
+                                label { "Page cap " input type="number" name="page_cap" min="1" placeholder="default" style="padding: 6px; width: 80px; border: 1px solid #ccc; border-radius: 4px;"; }
+                                label { "Delay (s) " input type="number" name="page_delay_secs" min="0" placeholder="default" style="padding: 6px; width: 70px; border: 1px solid #ccc; border-radius: 4px;"; }
+                                label { "Failures " input type="number" name="max_page_failures" min="1" placeholder="default" style="padding: 6px; width: 70px; border: 1px solid #ccc; border-radius: 4px;"; }
+                                label { "Proxy " input type="text" name="proxy_country" maxlength="2" placeholder="us" style="padding: 6px; width: 50px; border: 1px solid #ccc; border-radius: 4px;"; }
+                                select name="enrich_details" style="padding: 6px; border-radius: 4px; border: 1px solid #ccc;" {
+                                    option value="" selected { "Detail pages: default" }
+                                    option value="true" { "Fetch detail pages" }
+                                    option value="false" { "Skip detail pages" }
+                                }
+                            }
+                        }
                         button type="submit" style="padding: 8px 16px; background: #10b981; color: white; border: none; border-radius: 4px; cursor: pointer;" { "Start Scrape Job" }
                     }
+                    @if !vm.geo_data_loaded {
+                        p style="color: #b45309; font-size: 0.9em; margin: -0.5rem 0 1rem;" {
+                            "County and ZIP shards need the Census reference files; set GEO_DATA_DIR (see README) and restart."
+                        }
+                    }
+
+                    h4 { "Running Jobs" }
+                    div hx-get="/admin/jobs" hx-trigger="load, every 3s" hx-swap="innerHTML" style="margin-bottom: 1.5rem;" {
+                        (jobs_table(&vm.jobs))
+                    }
+
+                    h4 { "Schedules" }
+                    form action="/admin/schedules" method="post" style="display: flex; gap: 8px; align-items: center; flex-wrap: wrap; margin-bottom: 1rem;" {
+                        (schedule_fields(None, "state", "for_sale", "full", None, 24, vm.geo_data_loaded))
+                        button type="submit" style="padding: 6px 12px; background: #3b82f6; color: white; border: none; border-radius: 4px; cursor: pointer;" { "Add Schedule" }
+                    }
+                    @if !vm.schedules.is_empty() {
+                        div style="overflow-x: auto; margin-bottom: 1.5rem;" {
+                            table style="width: 100%; border-collapse: collapse; font-size: 0.9em;" {
+                                thead {
+                                    tr {
+                                        th style="padding: 8px; text-align: left; border-bottom: 2px solid #eee;" { "Schedule" }
+                                        th style="padding: 8px; text-align: left; border-bottom: 2px solid #eee;" { "Last Run" }
+                                        th style="padding: 8px; text-align: left; border-bottom: 2px solid #eee;" { "Next Run" }
+                                        th style="padding: 8px; text-align: left; border-bottom: 2px solid #eee;" { "Actions" }
+                                    }
+                                }
+                                tbody {
+                                    @for schedule in &vm.schedules {
+                                        tr {
+                                            td style="padding: 8px; border-bottom: 1px solid #f9f9f9;" {
+                                                form action=(format!("/admin/schedules/{}", schedule.id)) method="post" style="display: flex; gap: 6px; align-items: center; flex-wrap: wrap; margin: 0;" {
+                                                    (schedule_fields(Some(&schedule.state), &schedule.shard_by, &schedule.search_kind, &schedule.mode, schedule.stop_after, schedule.cadence_hours, vm.geo_data_loaded))
+                                                    button type="submit" style="padding: 4px 8px; background: #3b82f6; color: white; border: none; border-radius: 4px; cursor: pointer;" { "Save" }
+                                                }
+                                            }
+                                            td style="padding: 8px; border-bottom: 1px solid #f9f9f9; color: #6b7280;" {
+                                                @match schedule.last_run_at {
+                                                    Some(ts) => (format_ts(ts)),
+                                                    None => "Never",
+                                                }
+                                                @if let (Some(error), Some(ts)) = (&schedule.last_error, schedule.last_attempt_at) {
+                                                    div style="color: #b91c1c; font-size: 0.85em; margin-top: 4px;" {
+                                                        "Failed to start " (format_ts(ts)) ": " (error)
+                                                    }
+                                                }
+                                            }
+                                            td style="padding: 8px; border-bottom: 1px solid #f9f9f9;" {
+                                                @if schedule.enabled {
+                                                    (format_ts(schedule.next_run_at))
+                                                } @else {
+                                                    span style="color: #6b7280; font-style: italic;" { "Paused" }
+                                                }
+                                            }
+                                            td style="padding: 8px; border-bottom: 1px solid #f9f9f9;" {
+                                                form action=(format!("/admin/schedules/{}/toggle", schedule.id)) method="post" style="margin: 0;" {
+                                                    @if schedule.enabled {
+                                                        button type="submit" style="padding: 4px 8px; background: #f59e0b; color: white; border: none; border-radius: 4px; cursor: pointer;" { "Pause" }
+                                                    } @else {
+                                                        button type="submit" style="padding: 4px 8px; background: #10b981; color: white; border: none; border-radius: 4px; cursor: pointer;" { "Enable" }
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
 
                     h4 { "Recent Runs" }
+                    @if vm.quarantined > 0 {
+                        p style="margin: 0 0 8px; color: #b45309;" {
+                            (vm.quarantined) " listings in quarantine. "
+                            a href="/admin/quarantine" { "Review" }
+                        }
+                    }
+                    @if vm.duplicates > 0 {
+                        p style="margin: 0 0 8px; color: #b45309;" {
+                            (vm.duplicates) " possible duplicate properties. "
+                            a href="/admin/duplicates" { "Review" }
+                        }
+                    }
                     div style="overflow-x: auto;" {
                         table style="width: 100%; border-collapse: collapse; font-size: 0.9em;" {
                             thead {
                                 tr {
                                     th style="padding: 8px; text-align: left; border-bottom: 2px solid #eee;" { "ID" }
                                     th style="padding: 8px; text-align: left; border-bottom: 2px solid #eee;" { "State" }
+                                    th style="padding: 8px; text-align: left; border-bottom: 2px solid #eee;" { "Shard" }
                                     th style="padding: 8px; text-align: left; border-bottom: 2px solid #eee;" { "Started" }
                                     th style="padding: 8px; text-align: left; border-bottom: 2px solid #eee;" { "Status" }
                                     th style="padding: 8px; text-align: left; border-bottom: 2px solid #eee;" { "Pages" }
                                     th style="padding: 8px; text-align: left; border-bottom: 2px solid #eee;" { "Found" }
+                                    th style="padding: 8px; text-align: left; border-bottom: 2px solid #eee;" { "Coverage" }
+                                    th style="padding: 8px; text-align: left; border-bottom: 2px solid #eee;" { "Blocked" }
+                                    th style="padding: 8px; text-align: left; border-bottom: 2px solid #eee;" { "Actions" }
                                 }
                             }
                             tbody {
@@ -85,6 +326,23 @@ pub fn admin_page(vm: &AdminVm) -> Markup {
                                     tr {
                                         td style="padding: 8px; border-bottom: 1px solid #f9f9f9;" { (run.id) }
                                         td style="padding: 8px; border-bottom: 1px solid #f9f9f9;" { (run.state) }
+                                        td style="padding: 8px; border-bottom: 1px solid #f9f9f9;" {
+                                            (run.shard.as_deref().unwrap_or("—"))
+                                            @if run.mode.as_deref() == Some("incremental") {
+                                                br; span style="font-size: 0.8em; color: #666;" { "incremental" }
+                                            }
+                                            @match run.search_kind.as_deref() {
+                                                Some("sold") => { br; span style="font-size: 0.8em; color: #666;" { "recently sold" } }
+                                                Some("for_rent") => { br; span style="font-size: 0.8em; color: #666;" { "rentals" } }
+                                                _ => {}
+                                            }
+                                            @if let Some(parent) = run.parent_run_id {
+                                                br; span style="font-size: 0.8em; color: #666;" { "from run " (parent) }
+                                            }
+                                            @if let Some(config) = &run.config {
+                                                br; span title=(config) style="font-size: 0.8em; color: #666; cursor: help;" { "config" }
+                                            }
+                                        }
                                         td style="padding: 8px; border-bottom: 1px solid #f9f9f9;" { (run.started_at) }
                                         td style="padding: 8px; border-bottom: 1px solid #f9f9f9;" {
                                             @if run.finished_at.is_none() {
@@ -100,6 +358,31 @@ pub fn admin_page(vm: &AdminVm) -> Markup {
                                         }
                                         td style="padding: 8px; border-bottom: 1px solid #f9f9f9;" { (run.pages_fetched.unwrap_or(0)) }
                                         td style="padding: 8px; border-bottom: 1px solid #f9f9f9;" { (run.properties_seen.unwrap_or(0)) }
+                                        td style="padding: 8px; border-bottom: 1px solid #f9f9f9;" {
+                                            @match (run.coverage, run.expected_total) {
+                                                (Some(c), Some(t)) => { (format!("{:.0}% of {}", c * 100.0, t)) }
+                                                _ => "—",
+                                            }
+                                            @if let Some(n) = run.split_into {
+                                                br; span style="font-size: 0.8em; color: #666;" { "split into " (n) " price bands" }
+                                            }
+                                        }
+                                        td style="padding: 8px; border-bottom: 1px solid #f9f9f9;" {
+                                            @match (run.blocked_attempts, run.block_rate) {
+                                                (Some(n), Some(rate)) if n > 0 => {
+                                                    span style="color: #b45309;" { (n) " (" (format!("{:.0}%", rate * 100.0)) ")" }
+                                                }
+                                                (Some(_), _) => "0",
+                                                _ => "—",
+                                            }
+                                        }
+                                        td style="padding: 8px; border-bottom: 1px solid #f9f9f9;" {
+                                            @if run.finished_at.is_some() && run.success != Some(true) {
+                                                form action=(format!("/admin/scrapes/{}/resume", run.id)) method="post" style="margin: 0;" {
+                                                    button type="submit" style="padding: 4px 8px; background: #3b82f6; color: white; border: none; border-radius: 4px; cursor: pointer;" { "Resume" }
+                                                }
+                                            }
+                                        }
                                     }
                                 }
                             }
@@ -163,3 +446,197 @@ pub fn admin_page(vm: &AdminVm) -> Markup {
         },
     )
 }
+
+/// Listings that couldn't be saved, with the raw JSON and why.
+pub fn quarantine_page(records: &[crate::db::quarantine::QuarantinedRecord], open: i64) -> Markup {
+    desktop_layout(
+        "Quarantine",
+        true,
+        html! {
+            main class="container" {
+                p { a href="/admin" { "← Admin Dashboard" } }
+                h1 { "Quarantined Listings" }
+
+                div class="card" {
+                    div style="display: flex; justify-content: space-between; align-items: center;" {
+                        p style="margin: 0;" {
+                            (open) " unresolved"
+                            @if open > records.len() as i64 {
+                                " (showing the newest " (records.len()) ")"
+                            }
+                        }
+                        @if open > 0 {
+                            form action="/admin/quarantine/reprocess" method="post" style="margin: 0;" {
+                                button type="submit" style="padding: 6px 12px; background: #3b82f6; color: white; border: none; border-radius: 4px; cursor: pointer;" { "Re-process all" }
+                            }
+                        }
+                    }
+
+                    @if records.is_empty() {
+                        p style="color: #6b7280; font-style: italic;" { "Nothing in quarantine." }
+                    } @else {
+                        table style="width: 100%; border-collapse: collapse; margin-top: 1rem; font-size: 0.9em;" {
+                            thead {
+                                tr {
+                                    th style="padding: 8px; text-align: left; border-bottom: 2px solid #eee;" { "ID" }
+                                    th style="padding: 8px; text-align: left; border-bottom: 2px solid #eee;" { "Run / Page" }
+                                    th style="padding: 8px; text-align: left; border-bottom: 2px solid #eee;" { "Stage" }
+                                    th style="padding: 8px; text-align: left; border-bottom: 2px solid #eee;" { "Error" }
+                                    th style="padding: 8px; text-align: left; border-bottom: 2px solid #eee;" { "Raw" }
+                                    th style="padding: 8px; text-align: left; border-bottom: 2px solid #eee;" { "Quarantined" }
+                                    th style="padding: 8px; text-align: left; border-bottom: 2px solid #eee;" { "" }
+                                }
+                            }
+                            tbody {
+                                @for record in records {
+                                    tr style="vertical-align: top;" {
+                                        td style="padding: 8px; border-bottom: 1px solid #f9f9f9;" { (record.id) }
+                                        td style="padding: 8px; border-bottom: 1px solid #f9f9f9;" {
+                                            (record.scrape_run_id.map(|id| id.to_string()).unwrap_or_else(|| "—".into()))
+                                            " / "
+                                            (record.page_number.map(|p| p.to_string()).unwrap_or_else(|| "—".into()))
+                                        }
+                                        td style="padding: 8px; border-bottom: 1px solid #f9f9f9;" { (record.stage) }
+                                        td style="padding: 8px; border-bottom: 1px solid #f9f9f9; color: #dc2626;" { (record.error) }
+                                        td style="padding: 8px; border-bottom: 1px solid #f9f9f9; max-width: 420px;" {
+                                            details {
+                                                summary style="cursor: pointer;" { (record.raw_json.len()) " bytes" }
+                                                pre style="white-space: pre-wrap; word-break: break-all; font-size: 0.8em; max-height: 300px; overflow: auto;" { (record.raw_json) }
+                                            }
+                                        }
+                                        td style="padding: 8px; border-bottom: 1px solid #f9f9f9; color: #6b7280;" { (format_ts(record.created_at)) }
+                                        td style="padding: 8px; border-bottom: 1px solid #f9f9f9;" {
+                                            form action=(format!("/admin/quarantine/{}/reprocess", record.id)) method="post" style="margin: 0;" {
+                                                button type="submit" style="padding: 4px 8px; background: #3b82f6; color: white; border: none; border-radius: 4px; cursor: pointer;" { "Re-process" }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        },
+    )
+}
+
+fn candidate_side(side: &crate::db::duplicates::CandidateSide) -> Markup {
+    html! {
+        div { strong { (side.address) } }
+        div style="color: #6b7280; font-size: 0.9em;" {
+            "#" (side.id) " · "
+            (side.status.as_deref().unwrap_or("no status")) " · "
+            (side.list_price.map(|p| format!("${p}")).unwrap_or_else(|| "no price".into())) " · "
+            (side.sources) " source(s) · first seen " (side.first_seen_at)
+        }
+    }
+}
+
+/// Likely duplicate property pairs to merge or dismiss, plus the merge log.
+pub fn duplicates_page(
+    candidates: &[crate::db::duplicates::DuplicateCandidate],
+    open: i64,
+    merges: &[crate::db::duplicates::PropertyMerge],
+) -> Markup {
+    desktop_layout(
+        "Duplicates",
+        true,
+        html! {
+            main class="container" {
+                p { a href="/admin" { "← Admin Dashboard" } }
+                h1 { "Possible Duplicate Properties" }
+
+                div class="card" {
+                    div style="display: flex; justify-content: space-between; align-items: center;" {
+                        p style="margin: 0;" {
+                            (open) " open"
+                            @if open > candidates.len() as i64 {
+                                " (showing the top " (candidates.len()) ")"
+                            }
+                        }
+                        form action="/admin/duplicates/scan" method="post" style="margin: 0;" {
+                            button type="submit" style="padding: 6px 12px; background: #3b82f6; color: white; border: none; border-radius: 4px; cursor: pointer;" { "Scan now" }
+                        }
+                    }
+
+                    @if candidates.is_empty() {
+                        p style="color: #6b7280; font-style: italic;" { "No open candidates." }
+                    } @else {
+                        table style="width: 100%; border-collapse: collapse; margin-top: 1rem; font-size: 0.9em;" {
+                            thead {
+                                tr {
+                                    th style="padding: 8px; text-align: left; border-bottom: 2px solid #eee;" { "Property A" }
+                                    th style="padding: 8px; text-align: left; border-bottom: 2px solid #eee;" { "Property B" }
+                                    th style="padding: 8px; text-align: left; border-bottom: 2px solid #eee;" { "Why" }
+                                    th style="padding: 8px; text-align: left; border-bottom: 2px solid #eee;" { "" }
+                                }
+                            }
+                            tbody {
+                                @for c in candidates {
+                                    tr style="vertical-align: top;" {
+                                        td style="padding: 8px; border-bottom: 1px solid #f9f9f9;" { (candidate_side(&c.a)) }
+                                        td style="padding: 8px; border-bottom: 1px solid #f9f9f9;" { (candidate_side(&c.b)) }
+                                        td style="padding: 8px; border-bottom: 1px solid #f9f9f9;" {
+                                            (c.reasons)
+                                            div style="color: #6b7280;" { (format!("score {:.1}", c.score)) " · found " (format_ts(c.detected_at)) }
+                                        }
+                                        td style="padding: 8px; border-bottom: 1px solid #f9f9f9; white-space: nowrap;" {
+                                            form action=(format!("/admin/duplicates/{}/merge", c.id)) method="post" style="margin: 0 0 4px;" onsubmit="return confirm('Merge these properties? This cannot be undone.');" {
+                                                select name="keep" style="padding: 4px;" {
+                                                    option value=(c.a.id) { "Keep A (#" (c.a.id) ")" }
+                                                    option value=(c.b.id) { "Keep B (#" (c.b.id) ")" }
+                                                }
+                                                " "
+                                                button type="submit" style="padding: 4px 8px; background: #3b82f6; color: white; border: none; border-radius: 4px; cursor: pointer;" { "Merge" }
+                                            }
+                                            form action=(format!("/admin/duplicates/{}/dismiss", c.id)) method="post" style="margin: 0;" {
+                                                button type="submit" style="color: #6b7280; background: none; border: none; cursor: pointer; padding: 0;" { "Not a duplicate" }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+
+                div class="card" {
+                    h4 { "Recent Merges" }
+                    @if merges.is_empty() {
+                        p style="color: #6b7280; font-style: italic;" { "No merges yet." }
+                    } @else {
+                        table style="width: 100%; border-collapse: collapse; font-size: 0.9em;" {
+                            thead {
+                                tr {
+                                    th style="padding: 8px; text-align: left; border-bottom: 2px solid #eee;" { "When" }
+                                    th style="padding: 8px; text-align: left; border-bottom: 2px solid #eee;" { "Merged" }
+                                    th style="padding: 8px; text-align: left; border-bottom: 2px solid #eee;" { "Into" }
+                                    th style="padding: 8px; text-align: left; border-bottom: 2px solid #eee;" { "Moved" }
+                                    th style="padding: 8px; text-align: left; border-bottom: 2px solid #eee;" { "Why" }
+                                    th style="padding: 8px; text-align: left; border-bottom: 2px solid #eee;" { "By" }
+                                }
+                            }
+                            tbody {
+                                @for m in merges {
+                                    tr {
+                                        td style="padding: 8px; border-bottom: 1px solid #f9f9f9; color: #6b7280;" { (format_ts(m.merged_at)) }
+                                        td style="padding: 8px; border-bottom: 1px solid #f9f9f9;" { "#" (m.merged_property_id) " " (m.merged_address) }
+                                        td style="padding: 8px; border-bottom: 1px solid #f9f9f9;" { "#" (m.surviving_property_id) }
+                                        td style="padding: 8px; border-bottom: 1px solid #f9f9f9;" {
+                                            (m.history_moved) " history, " (m.sources_moved) " sources"
+                                        }
+                                        td style="padding: 8px; border-bottom: 1px solid #f9f9f9;" { (m.reasons.as_deref().unwrap_or("—")) }
+                                        td style="padding: 8px; border-bottom: 1px solid #f9f9f9;" {
+                                            (m.merged_by.map(|id| format!("user {id}")).unwrap_or_else(|| "—".into()))
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        },
+    )
+}
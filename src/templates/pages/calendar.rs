@@ -0,0 +1,91 @@
+// src/templates/pages/calendar.rs
+
+use crate::db::open_houses::OpenHouseEvent;
+use crate::templates::desktop_layout;
+use maud::{html, Markup};
+
+/// Upcoming open houses, and the form that issues a calendar subscription
+/// link. `feed_url` is set right after a link is made; it can't be shown again.
+pub fn calendar_page(upcoming: &[OpenHouseEvent], feed_url: Option<&str>) -> Markup {
+    desktop_layout(
+        "Open Houses",
+        true,
+        html! {
+            div class="mb-6" {
+                h1 class="text-3xl font-bold text-gray-800" { "Open Houses" }
+                p class="text-gray-500 mt-1" { "Subscribe from your calendar app to get open houses as they're listed." }
+            }
+
+            div class="bg-white border rounded-lg shadow-sm p-6 mb-8" {
+                h2 class="text-xl font-semibold text-gray-800 mb-4" { "Calendar Link" }
+                @if let Some(url) = feed_url {
+                    p class="text-sm text-gray-600 mb-2" {
+                        "Add this link to your calendar app as a subscription. Keep it private: anyone with it can see the feed. It won't be shown again; making a new link turns this one off."
+                    }
+                    input type="text" readonly value=(url) onclick="this.select()" class="w-full p-2 border border-gray-300 rounded-md font-mono text-sm mb-4";
+                }
+                form action="/calendar/feed" method="post" class="flex items-end space-x-4" {
+                    div {
+                        label for="state" class="block text-sm font-medium text-gray-700 mb-1" { "State" }
+                        select name="state" id="state" class="w-48 p-2 border border-gray-300 rounded-md shadow-sm" {
+                            option value="" selected { "Any state" }
+                            @for (abbr, name) in crate::geos::US_STATES {
+                                option value=(abbr) { (name) }
+                            }
+                        }
+                    }
+                    div {
+                        label for="county" class="block text-sm font-medium text-gray-700 mb-1" { "County" }
+                        input type="text" name="county" id="county" placeholder="e.g. Salt Lake" class="w-48 p-2 border border-gray-300 rounded-md shadow-sm";
+                    }
+                    div {
+                        label for="zip" class="block text-sm font-medium text-gray-700 mb-1" { "ZIP" }
+                        input type="text" name="zip" id="zip" placeholder="e.g. 84101" class="w-32 p-2 border border-gray-300 rounded-md shadow-sm";
+                    }
+                    div {
+                        button type="submit" class="px-5 py-2 bg-indigo-600 text-white font-semibold rounded-md shadow-sm hover:bg-indigo-700" {
+                            "Make Calendar Link"
+                        }
+                    }
+                }
+            }
+
+            h2 class="text-xl font-semibold text-gray-800 mb-4" { "Coming Up" }
+            @if upcoming.is_empty() {
+                p class="text-sm text-gray-500" { "No open houses scheduled." }
+            } @else {
+                div class="overflow-x-auto bg-white border rounded-lg shadow-sm" {
+                    table class="min-w-full divide-y divide-gray-200" {
+                        thead class="bg-gray-50" {
+                            tr {
+                                th class="px-6 py-3 text-left text-xs font-medium text-gray-500 uppercase tracking-wider" { "When" }
+                                th class="px-6 py-3 text-left text-xs font-medium text-gray-500 uppercase tracking-wider" { "Property" }
+                            }
+                        }
+                        tbody class="bg-white divide-y divide-gray-200" {
+                            @for event in upcoming {
+                                tr {
+                                    td class="px-6 py-4 whitespace-nowrap text-sm text-gray-700" {
+                                        (event.starts_at.format("%a %b %-d, %-I:%M %p"))
+                                        @if let Some(end) = event.ends_at {
+                                            " – " (end.format("%-I:%M %p"))
+                                        }
+                                        @if event.cancelled_at.is_some() {
+                                            span class="ml-2 text-xs font-semibold text-red-600" { "Cancelled" }
+                                        }
+                                    }
+                                    td class="px-6 py-4 whitespace-nowrap text-sm" {
+                                        div class="font-medium text-gray-900" { (event.address_line) }
+                                        div class="text-gray-500" {
+                                            (event.city) ", " (event.state_abbr.as_deref().unwrap_or("")) " " (event.postal_code)
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        },
+    )
+}
@@ -60,6 +60,34 @@ pub fn dashboard_page(changes: &[ChangeViewModel], years: &[String]) -> Markup {
                 }
             }
 
+            // --- As-Of Export Card ---
+            div class="bg-white border rounded-lg shadow-sm p-6 mb-8" {
+                h2 class="text-xl font-semibold text-gray-800 mb-4" { "Download Properties As Of a Date" }
+                p class="text-sm text-gray-600 mb-6" {
+                    "Every property in a state with its price, status and flags as they stood at the end of the chosen day."
+                }
+
+                form action="/export/as-of" method="get" class="flex items-end space-x-4" {
+                    div {
+                        label for="as-of-state" class="block text-sm font-medium text-gray-700 mb-1" { "State" }
+                        select name="state" id="as-of-state" required class="w-48 p-2 border border-gray-300 rounded-md shadow-sm focus:ring-indigo-500 focus:border-indigo-500" {
+                            option value="" disabled selected { "Select a State..." }
+                            @for (abbr, name) in crate::geos::US_STATES {
+                                option value=(abbr) { (name) }
+                            }
+                        }
+                    }
+                    div {
+                        label for="as-of-date" class="block text-sm font-medium text-gray-700 mb-1" { "Date" }
+                        input type="date" name="date" id="as-of-date" required class="w-48 p-2 border border-gray-300 rounded-md shadow-sm focus:ring-indigo-500 focus:border-indigo-500";
+                    }
+                    div {
+                        button type="submit" class="px-5 py-2 bg-indigo-600 text-white font-semibold rounded-md shadow-sm hover:bg-indigo-700 focus:outline-none focus:ring-2 focus:ring-offset-2 focus:ring-indigo-500" {
+                            "Download"
+                        }
+                    }
+                }
+            }
 
             // --- Recent Changes Preview ---
             div {
@@ -93,18 +121,33 @@ pub fn dashboard_page(changes: &[ChangeViewModel], years: &[String]) -> Markup {
                                 tr class="hover:bg-gray-50 transition-colors duration-150" {
                                     // Property Column
                                     td class="px-6 py-4 whitespace-nowrap" {
-                                        div class="text-sm font-medium text-gray-900" { (change.address_line) }
+                                        a href={"/properties/" (change.property_id)} class="text-sm font-medium text-indigo-700 hover:underline" { (change.address_line) }
                                         div class="text-sm text-gray-500" { (change.city) ", " (change.postal_code) }
+                                        @let summary = attribute_summary(change);
+                                        @if !summary.is_empty() {
+                                            div class="text-xs text-gray-400" { (summary) }
+                                        }
+                                        @if let Some(agent) = &change.listing_agent {
+                                            div class="text-xs text-gray-400" {
+                                                "Listed by " (agent)
+                                                @if let Some(brokerage) = &change.brokerage {
+                                                    ", " (brokerage)
+                                                }
+                                            }
+                                        }
                                     }
                                     // Last Changed Column
                                     td class="px-6 py-4 whitespace-nowrap text-sm text-gray-500" {
                                         div { (change.change_date.format("%Y-%m-%d")) }
                                         div class="text-xs text-gray-400" { (change.change_date.format("%-I:%M %p")) }
-                                        div class="text-xs text-indigo-600 font-medium" { (change.change_type) }
+                                        div class="text-xs text-indigo-600 font-medium" { (change.kind.label()) }
+                                        @if let Some(days) = change.days_on_market {
+                                            div class="text-xs text-gray-400" { (days) " days on market" }
+                                        }
                                     }
                                     // Status Column
                                     td class="px-6 py-4 whitespace-nowrap text-sm" {
-                                        @if change.change_type == "Status Change" {
+                                        @if !change.kind.is_price_change() && !change.previous_value.is_empty() {
                                             (format_status(&change.previous_value))
                                             span class="mx-1 text-gray-400" { "→" }
                                             (format_status(&change.current_value))
@@ -114,7 +157,7 @@ pub fn dashboard_page(changes: &[ChangeViewModel], years: &[String]) -> Markup {
                                     }
                                     // Price Column
                                     td class="px-6 py-4 whitespace-nowrap text-sm text-gray-500" {
-                                        @if change.change_type == "Price Change" {
+                                        @if change.kind.is_price_change() {
                                             div {
                                                 span class="line-through" { "$" (change.previous_value) }
                                                 span { " → $" (change.current_value) }
@@ -141,6 +184,9 @@ pub fn dashboard_page(changes: &[ChangeViewModel], years: &[String]) -> Markup {
                                         @if change.is_price_reduced {
                                             div class="font-semibold text-red-600" { "Price Reduced" }
                                         }
+                                        @if change.is_new_construction {
+                                            div class="font-semibold text-blue-800" { "New Construction" }
+                                        }
                                     }
                                 }
                             }
@@ -153,34 +199,64 @@ pub fn dashboard_page(changes: &[ChangeViewModel], years: &[String]) -> Markup {
 }
 
 /// Helper function to format a price for display with a dollar sign and commas.
-fn format_price(price: i64) -> String {
+pub(crate) fn format_price(price: i64) -> String {
     if price == 0 {
         return "N/A".to_string();
     }
-    // This is a simple but effective formatter for US currency.
-    let price_str = price.to_string();
+    format!("${}", format_thousands(price))
+}
+
+/// Inserts thousands separators, e.g. 1850 -> "1,850".
+pub(crate) fn format_thousands(n: i64) -> String {
+    // This is a simple but effective formatter for US-style numbers.
+    let digits = n.to_string();
     let mut result = String::new();
-    let len = price_str.len();
+    let len = digits.len();
     let first = len % 3;
     if first > 0 {
-        result.push_str(&price_str[..first]);
+        result.push_str(&digits[..first]);
     }
-    for (i, chunk) in price_str[first..].as_bytes().chunks(3).enumerate() {
+    for (i, chunk) in digits[first..].as_bytes().chunks(3).enumerate() {
         if first > 0 || i > 0 {
             result.push(',');
         }
         result.push_str(std::str::from_utf8(chunk).unwrap());
     }
-    format!("${}", result)
+    result
+}
+
+/// One-line summary such as "3 bd · 2 ba · 1,850 sqft · single family".
+fn attribute_summary(change: &ChangeViewModel) -> String {
+    let mut parts = Vec::new();
+    if let Some(beds) = change.beds {
+        parts.push(format!("{} bd", beds));
+    }
+    if let Some(baths) = change.baths {
+        parts.push(format!("{} ba", baths));
+    }
+    if let Some(sqft) = change.sqft {
+        parts.push(format!("{} sqft", format_thousands(sqft)));
+    }
+    if let Some(kind) = change.property_type.as_deref() {
+        parts.push(kind.replace('_', " "));
+    }
+    parts.join(" · ")
 }
 
 /// Helper function to render a status string as a colored badge.
-fn format_status(status: &str) -> Markup {
+pub(crate) fn format_status(status: &str) -> Markup {
     let (text, color_classes) = match status {
         "for_sale" => ("For Sale", "bg-blue-100 text-blue-800"),
         "contingent" => ("Contingent", "bg-yellow-100 text-yellow-800"),
         "pending" => ("Pending", "bg-orange-100 text-orange-800"),
         "sold" => ("Sold", "bg-green-100 text-green-800"),
+        // Canonical statuses, as on change events.
+        "Active" => ("Active", "bg-blue-100 text-blue-800"),
+        "Contingent" => ("Contingent", "bg-yellow-100 text-yellow-800"),
+        "Pending" => ("Pending", "bg-orange-100 text-orange-800"),
+        "Sold" => ("Sold", "bg-green-100 text-green-800"),
+        "Coming Soon" => ("Coming Soon", "bg-purple-100 text-purple-800"),
+        "Off Market" => ("Off Market", "bg-red-100 text-red-800"),
         s if s.is_empty() => ("(unknown)", "bg-gray-100 text-gray-800"),
         s => (s, "bg-gray-100 text-gray-800"), // Default case for any other status
     };
@@ -0,0 +1,125 @@
+// src/templates/pages/market.rs
+
+use super::dashboard::{format_price, format_thousands};
+use crate::db::market_stats::{MarketStatsFilter, MarketStatsRow};
+use crate::templates::desktop_layout;
+use maud::{html, Markup};
+
+/// Renders the "Market Trends" page: a filter form and the daily figures for
+/// the chosen state, county or ZIP, newest first.
+pub fn market_page(
+    filter: &MarketStatsFilter,
+    counties: &[String],
+    rows: &[MarketStatsRow],
+) -> Markup {
+    let area = match (&filter.postal_code, &filter.county) {
+        (Some(zip), _) => format!("ZIP {zip}"),
+        (None, Some(county)) => format!("{county} County, {}", filter.state),
+        (None, None) => filter.state.clone(),
+    };
+    let input_class = "p-2 border border-gray-300 rounded-md shadow-sm focus:ring-indigo-500 focus:border-indigo-500";
+    let date_value = |d: Option<chrono::NaiveDate>| d.map(|d| d.format("%Y-%m-%d").to_string());
+
+    desktop_layout(
+        "Market Trends",
+        true,
+        html! {
+            // Page Header
+            div class="mb-6" {
+                h1 class="text-3xl font-bold text-gray-800" { "Market Trends" }
+                p class="text-gray-500 mt-1" { "Daily inventory, sales and prices by state, county or ZIP, updated nightly." }
+            }
+
+            // --- Filters ---
+            div class="bg-white border rounded-lg shadow-sm p-6 mb-8" {
+                form action="/market" method="get" class="flex flex-wrap items-end gap-4" {
+                    div {
+                        label for="state" class="block text-sm font-medium text-gray-700 mb-1" { "State" }
+                        select name="state" id="state" required class={"w-48 " (input_class)} {
+                            option value="" disabled selected[filter.state.is_empty()] { "Select a State..." }
+                            @for (abbr, name) in crate::geos::US_STATES {
+                                option value=(abbr) selected[filter.state == *abbr] { (name) }
+                            }
+                        }
+                    }
+                    div {
+                        label for="county" class="block text-sm font-medium text-gray-700 mb-1" { "County" }
+                        input type="text" name="county" id="county" list="counties" placeholder="All counties"
+                            value=[filter.county.as_deref()] class={"w-48 " (input_class)};
+                        datalist id="counties" {
+                            @for county in counties {
+                                option value=(county) {}
+                            }
+                        }
+                    }
+                    div {
+                        label for="zip" class="block text-sm font-medium text-gray-700 mb-1" { "ZIP" }
+                        input type="text" name="zip" id="zip" placeholder="Any" inputmode="numeric"
+                            value=[filter.postal_code.as_deref()] class={"w-28 " (input_class)};
+                    }
+                    div {
+                        label for="from" class="block text-sm font-medium text-gray-700 mb-1" { "From" }
+                        input type="date" name="from" id="from" value=[date_value(filter.from)] class={"w-44 " (input_class)};
+                    }
+                    div {
+                        label for="to" class="block text-sm font-medium text-gray-700 mb-1" { "To" }
+                        input type="date" name="to" id="to" value=[date_value(filter.to)] class={"w-44 " (input_class)};
+                    }
+                    div {
+                        button type="submit" class="px-5 py-2 bg-indigo-600 text-white font-semibold rounded-md shadow-sm hover:bg-indigo-700 focus:outline-none focus:ring-2 focus:ring-offset-2 focus:ring-indigo-500" {
+                            "Show"
+                        }
+                    }
+                }
+            }
+
+            @if filter.state.is_empty() {
+                p class="text-sm text-gray-500" { "Pick a state to see its market." }
+            } @else if rows.is_empty() {
+                div class="text-center py-12 px-6 bg-white border rounded-lg shadow-sm" {
+                    h3 class="text-sm font-medium text-gray-900" { "No market stats for " (area) }
+                    p class="mt-1 text-sm text-gray-500" { "Figures for each day are worked out the night after." }
+                }
+            } @else {
+                h2 class="text-xl font-semibold text-gray-800 mb-4" { (area) }
+                div class="overflow-x-auto bg-white border rounded-lg shadow-sm" {
+                    table class="min-w-full divide-y divide-gray-200" {
+                        thead class="bg-gray-50" {
+                            tr {
+                                @for heading in ["Date", "Active", "New", "Pending", "Sold", "Median List", "Median Sold", "Median DOM", "Price Cut", "Sale-to-List"] {
+                                    th class="px-4 py-3 text-left text-xs font-medium text-gray-500 uppercase tracking-wider" { (heading) }
+                                }
+                            }
+                        }
+                        tbody class="bg-white divide-y divide-gray-200" {
+                            @for row in rows {
+                                @let stats = &row.stats;
+                                tr class="hover:bg-gray-50" {
+                                    td class="px-4 py-2 whitespace-nowrap text-sm text-gray-900" { (row.stat_date.format("%Y-%m-%d")) }
+                                    td class="px-4 py-2 text-sm text-gray-700" { (format_thousands(stats.active_inventory)) }
+                                    td class="px-4 py-2 text-sm text-gray-700" { (stats.new_listings) }
+                                    td class="px-4 py-2 text-sm text-gray-700" { (stats.pendings) }
+                                    td class="px-4 py-2 text-sm text-gray-700" { (stats.solds) }
+                                    td class="px-4 py-2 text-sm text-gray-700" { (or_dash(stats.median_list_price.map(format_price))) }
+                                    td class="px-4 py-2 text-sm text-gray-700" { (or_dash(stats.median_sold_price.map(format_price))) }
+                                    td class="px-4 py-2 text-sm text-gray-700" { (or_dash(stats.median_days_on_market.map(|d| d.to_string()))) }
+                                    td class="px-4 py-2 text-sm text-gray-700" { (or_dash(stats.price_cut_share.map(percent))) }
+                                    td class="px-4 py-2 text-sm text-gray-700" { (or_dash(stats.sale_to_list_ratio.map(percent))) }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        },
+    )
+}
+
+/// 0.973 -> "97.3%".
+fn percent(share: f64) -> String {
+    format!("{:.1}%", share * 100.0)
+}
+
+fn or_dash(value: Option<String>) -> String {
+    value.unwrap_or_else(|| "—".to_string())
+}
@@ -1,4 +1,5 @@
 pub mod admin;
+pub mod calendar;
 pub mod campaigns;
 pub mod lists;
 pub mod mailings;
@@ -7,9 +8,12 @@ pub mod check_email;
 pub mod dashboard;
 pub mod home;
 pub mod login;
+pub mod market;
 pub mod preview;
+pub mod property;
 
 pub use admin::admin_page;
+pub use calendar::calendar_page;
 pub use campaigns::{
     campaign_details_page, campaigns_index_page, new_campaign_page, new_media_page,
 };
@@ -20,3 +24,5 @@ pub use mailings::{mailings_index_page, new_mailing_page};
 pub use check_email::check_email_content;
 pub use dashboard::dashboard_page;
 pub use home::home_page;
+pub use market::market_page;
+pub use property::property_page;
@@ -0,0 +1,262 @@
+// src/templates/pages/property.rs
+
+use super::dashboard::{format_price, format_status, format_thousands};
+use crate::domain::property_page::PropertyPageViewModel;
+use crate::templates::desktop_layout;
+use chrono::NaiveDateTime;
+use maud::{html, Markup};
+
+/// Renders the page for a single property: its address and attributes, the
+/// listings it's linked to, a list price chart and its full change history.
+pub fn property_page(vm: &PropertyPageViewModel) -> Markup {
+    let tracked = &vm.tracked;
+    let yes_no = |flag: Option<bool>| flag.map(|f| if f { "Yes" } else { "No" }.to_string());
+    let number = |n: Option<i64>| n.map(format_thousands);
+    let attributes = [
+        ("List Price", tracked.list_price.map(format_price)),
+        ("Sold Price", tracked.sold_price.map(format_price)),
+        (
+            "Sold Date",
+            tracked.sold_date.map(|d| d.format("%Y-%m-%d").to_string()),
+        ),
+        (
+            "Site Status",
+            tracked.status.as_ref().map(|s| s.replace('_', " ")),
+        ),
+        ("Beds", number(tracked.beds)),
+        ("Baths", tracked.baths.map(|b| b.to_string())),
+        ("SqFt", number(tracked.sqft)),
+        ("Lot SqFt", number(tracked.lot_sqft)),
+        (
+            "Property Type",
+            tracked.property_type.as_ref().map(|t| t.replace('_', " ")),
+        ),
+        ("Year Built", tracked.year_built.map(|y| y.to_string())),
+        ("Garage", number(tracked.garage)),
+        ("Stories", number(tracked.stories)),
+        ("Subdivision", vm.subdivision.clone()),
+        ("New Construction", yes_no(tracked.is_new_construction)),
+        ("Pending", yes_no(tracked.is_pending)),
+        ("Contingent", yes_no(tracked.is_contingent)),
+        ("Coming Soon", yes_no(tracked.is_coming_soon)),
+        ("New Listing", yes_no(tracked.is_new_listing)),
+        ("Price Reduced", yes_no(tracked.is_price_reduced)),
+        ("Foreclosure", yes_no(tracked.is_foreclosure)),
+    ];
+
+    desktop_layout(
+        &vm.address_line,
+        true,
+        html! {
+            // Page Header
+            div class="mb-6" {
+                a href="/dashboard" class="text-sm text-indigo-600 hover:underline" { "← Dashboard" }
+                div class="flex items-center space-x-3 mt-2" {
+                    h1 class="text-3xl font-bold text-gray-800" { (vm.address_line) }
+                    (format_status(vm.canonical_status))
+                }
+                p class="text-gray-500 mt-1" {
+                    (vm.city) ", " (vm.state_abbr.as_deref().unwrap_or("")) " " (vm.postal_code)
+                    @if let Some(county) = &vm.county_name {
+                        " · " (county) " County"
+                    }
+                }
+                p class="text-xs text-gray-400 mt-1" {
+                    "First seen " (vm.first_seen_at.format("%Y-%m-%d"))
+                    " · last seen " (vm.last_seen_at.format("%Y-%m-%d"))
+                    @if let Some(delisted) = vm.delisted_at {
+                        " · off the market since " (delisted.format("%Y-%m-%d"))
+                    }
+                }
+            }
+
+            // --- Price Chart ---
+            div class="bg-white border rounded-lg shadow-sm p-6 mb-8" {
+                h2 class="text-xl font-semibold text-gray-800 mb-4" { "List Price" }
+                (price_chart(&vm.price_points(), vm.price_points_until()))
+            }
+
+            div class="grid grid-cols-1 md:grid-cols-2 gap-8 mb-8" {
+                // --- Attributes ---
+                div class="bg-white border rounded-lg shadow-sm p-6" {
+                    h2 class="text-xl font-semibold text-gray-800 mb-4" { "Details" }
+                    dl class="grid grid-cols-2 gap-x-4 gap-y-2 text-sm" {
+                        @for (label, value) in &attributes {
+                            @if let Some(value) = value {
+                                dt class="text-gray-500" { (label) }
+                                dd class="text-gray-900" { (value) }
+                            }
+                        }
+                    }
+                    @if vm.listing_agent.is_some() || vm.detail_url.is_some() {
+                        div class="mt-4 pt-4 border-t text-sm text-gray-600" {
+                            @if let Some(agent) = &vm.listing_agent {
+                                div {
+                                    "Listed by " (agent)
+                                    @if let Some(brokerage) = &vm.brokerage {
+                                        ", " (brokerage)
+                                    }
+                                }
+                            }
+                            @if let Some(url) = &vm.detail_url {
+                                a href=(url) target="_blank" rel="noopener" class="text-indigo-600 hover:underline" { "View listing" }
+                            }
+                        }
+                    }
+                }
+
+                // --- Linked Listings ---
+                div class="bg-white border rounded-lg shadow-sm p-6" {
+                    h2 class="text-xl font-semibold text-gray-800 mb-4" { "Listings" }
+                    @if vm.sources.is_empty() {
+                        p class="text-sm text-gray-500" { "No listings linked." }
+                    } @else {
+                        table class="min-w-full text-sm" {
+                            thead {
+                                tr class="text-left text-xs text-gray-500 uppercase" {
+                                    th class="pb-2" { "Source" }
+                                    th class="pb-2" { "Listing ID" }
+                                    th class="pb-2" { "First Seen" }
+                                    th class="pb-2" { "Last Seen" }
+                                }
+                            }
+                            tbody class="divide-y divide-gray-100" {
+                                @for source in &vm.sources {
+                                    tr {
+                                        td class="py-1 text-gray-900" { (source.source_name) }
+                                        td class="py-1 font-mono text-gray-600" { (source.source_listing_id) }
+                                        td class="py-1 text-gray-500" { (source.first_seen_at.format("%Y-%m-%d")) }
+                                        td class="py-1 text-gray-500" { (source.last_seen_at.format("%Y-%m-%d")) }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            // --- Timeline ---
+            h2 class="text-xl font-semibold text-gray-800 mb-4" { "History" }
+            @if vm.timeline.is_empty() {
+                p class="text-sm text-gray-500" { "No history recorded." }
+            } @else {
+                div class="overflow-x-auto bg-white border rounded-lg shadow-sm" {
+                    table class="min-w-full divide-y divide-gray-200" {
+                        thead class="bg-gray-50" {
+                            tr {
+                                th class="px-6 py-3 text-left text-xs font-medium text-gray-500 uppercase tracking-wider" { "When" }
+                                th class="px-6 py-3 text-left text-xs font-medium text-gray-500 uppercase tracking-wider" { "Field" }
+                                th class="px-6 py-3 text-left text-xs font-medium text-gray-500 uppercase tracking-wider" { "Change" }
+                                th class="px-6 py-3 text-left text-xs font-medium text-gray-500 uppercase tracking-wider" { "Reported By" }
+                            }
+                        }
+                        tbody class="bg-white divide-y divide-gray-200" {
+                            @for entry in &vm.timeline {
+                                tr {
+                                    td class="px-6 py-2 whitespace-nowrap text-sm text-gray-500" {
+                                        (entry.observed_at.format("%Y-%m-%d %-I:%M %p"))
+                                    }
+                                    td class="px-6 py-2 whitespace-nowrap text-sm text-gray-900" { (field_label(&entry.field_name)) }
+                                    td class="px-6 py-2 text-sm text-gray-700" {
+                                        @if let Some(previous) = &entry.previous_value {
+                                            span class="text-gray-400" { (history_value(&entry.field_name, previous)) }
+                                            span class="mx-1 text-gray-400" { "→" }
+                                        }
+                                        (history_value(&entry.field_name, &entry.current_value))
+                                    }
+                                    td class="px-6 py-2 whitespace-nowrap text-sm text-gray-500" { (entry.reported_by) }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        },
+    )
+}
+
+/// A step chart of list price over time, as inline SVG. Each price holds
+/// until the next change, and the last one runs to `until`.
+fn price_chart(points: &[(NaiveDateTime, i64)], until: NaiveDateTime) -> Markup {
+    const WIDTH: f64 = 640.0;
+    const HEIGHT: f64 = 220.0;
+    const LEFT: f64 = 80.0;
+    const RIGHT: f64 = 16.0;
+    const TOP: f64 = 16.0;
+    const BOTTOM: f64 = 28.0;
+
+    let (Some(&(start, _)), Some(&(last, _))) = (points.first(), points.last()) else {
+        return html! { p class="text-sm text-gray-500" { "No list price recorded." } };
+    };
+    let end = until.max(last);
+    let span = (end - start).num_seconds().max(1) as f64;
+
+    let low = points.iter().map(|&(_, p)| p).min().unwrap_or(0) as f64;
+    let high = points.iter().map(|&(_, p)| p).max().unwrap_or(0) as f64;
+    // Keep a flat price off the chart's edges.
+    let pad = ((high - low) * 0.1).max(high * 0.02).max(1.0);
+    let (low, high) = (low - pad, high + pad);
+
+    let x =
+        |t: NaiveDateTime| LEFT + (t - start).num_seconds() as f64 / span * (WIDTH - LEFT - RIGHT);
+    let y = |p: i64| TOP + (high - p as f64) / (high - low) * (HEIGHT - TOP - BOTTOM);
+
+    let mut path = format!("M{:.1} {:.1}", x(start), y(points[0].1));
+    for &(t, p) in &points[1..] {
+        path.push_str(&format!(" H{:.1} V{:.1}", x(t), y(p)));
+    }
+    path.push_str(&format!(" H{:.1}", x(end)));
+
+    let max_price = points.iter().map(|&(_, p)| p).max().unwrap_or(0);
+    let min_price = points.iter().map(|&(_, p)| p).min().unwrap_or(0);
+    let mut guides = vec![max_price];
+    if min_price != max_price {
+        guides.push(min_price);
+    }
+
+    html! {
+        svg viewBox=(format!("0 0 {WIDTH} {HEIGHT}")) class="w-full h-auto" role="img" aria-label="List price over time" {
+            @for price in &guides {
+                line x1=(LEFT) x2=(WIDTH - RIGHT) y1=(format!("{:.1}", y(*price))) y2=(format!("{:.1}", y(*price)))
+                    stroke="#e5e7eb" stroke-dasharray="4 4" {}
+                text x=(LEFT - 8.0) y=(format!("{:.1}", y(*price) + 4.0)) text-anchor="end" font-size="11" fill="#6b7280" {
+                    (format_price(*price))
+                }
+            }
+            line x1=(LEFT) x2=(WIDTH - RIGHT) y1=(HEIGHT - BOTTOM) y2=(HEIGHT - BOTTOM) stroke="#d1d5db" {}
+            text x=(LEFT) y=(HEIGHT - 8.0) font-size="11" fill="#6b7280" { (start.format("%b %-d, %Y")) }
+            text x=(WIDTH - RIGHT) y=(HEIGHT - 8.0) text-anchor="end" font-size="11" fill="#6b7280" { (end.format("%b %-d, %Y")) }
+            path d=(path) fill="none" stroke="#4f46e5" stroke-width="2" {}
+            @for &(t, p) in points {
+                circle cx=(format!("{:.1}", x(t))) cy=(format!("{:.1}", y(p))) r="4" fill="#4f46e5" {
+                    title { (format_price(p)) " on " (t.format("%Y-%m-%d")) }
+                }
+            }
+        }
+    }
+}
+
+/// "is_price_reduced" -> "Price reduced".
+fn field_label(field_name: &str) -> String {
+    let name = field_name
+        .strip_prefix("is_")
+        .unwrap_or(field_name)
+        .replace('_', " ");
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => name,
+    }
+}
+
+/// A history value for display. Empty values are cleared fields.
+fn history_value(field_name: &str, value: &str) -> String {
+    match (field_name, value) {
+        (_, "") => "—".to_string(),
+        (_, "true") => "Yes".to_string(),
+        (_, "false") => "No".to_string(),
+        ("list_price" | "sold_price", v) => v.parse().map(format_price).unwrap_or(v.to_string()),
+        ("sqft" | "lot_sqft", v) => v.parse().map(format_thousands).unwrap_or(v.to_string()),
+        (_, v) => v.replace('_', " "),
+    }
+}
@@ -0,0 +1,81 @@
+// src/tests/cycles_tests.rs
+
+use crate::domain::events::ChangeEventKind;
+use crate::scraper::scraper::SearchKind;
+use crate::tests::utils::{init_test_db, main_st_pending_without_page_two, sweep_utah, FIXTURES};
+
+#[test]
+fn listing_cycles_track_pending_price_cuts_and_relists() {
+    let db = init_test_db();
+    let cycles = |address: &str| {
+        db.with_conn(|conn| {
+            let rows = conn
+                .prepare(
+                    "SELECT c.cycle_number, c.pending_at IS NOT NULL, c.price_cuts, c.outcome,
+                            c.days_on_market
+                     FROM listing_cycles c JOIN properties p ON p.id = c.property_id
+                     WHERE p.address_line = ? ORDER BY c.cycle_number",
+                )?
+                .query_map([address], |r| {
+                    Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?, r.get(4)?))
+                })?
+                .collect::<Result<Vec<(i64, bool, i64, Option<String>, Option<i64>)>, _>>()?;
+            Ok(rows)
+        })
+        .unwrap()
+    };
+
+    sweep_utah(&db, FIXTURES, SearchKind::ForSale);
+
+    sweep_utah(
+        &db,
+        main_st_pending_without_page_two("cycles"),
+        SearchKind::ForSale,
+    );
+    // Page two's listings come back.
+    sweep_utah(&db, FIXTURES, SearchKind::ForSale);
+
+    assert_eq!(cycles("123 N Main St"), vec![(1, true, 1, None, None)]);
+    assert_eq!(
+        cycles("4410 W Sunset Dr"),
+        vec![
+            (1, false, 0, Some("delisted".to_string()), Some(0)),
+            (2, false, 0, None, None),
+        ]
+    );
+
+    let year = chrono::Utc::now()
+        .format("%Y")
+        .to_string()
+        .parse::<i32>()
+        .unwrap();
+    let counties = db
+        .with_conn(|conn| crate::db::cycles::county_cycle_summary(conn, "UT", year))
+        .unwrap();
+    let totals = counties.iter().fold((0, 0, 0), |(c, o, d), s| {
+        (c + s.cycles, o + s.open, d + s.delisted)
+    });
+    assert_eq!(totals, (7, 5, 2));
+
+    let events = db
+        .with_conn(|conn| crate::db::properties::get_change_events_for_export(conn, "UT", year))
+        .unwrap();
+    let relisted = events
+        .iter()
+        .find(|e| e.address_line == "4410 W Sunset Dr" && e.kind == ChangeEventKind::BackOnMarket)
+        .unwrap();
+    assert_eq!(relisted.listing_cycle, Some(2));
+    assert_eq!(
+        relisted.days_on_market,
+        Some(0),
+        "open cycles count to today"
+    );
+    let cut = events
+        .iter()
+        .find(|e| e.kind == ChangeEventKind::PriceDrop)
+        .unwrap();
+    assert_eq!(
+        (cut.listing_cycle, cut.price_cuts, cut.days_to_pending),
+        (Some(1), Some(1), Some(0))
+    );
+}
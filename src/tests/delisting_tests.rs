@@ -0,0 +1,420 @@
+// src/tests/delisting_tests.rs
+
+use crate::db::scrapes;
+use crate::scraper::config::ScraperConfig;
+use crate::scraper::fetcher::FileFetcher;
+use crate::scraper::scraper::SearchKind;
+use crate::scraper::shards::ScrapeShard;
+use crate::scraper::sources::{self, zillow::ZillowAdapter};
+use crate::scraper::RealtorScraper;
+use crate::tests::utils::{
+    edited_fixtures, init_test_db, query_i64, scrape_utah, sweep_utah, utah_run, FIXTURES,
+    PAGE_ONE, PAGE_TWO, UTAH_URL,
+};
+
+const DELISTED: &str = "SELECT COUNT(*) FROM properties WHERE delisted_at IS NOT NULL";
+
+#[test]
+fn complete_sweeps_delist_missing_properties_and_relist_returning_ones() {
+    let db = init_test_db();
+    let market_status = || {
+        db.with_conn(|conn| {
+            let rows = conn
+                .prepare(
+                    "SELECT p.address_line, h.current_value FROM property_history h
+                     JOIN properties p ON p.id = h.property_id
+                     WHERE h.field_name = 'market_status' ORDER BY h.id",
+                )?
+                .query_map([], |r| Ok((r.get(0)?, r.get(1)?)))?
+                .collect::<Result<Vec<(String, String)>, _>>()?;
+            Ok(rows)
+        })
+        .unwrap()
+    };
+
+    sweep_utah(&db, FIXTURES, SearchKind::ForSale);
+    assert!(market_status().is_empty());
+
+    // Page two's listings are gone and the site now reports three results.
+    let dir = edited_fixtures("delisted", &[PAGE_ONE], |_, html| {
+        html.replace(r#""totalProperties": 5"#, r#""totalProperties": 3"#)
+    });
+    sweep_utah(&db, &dir, SearchKind::ForSale);
+    assert_eq!(
+        market_status(),
+        vec![
+            ("4410 W Sunset Dr".to_string(), "off_market".to_string()),
+            ("18 Maple Ln".to_string(), "off_market".to_string()),
+        ]
+    );
+
+    // An incomplete sweep (three of five results) delists nothing further.
+    std::fs::copy(format!("{FIXTURES}/{PAGE_ONE}"), dir.join(PAGE_ONE)).unwrap();
+    sweep_utah(&db, &dir, SearchKind::ForSale);
+    assert_eq!(market_status().len(), 2);
+
+    sweep_utah(&db, FIXTURES, SearchKind::ForSale);
+    let transitions: Vec<String> = market_status().into_iter().map(|(_, v)| v).collect();
+    assert_eq!(
+        transitions,
+        vec![
+            "off_market",
+            "off_market",
+            "back_on_market",
+            "back_on_market"
+        ]
+    );
+    assert_eq!(query_i64(&db, DELISTED), 0);
+}
+
+#[test]
+fn a_failed_price_band_keeps_the_sweep_from_delisting_anything() {
+    let db = init_test_db();
+    db.with_conn(|conn| {
+        conn.execute(
+            "INSERT INTO properties (address_line, city, postal_code, state_abbr, address_key,
+                status, list_price, first_seen_at, last_seen_at)
+             VALUES ('1 Quarry Ln', 'Provo', '84601', 'UT', '1 quarry ln|provo|84601',
+                'for_sale', 300000, '2025-01-01 00:00:00', '2025-01-01 00:00:00')",
+            [],
+        )?;
+        Ok(())
+    })
+    .unwrap();
+
+    // Every band pages through except the one above $1M, which finds no
+    // listings anyway, so the split run's coverage still adds up to 100%.
+    let bands = [
+        PAGE_ONE,
+        "realestateandhomes-search_Utah_price-0-1000000.html",
+        "realestateandhomes-search_Utah_price-0-500000.html",
+        "realestateandhomes-search_Utah_price-500001-1000000.html",
+    ];
+    let dir = edited_fixtures("failed_band", &bands, |_, html| html);
+    std::fs::write(
+        dir.join("realestateandhomes-search_Utah_price-1000001-na.html"),
+        "<html><body>Something went wrong</body></html>",
+    )
+    .unwrap();
+    RealtorScraper::with_fetcher(Box::new(FileFetcher::new(&dir)))
+        .with_config(ScraperConfig {
+            page_cap: 1,
+            ..Default::default()
+        })
+        .run_sweep(&db, vec![ScrapeShard::state("UT", "Utah")]);
+
+    let runs = db
+        .with_conn(|conn| scrapes::get_recent_scrapes(conn))
+        .unwrap();
+    let root = runs.iter().find(|r| r.parent_run_id.is_none()).unwrap();
+    assert_eq!(root.coverage, Some(1.0));
+    assert!(runs
+        .iter()
+        .any(|r| r.shard.as_deref() == Some("price:1000001-na") && r.success == Some(false)));
+
+    assert_eq!(query_i64(&db, DELISTED), 0);
+}
+
+#[test]
+fn a_capped_run_without_a_reported_total_is_not_a_complete_sweep() {
+    let db = init_test_db();
+    db.with_conn(|conn| {
+        let finished = |run_id: i64| {
+            scrapes::end_scrape_run(conn, run_id, 1_700_000_100, 1, 42, true, None)?;
+            scrapes::set_expected_total(conn, run_id, None)
+        };
+        let complete = scrapes::start_scrape_run(conn, &utah_run(), 1_700_000_000)?;
+        finished(complete)?;
+        assert!(scrapes::sweep_is_complete(conn, &[complete])?);
+
+        let capped = scrapes::start_scrape_run(conn, &utah_run(), 1_700_000_000)?;
+        finished(capped)?;
+        scrapes::set_capped(conn, capped)?;
+        assert!(!scrapes::sweep_is_complete(conn, &[capped])?);
+        Ok(())
+    })
+    .unwrap();
+}
+
+#[test]
+fn a_delisted_home_seen_sold_is_not_put_back_on_the_market() {
+    let db = init_test_db();
+    let sweep = |kind| sweep_utah(&db, FIXTURES, kind);
+    sweep(SearchKind::Sold);
+    let id: i64 = db
+        .with_conn(|conn| {
+            Ok(conn.query_row(
+                "SELECT id FROM properties WHERE address_line = '56 Oak Hollow Dr'",
+                [],
+                |r| r.get(0),
+            )?)
+        })
+        .unwrap();
+    db.with_conn(|conn| {
+        conn.execute(
+            "UPDATE properties SET delisted_at = '2020-01-01 00:00:00' WHERE id = ?",
+            [id],
+        )?;
+        Ok(())
+    })
+    .unwrap();
+
+    sweep(SearchKind::Sold);
+    let (delisted, market_status, back_on_market): (bool, Vec<String>, i64) = db
+        .with_conn(|conn| {
+            let delisted = conn.query_row(
+                "SELECT delisted_at IS NOT NULL FROM properties WHERE id = ?",
+                [id],
+                |r| r.get(0),
+            )?;
+            let market_status = conn
+                .prepare(
+                    "SELECT current_value FROM property_history
+                     WHERE property_id = ? AND field_name = 'market_status' ORDER BY id",
+                )?
+                .query_map([id], |r| r.get(0))?
+                .collect::<Result<_, _>>()?;
+            let back_on_market = conn.query_row(
+                "SELECT COUNT(*) FROM change_events
+                 WHERE property_id = ? AND event_type = 'back_on_market'",
+                [id],
+                |r| r.get(0),
+            )?;
+            Ok((delisted, market_status, back_on_market))
+        })
+        .unwrap();
+    assert!(!delisted);
+    assert_eq!(market_status, vec!["sold".to_string()]);
+    assert_eq!(back_on_market, 0);
+}
+
+#[test]
+fn a_delisted_home_seen_off_the_market_stays_delisted() {
+    let db = init_test_db();
+    scrape_utah(&db, FIXTURES, 1_700_000_000);
+    db.with_conn(|conn| {
+        conn.execute(
+            "UPDATE properties SET delisted_at = '2020-01-01 00:00:00'
+             WHERE address_line = '123 N Main St'",
+            [],
+        )?;
+        Ok(())
+    })
+    .unwrap();
+
+    // Neither search shows an off-market listing, so it isn't relisted.
+    let dir = edited_fixtures("off_market", &[PAGE_ONE], |_, html| {
+        html.replacen(r#""status": "for_sale""#, r#""status": "off_market""#, 1)
+            .replace("450000", "435000")
+    });
+    scrape_utah(&db, &dir, 1_700_100_000);
+
+    let events: Vec<(String, Option<String>, String)> = db
+        .with_conn(|conn| {
+            let rows = conn
+                .prepare(
+                    "SELECT e.event_type, e.previous_status, e.current_status
+                     FROM change_events e JOIN properties p ON p.id = e.property_id
+                     WHERE p.address_line = '123 N Main St' ORDER BY e.id",
+                )?
+                .query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)))?
+                .collect::<Result<_, _>>()?;
+            Ok(rows)
+        })
+        .unwrap();
+    // The price drop is logged against the delisting it happened under.
+    assert_eq!(
+        events,
+        vec![
+            ("new_listing".to_string(), None, "Active".to_string()),
+            (
+                "price_drop".to_string(),
+                Some("Off Market".to_string()),
+                "Off Market".to_string()
+            ),
+        ]
+    );
+    assert_eq!(query_i64(&db, DELISTED), 1);
+}
+
+#[test]
+fn for_sale_sweeps_leave_sold_homes_they_did_not_show_alone() {
+    let db = init_test_db();
+    let sweep = |kind| sweep_utah(&db, FIXTURES, kind);
+
+    // 56 Oak Hollow Dr only turns up in the sold search.
+    sweep(SearchKind::ForSale);
+    sweep(SearchKind::Sold);
+    sweep(SearchKind::ForSale);
+    sweep(SearchKind::Sold);
+
+    let (delisted, market_status): (bool, i64) = db
+        .with_conn(|conn| {
+            Ok(conn.query_row(
+                "SELECT p.delisted_at IS NOT NULL,
+                        (SELECT COUNT(*) FROM property_history h
+                         WHERE h.property_id = p.id AND h.field_name = 'market_status')
+                 FROM properties p WHERE p.address_line = '56 Oak Hollow Dr'",
+                [],
+                |r| Ok((r.get(0)?, r.get(1)?)),
+            )?)
+        })
+        .unwrap();
+    assert_eq!((delisted, market_status), (false, 0));
+}
+
+#[test]
+fn complete_sweeps_leave_properties_only_another_site_listed_alone() {
+    let db = init_test_db();
+
+    // 2210 S Highland Dr is only on Zillow, imported before the sweep.
+    let html = std::fs::read_to_string("src/tests/fixtures/zillow/search_UT.html").unwrap();
+    let observed_at = chrono::Utc::now().naive_utc() - chrono::Duration::days(1);
+    sources::import_search_page(&db, &ZillowAdapter, &html, observed_at).unwrap();
+    sweep_utah(&db, FIXTURES, SearchKind::ForSale);
+
+    let delisted: bool = db
+        .with_conn(|conn| {
+            Ok(conn.query_row(
+                "SELECT delisted_at IS NOT NULL FROM properties
+                 WHERE address_line = '2210 S Highland Dr'",
+                [],
+                |r| r.get(0),
+            )?)
+        })
+        .unwrap();
+    assert!(!delisted);
+    assert_eq!(query_i64(&db, DELISTED), 0);
+}
+
+#[test]
+fn sold_and_rental_sweeps_record_sales_and_rents_without_delisting() {
+    let db = init_test_db();
+    let sweep = |kind| sweep_utah(&db, FIXTURES, kind);
+    sweep(SearchKind::ForSale);
+    sweep(SearchKind::Sold);
+    sweep(SearchKind::Rent);
+
+    let runs = db
+        .with_conn(|conn| scrapes::get_recent_scrapes(conn))
+        .unwrap();
+    let mut kinds: Vec<(Option<&str>, Option<&str>, Option<bool>)> = runs
+        .iter()
+        .map(|r| (r.search_kind.as_deref(), r.search_url.as_deref(), r.success))
+        .collect();
+    kinds.reverse();
+    assert_eq!(
+        kinds,
+        vec![
+            (Some("for_sale"), Some(UTAH_URL), Some(true)),
+            (
+                Some("sold"),
+                Some("https://www.realtor.com/realestateandhomes-search/Utah/show-recently-sold"),
+                Some(true)
+            ),
+            (
+                Some("for_rent"),
+                Some("https://www.realtor.com/apartments/Utah"),
+                Some(true)
+            ),
+        ]
+    );
+
+    let sold: Vec<(String, String)> = db
+        .with_conn(|conn| {
+            let rows = conn
+                .prepare(
+                    "SELECT h.field_name, h.current_value FROM property_history h
+                     JOIN properties p ON p.id = h.property_id
+                     WHERE p.address_line = '123 N Main St' ORDER BY h.id",
+                )?
+                .query_map([], |r| Ok((r.get(0)?, r.get(1)?)))?
+                .collect::<Result<_, _>>()?;
+            Ok(rows)
+        })
+        .unwrap();
+    assert!(sold.contains(&("status".into(), "sold".into())));
+    assert!(sold.contains(&("sold_price".into(), "441000".into())));
+    assert!(sold.contains(&("sold_date".into(), "2025-10-02 00:00:00".into())));
+
+    let (rent, in_properties): (Option<i64>, bool) = db
+        .with_conn(|conn| {
+            Ok(conn.query_row(
+                "SELECT monthly_rent, EXISTS (
+                     SELECT 1 FROM properties WHERE address_line = '210 S 500 E Unit 12'
+                 )
+                 FROM rental_listings WHERE address_line = '210 S 500 E Unit 12'",
+                [],
+                |r| Ok((r.get(0)?, r.get(1)?)),
+            )?)
+        })
+        .unwrap();
+    assert_eq!((rent, in_properties), (Some(1850), false));
+
+    // Neither sweep saw the for-sale listings, but only a for-sale sweep
+    // says what is off the market.
+    assert_eq!(query_i64(&db, DELISTED), 0);
+}
+
+#[test]
+fn a_house_for_sale_and_for_rent_keeps_its_rent_out_of_the_list_price() {
+    let db = init_test_db();
+
+    // The first rental is 123 N Main St, which is also for sale.
+    let rentals = "apartments_Utah.html";
+    let dir = edited_fixtures(
+        "rent_and_sale",
+        &[rentals, PAGE_ONE, PAGE_TWO],
+        |page, html| {
+            if page != rentals {
+                return html;
+            }
+            html.replacen(
+                r#""line": "210 S 500 E Unit 12""#,
+                r#""line": "123 N Main St""#,
+                1,
+            )
+            .replacen(r#""postal_code": "84102""#, r#""postal_code": "84101""#, 1)
+        },
+    );
+
+    for kind in [
+        SearchKind::ForSale,
+        SearchKind::Rent,
+        SearchKind::ForSale,
+        SearchKind::Rent,
+    ] {
+        sweep_utah(&db, &dir, kind);
+    }
+
+    let (list_price, price_events): (Option<i64>, i64) = db
+        .with_conn(|conn| {
+            Ok(conn.query_row(
+                "SELECT p.list_price,
+                        (SELECT COUNT(*) FROM change_events e
+                         WHERE e.property_id = p.id
+                           AND e.event_type IN ('price_drop', 'price_increase'))
+                 FROM properties p WHERE p.address_line = '123 N Main St'",
+                [],
+                |r| Ok((r.get(0)?, r.get(1)?)),
+            )?)
+        })
+        .unwrap();
+    assert_eq!(list_price, Some(450000));
+    assert_eq!(price_events, 0);
+
+    let rents: Vec<(Option<i64>, Option<i64>)> = db
+        .with_conn(|conn| {
+            let rows = conn
+                .prepare(
+                    "SELECT h.previous_rent, h.current_rent FROM rental_history h
+                     JOIN rental_listings r ON r.id = h.rental_id
+                     WHERE r.address_line = '123 N Main St' ORDER BY h.id",
+                )?
+                .query_map([], |r| Ok((r.get(0)?, r.get(1)?)))?
+                .collect::<Result<_, _>>()?;
+            Ok(rows)
+        })
+        .unwrap();
+    assert_eq!(rents, vec![(None, Some(1850))]);
+}
@@ -0,0 +1,254 @@
+// src/tests/duplicates_tests.rs
+
+use crate::db::connection::{init_db, Database};
+use crate::db::duplicates;
+use crate::db::properties::rekey_properties;
+use crate::scraper::sources::{self, zillow::ZillowAdapter, ImportSummary};
+use crate::tests::utils::{
+    edited_fixtures, init_test_db, scrape_utah, tmp_dir, FIXTURES, PAGE_ONE, PAGE_TWO,
+};
+
+#[test]
+fn respelled_addresses_match_the_existing_property() {
+    let db = init_test_db();
+    scrape_utah(&db, FIXTURES, 1_700_000_000);
+
+    let dir = edited_fixtures("respelled", &[PAGE_ONE, PAGE_TWO], |_, html| {
+        html.replace("123 N Main St", "123 North Main Street")
+            .replace("77 E 400 S Apt 4", "77 East 400 South #4")
+    });
+    scrape_utah(&db, &dir, 1_700_000_000);
+
+    db.with_conn(|conn| {
+        let lines: Vec<String> = conn
+            .prepare("SELECT address_line FROM properties ORDER BY id")?
+            .query_map([], |r| r.get(0))?
+            .collect::<Result<_, _>>()?;
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[0], "123 N Main St", "display form is the first seen");
+        assert_eq!(lines[1], "77 E 400 S Apt 4");
+        Ok(())
+    })
+    .unwrap();
+}
+
+#[test]
+fn rekey_merges_legacy_rows_that_share_a_normalized_address() {
+    let db = init_test_db();
+    db.with_conn(|conn| {
+        conn.execute_batch(
+            "INSERT INTO properties (id, address_line, city, postal_code, first_seen_at, last_seen_at)
+             VALUES (1, '9 Canyon Road', 'Provo', '84604', '2024-01-01 00:00:00', '2024-02-01 00:00:00'),
+                    (2, '9 CANYON RD.', 'PROVO', '84604-1111', '2023-06-01 00:00:00', '2024-03-01 00:00:00'),
+                    (3, '9 Canyon Rd Unit 2', 'Provo', '84604', '2024-01-01 00:00:00', '2024-01-01 00:00:00');
+             INSERT INTO property_history (property_id, observed_at, field_name, current_value)
+             VALUES (1, '2024-01-01 00:00:00', 'status', 'for_sale'),
+                    (2, '2024-03-01 00:00:00', 'status', 'pending');
+             INSERT INTO property_sources (property_id, source_name, source_listing_id, first_seen_at, last_seen_at)
+             VALUES (2, 'realtor', 'M-2', '2023-06-01 00:00:00', '2024-03-01 00:00:00');",
+        )?;
+
+        assert_eq!(rekey_properties(conn)?, 1);
+        assert_eq!(rekey_properties(conn)?, 0, "already keyed rows are left alone");
+
+        let rows: Vec<(i64, String, String, String)> = conn
+            .prepare("SELECT id, address_key, first_seen_at, last_seen_at FROM properties ORDER BY id")?
+            .query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?)))?
+            .collect::<Result<_, _>>()?;
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].1, "9 CANYON RD|PROVO|84604");
+        assert_eq!(rows[0].2, "2023-06-01 00:00:00");
+        assert_eq!(rows[0].3, "2024-03-01 00:00:00");
+        assert_eq!(rows[1].1, "9 CANYON RD # 2|PROVO|84604");
+
+        let moved: i64 = conn.query_row(
+            "SELECT (SELECT COUNT(*) FROM property_history WHERE property_id = 1)
+                  + (SELECT COUNT(*) FROM property_sources WHERE property_id = 1)",
+            [],
+            |r| r.get(0),
+        )?;
+        assert_eq!(moved, 3);
+        Ok(())
+    })
+    .unwrap();
+}
+
+#[test]
+fn restarting_keeps_an_older_database_and_upgrades_it_in_place() {
+    let path = tmp_dir("legacy_db").with_extension("sqlite");
+    let db = Database::new(path.to_string_lossy().to_string());
+    db.with_conn(|conn| {
+        conn.execute_batch(
+            "CREATE TABLE properties (
+                id INTEGER PRIMARY KEY, address_line TEXT NOT NULL, city TEXT NOT NULL,
+                postal_code TEXT NOT NULL, state_abbr TEXT, county_name TEXT, status TEXT,
+                list_price INTEGER, sold_price INTEGER, sold_date DATETIME, is_pending INTEGER,
+                is_contingent INTEGER, is_new_listing INTEGER, is_foreclosure INTEGER,
+                is_price_reduced INTEGER, is_coming_soon INTEGER,
+                first_seen_at DATETIME NOT NULL, last_seen_at DATETIME NOT NULL,
+                UNIQUE (address_line, city, postal_code)
+             );
+             CREATE TABLE property_history (
+                id INTEGER PRIMARY KEY, property_id INTEGER NOT NULL, observed_at DATETIME NOT NULL,
+                field_name TEXT NOT NULL, previous_value TEXT, current_value TEXT NOT NULL
+             );
+             INSERT INTO properties (id, address_line, city, postal_code, state_abbr, status,
+                                     list_price, first_seen_at, last_seen_at)
+             VALUES (1, '9 Canyon Road', 'Provo', '84604', 'UT', 'for_sale', 480000,
+                     '2024-01-01 00:00:00', '2024-02-01 00:00:00');
+             INSERT INTO property_history (property_id, observed_at, field_name, previous_value, current_value)
+             VALUES (1, '2024-01-01 00:00:00', 'status', NULL, 'for_sale'),
+                    (1, '2024-01-01 00:00:00', 'list_price', NULL, '500000'),
+                    (1, '2024-02-01 00:00:00', 'list_price', '500000', '480000');",
+        )?;
+        Ok(())
+    })
+    .unwrap();
+
+    init_db(&db, "sql/schema.sql").unwrap();
+    init_db(&db, "sql/schema.sql").unwrap();
+
+    db.with_conn(|conn| {
+        let (key, price, beds): (String, i64, Option<i64>) = conn.query_row(
+            "SELECT address_key, list_price, beds FROM properties WHERE id = 1",
+            [],
+            |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)),
+        )?;
+        assert_eq!(key, "9 CANYON RD|PROVO|84604");
+        assert_eq!((price, beds), (480000, None));
+
+        let events: Vec<String> = conn
+            .prepare("SELECT event_type FROM change_events WHERE property_id = 1 ORDER BY id")?
+            .query_map([], |r| r.get(0))?
+            .collect::<Result<_, _>>()?;
+        assert_eq!(events, ["new_listing", "price_drop"]);
+        let cuts: i64 = conn.query_row(
+            "SELECT price_cuts FROM listing_cycles WHERE property_id = 1",
+            [],
+            |r| r.get(0),
+        )?;
+        assert_eq!(cuts, 1);
+        Ok(())
+    })
+    .unwrap();
+}
+
+#[test]
+fn duplicate_scan_flags_unit_coordinate_and_listing_overlaps_once() {
+    let db = init_test_db();
+    db.with_conn(|conn| {
+        conn.execute_batch(
+            "INSERT INTO properties (id, address_line, city, postal_code, address_key, latitude, longitude, first_seen_at, last_seen_at)
+             VALUES (1, '9 Canyon Rd', 'Provo', '84604', '9 CANYON RD|PROVO|84604', 40.25, -111.65, '2024-01-01 00:00:00', '2024-01-01 00:00:00'),
+                    (2, '9 Canyon Rd Unit A', 'Provo', '84604', '9 CANYON RD # A|PROVO|84604', NULL, NULL, '2024-01-01 00:00:00', '2024-01-01 00:00:00'),
+                    (3, '11 Canyon Rd', 'Provo', '84604', '11 CANYON RD|PROVO|84604', 40.25005, -111.65, '2024-01-01 00:00:00', '2024-01-01 00:00:00'),
+                    (4, '9 Canyon Rd Unit B', 'Provo', '84604', '9 CANYON RD # B|PROVO|84604', NULL, NULL, '2024-01-01 00:00:00', '2024-01-01 00:00:00'),
+                    (5, '500 Elm St', 'Orem', '84057', '500 ELM ST|OREM|84057', 40.30, -111.70, '2024-01-01 00:00:00', '2024-01-01 00:00:00'),
+                    (6, '5000 Elm St', 'Orem', '84057', '5000 ELM ST|OREM|84057', 40.40, -111.70, '2024-01-01 00:00:00', '2024-01-01 00:00:00');
+             INSERT INTO property_sources (property_id, source_name, source_listing_id, first_seen_at, last_seen_at)
+             VALUES (5, 'UtahRealEstate', '1900001', '2024-01-01 00:00:00', '2024-01-01 00:00:00'),
+                    (6, 'WFRMLS', '1900001', '2024-01-01 00:00:00', '2024-01-01 00:00:00');",
+        )?;
+
+        assert_eq!(duplicates::detect_duplicates(conn, 1_700_000_000)?, 4);
+        assert_eq!(duplicates::detect_duplicates(conn, 1_700_000_100)?, 0);
+
+        let mut pairs: Vec<(i64, i64, String)> = duplicates::list_open_candidates(conn, 10)?
+            .into_iter()
+            .map(|c| (c.a.id, c.b.id, c.reasons))
+            .collect();
+        pairs.sort();
+        assert_eq!(
+            pairs,
+            vec![
+                (1, 2, "same street address".to_string()),
+                (1, 3, "6 m apart".to_string()),
+                (1, 4, "same street address".to_string()),
+                (5, 6, "shared listing id".to_string()),
+            ]
+        );
+        Ok(())
+    })
+    .unwrap();
+}
+
+#[test]
+fn second_source_merges_into_realtor_properties_and_is_credited_per_change() {
+    let db = init_test_db();
+    scrape_utah(&db, FIXTURES, 1_700_000_000);
+
+    let html = std::fs::read_to_string("src/tests/fixtures/zillow/search_UT.html").unwrap();
+    let observed_at = chrono::Utc::now().naive_utc();
+    let summary = sources::import_search_page(&db, &ZillowAdapter, &html, observed_at).unwrap();
+    assert_eq!(
+        summary,
+        ImportSummary {
+            new: 1,
+            changed: 2,
+            unchanged: 0,
+            rejected: 1,
+            total: Some(4)
+        }
+    );
+
+    let reporters = |address: &str, field: &str| -> Vec<(String, String)> {
+        db.with_conn(|conn| {
+            let rows = conn
+                .prepare(
+                    "SELECT h.current_value, GROUP_CONCAT(hs.site, ',')
+                     FROM property_history h
+                     JOIN properties p ON p.id = h.property_id
+                     JOIN property_history_sources hs ON hs.history_id = h.id
+                     WHERE p.address_line = ?1 AND h.field_name = ?2
+                     GROUP BY h.id ORDER BY h.id",
+                )?
+                .query_map([address, field], |r| Ok((r.get(0)?, r.get(1)?)))?
+                .collect::<Result<_, _>>()?;
+            Ok(rows)
+        })
+        .unwrap()
+    };
+
+    // Zillow's lower price is a change only it reported; the beds both
+    // sites agree on are credited to both.
+    assert_eq!(
+        reporters("123 N Main St", "list_price"),
+        vec![
+            ("450000".to_string(), "realtor".to_string()),
+            ("445000".to_string(), "zillow".to_string()),
+        ]
+    );
+    assert_eq!(
+        reporters("123 N Main St", "beds"),
+        vec![("3".to_string(), "realtor,zillow".to_string())]
+    );
+    // Fields Zillow doesn't show aren't cleared.
+    assert_eq!(
+        reporters("123 N Main St", "is_new_listing"),
+        vec![("true".to_string(), "realtor".to_string())]
+    );
+    assert_eq!(
+        reporters("77 E 400 S Apt 4", "is_pending"),
+        vec![("true".to_string(), "zillow".to_string())]
+    );
+    // Zillow's half bath is kept, not rounded up to Realtor's count.
+    assert_eq!(
+        reporters("77 E 400 S Apt 4", "baths"),
+        vec![
+            ("2".to_string(), "realtor".to_string()),
+            ("1.5".to_string(), "zillow".to_string()),
+        ]
+    );
+
+    db.with_conn(|conn| {
+        let (properties, zillow_sources): (i64, i64) = conn.query_row(
+            "SELECT (SELECT COUNT(*) FROM properties),
+                    (SELECT COUNT(*) FROM property_sources WHERE source_name = 'zillow')",
+            [],
+            |r| Ok((r.get(0)?, r.get(1)?)),
+        )?;
+        assert_eq!((properties, zillow_sources), (6, 3));
+        Ok(())
+    })
+    .unwrap();
+}
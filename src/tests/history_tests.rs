@@ -0,0 +1,219 @@
+// src/tests/history_tests.rs
+
+use crate::db::history;
+use crate::scraper::scraper::SearchKind;
+use crate::tests::utils::{
+    edited_fixtures, init_test_db, main_st_pending_without_page_two, query_i64, scrape_utah,
+    sweep_utah, FIXTURES, PAGE_ONE, PAGE_TWO,
+};
+
+#[test]
+fn typed_change_events_are_recorded_as_listings_change() {
+    let db = init_test_db();
+    let events = || {
+        db.with_conn(|conn| {
+            let rows = conn
+                .prepare(
+                    "SELECT p.address_line, e.event_type, e.previous_status, e.current_status,
+                            e.site
+                     FROM change_events e JOIN properties p ON p.id = e.property_id
+                     WHERE e.event_type != 'new_listing' ORDER BY e.id",
+                )?
+                .query_map([], |r| {
+                    Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?, r.get(4)?))
+                })?
+                .collect::<Result<Vec<(String, String, Option<String>, String, Option<String>)>, _>>()?;
+            Ok(rows)
+        })
+        .unwrap()
+    };
+
+    sweep_utah(&db, FIXTURES, SearchKind::ForSale);
+    assert_eq!(
+        query_i64(
+            &db,
+            "SELECT COUNT(*) FROM change_events WHERE event_type = 'new_listing'"
+        ),
+        5
+    );
+    assert!(events().is_empty());
+
+    sweep_utah(
+        &db,
+        main_st_pending_without_page_two("typed_events"),
+        SearchKind::ForSale,
+    );
+
+    let active = Some("Active".to_string());
+    let realtor = Some("realtor".to_string());
+    assert_eq!(
+        events(),
+        vec![
+            (
+                "123 N Main St".to_string(),
+                "went_pending".to_string(),
+                active.clone(),
+                "Pending".to_string(),
+                realtor.clone()
+            ),
+            (
+                "123 N Main St".to_string(),
+                "price_drop".to_string(),
+                active.clone(),
+                "Pending".to_string(),
+                realtor
+            ),
+            (
+                "4410 W Sunset Dr".to_string(),
+                "delisted".to_string(),
+                active.clone(),
+                "Off Market".to_string(),
+                None
+            ),
+            (
+                "18 Maple Ln".to_string(),
+                "delisted".to_string(),
+                active,
+                "Off Market".to_string(),
+                None
+            ),
+        ]
+    );
+}
+
+#[test]
+fn state_as_of_replays_history_in_the_order_it_was_observed() {
+    let db = init_test_db();
+    // The newer observation went in first, e.g. from an import of older pages.
+    db.with_conn(|conn| {
+        conn.execute_batch(
+            "INSERT INTO properties (id, address_line, city, postal_code, state_abbr, address_key,
+                                     status, list_price, first_seen_at, last_seen_at)
+             VALUES (1, '9 Canyon Rd', 'Provo', '84604', 'UT', '9 CANYON RD|PROVO|84604',
+                     'for_sale', 480000, '2025-03-01 09:00:00', '2025-03-10 09:00:00');
+             INSERT INTO property_history (id, property_id, observed_at, field_name, previous_value, current_value)
+             VALUES (1, 1, '2025-03-10 09:00:00', 'list_price', '500000', '480000'),
+                    (2, 1, '2025-03-01 09:00:00', 'status', NULL, 'for_sale'),
+                    (3, 1, '2025-03-01 09:00:00', 'list_price', NULL, '500000');",
+        )?;
+        Ok(())
+    })
+    .unwrap();
+
+    let at = |day: u32| {
+        chrono::NaiveDate::from_ymd_opt(2025, 3, day)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+    };
+    let price = |day| {
+        let rows = db
+            .with_conn(|conn| history::state_as_of(conn, "UT", at(day)))
+            .unwrap();
+        rows[0].snapshot.property.list_price
+    };
+    assert_eq!(price(5), Some(500_000));
+    assert_eq!(price(11), Some(480_000));
+}
+
+#[test]
+fn state_as_of_rebuilds_each_property_from_its_history() {
+    let db = init_test_db();
+    let as_of = |at: chrono::NaiveDateTime| {
+        db.with_conn(|conn| history::state_as_of(conn, "UT", at))
+            .unwrap()
+    };
+
+    let before = chrono::Utc::now().naive_utc();
+    sweep_utah(&db, FIXTURES, SearchKind::ForSale);
+    let listed = chrono::Utc::now().naive_utc();
+
+    let dir = edited_fixtures("as_of", &[PAGE_ONE, PAGE_TWO], |_, html| {
+        html.replacen(r#""list_price": 450000"#, r#""list_price": 439000"#, 1)
+            .replacen(r#""is_pending": null"#, r#""is_pending": true"#, 1)
+    });
+    sweep_utah(&db, &dir, SearchKind::ForSale);
+
+    assert!(as_of(before).is_empty());
+
+    let main_st = |rows: &[history::PropertyAsOf]| {
+        let row = rows
+            .iter()
+            .find(|r| r.address_line == "123 N Main St")
+            .unwrap();
+        (
+            row.snapshot.property.list_price,
+            row.snapshot.listing_state().canonical_status(),
+        )
+    };
+    let then = as_of(listed);
+    assert_eq!(then.len(), 5);
+    assert_eq!(main_st(&then), (Some(450_000), "Active"));
+
+    let latest = as_of(chrono::Utc::now().naive_utc());
+    assert_eq!(main_st(&latest), (Some(439_000), "Pending"));
+
+    // Replaying all of history lands on what's stored.
+    db.with_conn(|conn| {
+        for row in &latest {
+            let property = &row.snapshot.property;
+            let (list_price, is_pending, beds): (Option<i64>, Option<bool>, Option<i64>) = conn
+                .query_row(
+                    "SELECT list_price, is_pending, beds FROM properties WHERE id = ?",
+                    [property.id],
+                    |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)),
+                )?;
+            assert_eq!(
+                (property.list_price, property.is_pending, property.beds),
+                (list_price, is_pending, beds),
+                "{}",
+                row.address_line
+            );
+        }
+        Ok(())
+    })
+    .unwrap();
+}
+
+#[test]
+fn listing_attributes_are_stored_and_changes_to_them_are_tracked() {
+    let db = init_test_db();
+    scrape_utah(&db, FIXTURES, 1_700_000_000);
+
+    let (beds, sqft, kind, lat, fips): (i64, i64, String, f64, i64) = db
+        .with_conn(|conn| {
+            Ok(conn.query_row(
+                "SELECT beds, sqft, property_type, latitude, county_fips FROM properties
+                 WHERE address_line = '123 N Main St'",
+                [],
+                |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?, r.get(4)?)),
+            )?)
+        })
+        .unwrap();
+    assert_eq!((beds, sqft, kind.as_str()), (3, 1850, "single_family"));
+    assert_eq!((lat, fips), (40.6, 49035));
+
+    // A finished basement adds a bedroom to the first listing on page one.
+    let dir = edited_fixtures("attributes", &[PAGE_ONE, PAGE_TWO], |page, html| {
+        if page == PAGE_ONE {
+            html.replacen(r#""beds": 3"#, r#""beds": 4"#, 1)
+        } else {
+            html
+        }
+    });
+    scrape_utah(&db, &dir, 1_700_000_100);
+
+    let beds_history: Vec<(Option<String>, String)> = db
+        .with_conn(|conn| {
+            let rows = conn
+                .prepare(
+                    "SELECT previous_value, current_value FROM property_history
+                     WHERE field_name = 'beds' AND previous_value IS NOT NULL",
+                )?
+                .query_map([], |r| Ok((r.get(0)?, r.get(1)?)))?
+                .collect::<Result<_, _>>()?;
+            Ok(rows)
+        })
+        .unwrap();
+    assert_eq!(beds_history, vec![(Some("3".to_string()), "4".to_string())]);
+}
@@ -0,0 +1,225 @@
+// src/tests/market_stats_tests.rs
+
+use crate::db::market_stats;
+use crate::scraper::scraper::SearchKind;
+use crate::tests::utils::{init_test_db, main_st_pending_without_page_two, sweep_utah, FIXTURES};
+
+#[test]
+fn market_stats_sum_up_each_day_per_state_county_and_zip() {
+    let db = init_test_db();
+    sweep_utah(&db, FIXTURES, SearchKind::ForSale);
+    sweep_utah(
+        &db,
+        main_st_pending_without_page_two("market"),
+        SearchKind::ForSale,
+    );
+
+    let now = chrono::Utc::now().naive_utc();
+    let today = now.date();
+    db.with_conn(|conn| {
+        conn.execute(
+            "INSERT INTO property_history (property_id, observed_at, field_name, previous_value, current_value)
+             SELECT id, ?1, 'sold_date', NULL, ?2 FROM properties WHERE address_line = '123 N Main St'
+             UNION ALL
+             SELECT id, ?1, 'sold_price', NULL, '441000' FROM properties WHERE address_line = '123 N Main St'",
+            rusqlite::params![now, now.to_string()],
+        )?;
+        Ok(())
+    })
+    .unwrap();
+
+    let rows = db
+        .with_conn(|conn| market_stats::compute_market_stats(conn, today, today, now))
+        .unwrap();
+    assert!(rows > 1, "a state row plus county and ZIP rows");
+
+    let filter = |county: Option<&str>, zip: Option<&str>| market_stats::MarketStatsFilter {
+        state: "UT".to_string(),
+        county: county.map(str::to_string),
+        postal_code: zip.map(str::to_string),
+        from: Some(today),
+        to: Some(today),
+    };
+    let stats = |filter: market_stats::MarketStatsFilter| {
+        db.with_conn(|conn| market_stats::get_market_stats(conn, &filter))
+            .unwrap()
+    };
+
+    let state = stats(filter(None, None));
+    assert_eq!(state.len(), 1);
+    let s = &state[0].stats;
+    assert_eq!(
+        (s.active_inventory, s.new_listings, s.pendings, s.solds),
+        (2, 5, 1, 1)
+    );
+    assert_eq!(s.price_cut_share, Some(0.0), "the cut listing went pending");
+    assert_eq!(s.median_sold_price, Some(441000));
+    assert_eq!(s.sale_to_list_ratio, Some(441000.0 / 439000.0));
+
+    let (county, zip) = db
+        .with_conn(|conn| {
+            Ok(conn.query_row(
+                "SELECT county_name, postal_code FROM properties WHERE address_line = '123 N Main St'",
+                [],
+                |r| Ok((r.get::<_, String>(0)?, r.get::<_, String>(1)?)),
+            )?)
+        })
+        .unwrap();
+    assert_eq!(stats(filter(Some(&county), None))[0].stats.pendings, 1);
+    assert_eq!(stats(filter(None, Some(&zip)))[0].stats.solds, 1);
+
+    // Tomorrow's run fills in today, then has nothing left to do.
+    db.with_conn(|conn| Ok(conn.execute("DELETE FROM market_stats", [])?))
+        .unwrap();
+    let tomorrow = now + chrono::Duration::days(1);
+    let written = db
+        .with_conn(|conn| market_stats::refresh_market_stats(conn, tomorrow))
+        .unwrap();
+    assert_eq!(written, rows);
+    assert_eq!(
+        db.with_conn(|conn| market_stats::refresh_market_stats(conn, tomorrow))
+            .unwrap(),
+        0
+    );
+}
+
+#[test]
+fn market_stats_replay_each_day_from_change_events() {
+    let db = init_test_db();
+    db.with_conn(|conn| {
+        conn.execute_batch(
+            "INSERT INTO properties (id, address_line, city, postal_code, state_abbr, address_key,
+                                     status, list_price, first_seen_at, last_seen_at)
+             VALUES (1, '1 A St', 'Provo', '84604', 'UT', '1 A ST|PROVO|84604', 'for_sale',
+                     480000, '2025-03-01 09:00:00', '2025-03-05 09:00:00'),
+                    (2, '2 B St', 'Provo', '84604', 'UT', '2 B ST|PROVO|84604', 'for_sale',
+                     300000, '2025-03-02 09:00:00', '2025-03-05 09:00:00');
+             INSERT INTO change_events (property_id, observed_at, event_type, previous_status,
+                                        current_status, previous_price, current_price)
+             VALUES (1, '2025-03-01 09:00:00', 'new_listing', NULL, 'Active', NULL, 500000),
+                    (2, '2025-03-02 09:00:00', 'new_listing', NULL, 'Active', NULL, 300000),
+                    (1, '2025-03-03 09:00:00', 'price_drop', 'Active', 'Active', 500000, 480000),
+                    (1, '2025-03-04 09:00:00', 'delisted', 'Active', 'Off Market', 480000, 480000),
+                    (1, '2025-03-05 09:00:00', 'back_on_market', 'Off Market', 'Active', 480000, 480000);
+             INSERT INTO listing_cycles (property_id, cycle_number, listed_at, ended_at, outcome,
+                                         initial_price, last_price, price_cuts)
+             VALUES (1, 1, '2025-03-01 09:00:00', '2025-03-04 09:00:00', 'delisted', 500000, 480000, 1),
+                    (2, 1, '2025-03-02 09:00:00', NULL, NULL, 300000, 300000, 0),
+                    (1, 2, '2025-03-05 09:00:00', NULL, NULL, 480000, 480000, 0);",
+        )?;
+        Ok(())
+    })
+    .unwrap();
+
+    let day = |d: u32| chrono::NaiveDate::from_ymd_opt(2025, 3, d).unwrap();
+    let now = day(6).and_hms_opt(1, 0, 0).unwrap();
+    // Starting part-way through replays the earlier events first.
+    for from in [day(1), day(3)] {
+        db.with_conn(|conn| market_stats::compute_market_stats(conn, from, day(5), now))
+            .unwrap();
+    }
+
+    let rows = db
+        .with_conn(|conn| {
+            market_stats::get_market_stats(
+                conn,
+                &market_stats::MarketStatsFilter {
+                    state: "UT".into(),
+                    ..Default::default()
+                },
+            )
+        })
+        .unwrap();
+    let by_day: Vec<_> = rows
+        .iter()
+        .rev()
+        .map(|r| {
+            (
+                r.stat_date.format("%d").to_string(),
+                r.stats.active_inventory,
+                r.stats.new_listings,
+                r.stats.median_list_price,
+                r.stats.price_cut_share,
+            )
+        })
+        .collect();
+    let row =
+        |d: &str, active, new, median, cut| (d.to_string(), active, new, Some(median), Some(cut));
+    assert_eq!(
+        by_day,
+        vec![
+            row("01", 1, 1, 500000, 0.0),
+            row("02", 2, 1, 400000, 0.0),
+            row("03", 2, 0, 390000, 0.5),
+            row("04", 1, 0, 300000, 0.0),
+            // The relist starts a new cycle, so the earlier cut no longer counts.
+            row("05", 2, 1, 390000, 0.0),
+        ]
+    );
+}
+
+#[test]
+fn market_stats_count_every_sale_at_the_list_price_it_sold_from() {
+    let db = init_test_db();
+    db.with_conn(|conn| {
+        conn.execute_batch(
+            "INSERT INTO properties (id, address_line, city, postal_code, state_abbr, address_key,
+                                     status, list_price, sold_price, sold_date,
+                                     first_seen_at, last_seen_at)
+             VALUES (1, '1 A St', 'Provo', '84604', 'UT', '1 A ST|PROVO|84604', 'sold',
+                     450000, 440000, '2025-03-05 00:00:00',
+                     '2025-03-01 09:00:00', '2025-03-05 09:00:00');
+             INSERT INTO property_history (property_id, observed_at, field_name, previous_value, current_value)
+             VALUES (1, '2025-03-01 09:00:00', 'list_price', NULL, '400000'),
+                    (1, '2025-03-02 09:00:00', 'sold_date', NULL, '2025-03-02 00:00:00'),
+                    (1, '2025-03-02 09:00:00', 'sold_price', NULL, '410000'),
+                    -- Relisted for more, then sold again.
+                    (1, '2025-03-04 09:00:00', 'list_price', '400000', '450000'),
+                    (1, '2025-03-04 09:00:00', 'sold_date', '2025-03-02 00:00:00', ''),
+                    (1, '2025-03-04 09:00:00', 'sold_price', '410000', ''),
+                    (1, '2025-03-05 09:00:00', 'sold_date', NULL, '2025-03-04 00:00:00'),
+                    (1, '2025-03-05 09:00:00', 'sold_price', NULL, '440000'),
+                    -- The second sale's date is corrected a day later.
+                    (1, '2025-03-06 09:00:00', 'sold_date', '2025-03-04 00:00:00', '2025-03-05 00:00:00');",
+        )?;
+        Ok(())
+    })
+    .unwrap();
+
+    let day = |d: u32| chrono::NaiveDate::from_ymd_opt(2025, 3, d).unwrap();
+    let now = day(7).and_hms_opt(1, 0, 0).unwrap();
+    db.with_conn(|conn| market_stats::compute_market_stats(conn, day(1), day(6), now))
+        .unwrap();
+
+    let rows = db
+        .with_conn(|conn| {
+            market_stats::get_market_stats(
+                conn,
+                &market_stats::MarketStatsFilter {
+                    state: "UT".into(),
+                    ..Default::default()
+                },
+            )
+        })
+        .unwrap();
+    let sales: Vec<_> = rows
+        .iter()
+        .rev()
+        .filter(|r| r.stats.solds > 0)
+        .map(|r| {
+            (
+                r.stat_date.format("%d").to_string(),
+                r.stats.solds,
+                r.stats.median_sold_price,
+                r.stats.sale_to_list_ratio,
+            )
+        })
+        .collect();
+    assert_eq!(
+        sales,
+        vec![
+            ("02".to_string(), 1, Some(410000), Some(410000.0 / 400000.0)),
+            ("05".to_string(), 1, Some(440000), Some(440000.0 / 450000.0)),
+        ]
+    );
+}
@@ -1,4 +1,13 @@
 // tests/mod.rs
 
+pub mod cycles_tests;
+pub mod delisting_tests;
+pub mod duplicates_tests;
+pub mod history_tests;
+pub mod market_stats_tests;
+pub mod open_houses_tests;
+pub mod quarantine_tests;
 pub mod router_tests; // points to tests/router_tests/mod.rs
+pub mod scheduler_tests;
+pub mod scraper_tests;
 pub mod utils; // points to tests/utils.rs
@@ -0,0 +1,78 @@
+// src/tests/open_houses_tests.rs
+
+use crate::db::open_houses::{self, OpenHouseFilter};
+use crate::tests::utils::{
+    edited_fixtures, init_test_db, scrape_utah, FIXTURES, PAGE_ONE, PAGE_TWO,
+};
+
+#[test]
+fn open_houses_are_tracked_and_cancelled_ones_flagged_in_the_feed() {
+    let db = init_test_db();
+
+    // The first listing on page one advertises a Saturday open house.
+    let dir = edited_fixtures("open_houses", &[PAGE_ONE, PAGE_TWO], |page, html| {
+        if page != PAGE_ONE {
+            return html;
+        }
+        html.replacen(
+            r#""open_houses": null"#,
+            r#""open_houses": [{"start_date": "2099-10-17T12:00:00", "end_date": "2099-10-17T14:00:00", "time_zone": "MST"}]"#,
+            1,
+        )
+    });
+    scrape_utah(&db, &dir, 1_700_000_000);
+    // The next sweep no longer shows it.
+    scrape_utah(&db, FIXTURES, 1_700_000_100);
+
+    let history: Vec<(Option<String>, String)> = db
+        .with_conn(|conn| {
+            let rows = conn
+                .prepare(
+                    "SELECT previous_value, current_value FROM property_history
+                     WHERE field_name = 'open_house' ORDER BY id",
+                )?
+                .query_map([], |r| Ok((r.get(0)?, r.get(1)?)))?
+                .collect::<Result<_, _>>()?;
+            Ok(rows)
+        })
+        .unwrap();
+    assert_eq!(
+        history,
+        vec![
+            (None, "2099-10-17 12:00-14:00".to_string()),
+            (
+                Some("2099-10-17 12:00-14:00".to_string()),
+                "cancelled".to_string()
+            ),
+        ]
+    );
+
+    let filter = OpenHouseFilter {
+        county: Some("Salt Lake County".into()),
+        ..Default::default()
+    };
+    let from = chrono::NaiveDate::from_ymd_opt(2099, 1, 1).unwrap();
+    let events = db
+        .with_conn(|conn| open_houses::upcoming_open_houses(conn, &filter, from))
+        .unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].address_line, "123 N Main St");
+    assert!(events[0].cancelled_at.is_some());
+
+    // Still listed on its own day, even once that day's UTC clock has moved on.
+    let after_it_started = chrono::NaiveDate::from_ymd_opt(2099, 10, 18)
+        .unwrap()
+        .and_hms_opt(3, 0, 0)
+        .unwrap();
+    let today = open_houses::earliest_local_date(after_it_started);
+    assert_eq!(
+        db.with_conn(|conn| open_houses::upcoming_open_houses(conn, &filter, today))
+            .unwrap()
+            .len(),
+        1
+    );
+
+    let ics = crate::calendar::open_houses_ics(&events, from.and_hms_opt(0, 0, 0).unwrap());
+    assert!(ics.contains("DTSTART:20991017T120000\r\n"));
+    assert!(ics.contains("STATUS:CANCELLED\r\n"));
+}
@@ -0,0 +1,167 @@
+// src/tests/quarantine_tests.rs
+
+use crate::db::archive;
+use crate::db::quarantine;
+use crate::scraper::scraper::{ReprocessSummary, SearchKind};
+use crate::scraper::RealtorScraper;
+use crate::tests::utils::{
+    edited_fixtures, init_test_db, query_i64, scrape_utah, sweep_utah, tmp_dir, FIXTURES, PAGE_ONE,
+    UTAH_URL,
+};
+
+#[test]
+fn bad_listings_are_quarantined_without_losing_the_rest_of_the_page() {
+    let listing = |id: &str, line: &str, price: &str, zip: &str| {
+        format!(
+            r#"{{"status": "for_sale", "list_price": {price}, "source": {{"id": "SAUT", "name": "UtahRealEstate", "listing_id": "{id}"}}, "location": {{"address": {{"line": "{line}", "city": "Provo", "state_code": "UT", "postal_code": "{zip}"}}, "county": {{"name": "Utah", "fips_code": "49049"}}}}}}"#
+        )
+    };
+    let page = format!(
+        r#"<html><body><script id="__NEXT_DATA__" type="application/json">{{"props": {{"pageProps": {{"properties": [{}, {}, {}], "totalProperties": 3}}}}}}</script></body></html>"#,
+        listing("3100001", "1 Good St", "400000", "84604"),
+        listing("3100002", "2 Bad Price St", r#""call for price""#, "84604"),
+        listing("3100003", "3 No Zip St", "410000", ""),
+    );
+    let dir = tmp_dir("quarantine");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join(PAGE_ONE), page).unwrap();
+
+    let db = init_test_db();
+    let run_id = scrape_utah(&db, &dir, 1_700_000_000);
+
+    let records = db
+        .with_conn(|conn| quarantine::list_open_quarantine(conn, 10))
+        .unwrap();
+    let stages: Vec<&str> = records.iter().map(|r| r.stage.as_str()).collect();
+    assert_eq!(stages, vec!["validate", "deserialize"]);
+    assert!(records.iter().all(|r| r.scrape_run_id == Some(run_id)));
+    assert!(records[1].raw_json.contains("call for price"));
+
+    // Once the record is fixable it saves and leaves quarantine; the other
+    // still fails and stays.
+    let no_zip = &records[0];
+    db.with_conn(|conn| {
+        conn.execute(
+            "UPDATE scrape_quarantine SET raw_json = replace(raw_json, '\"postal_code\":\"\"', '\"postal_code\":\"84604\"') WHERE id = ?",
+            [no_zip.id],
+        )?;
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(
+        quarantine::reprocess_all_quarantined(&db, 1_700_000_100).unwrap(),
+        (1, 1)
+    );
+
+    assert_eq!(query_i64(&db, "SELECT COUNT(*) FROM properties"), 2);
+    assert_eq!(
+        db.with_conn(|conn| quarantine::count_open_quarantine(conn))
+            .unwrap(),
+        1
+    );
+}
+
+#[test]
+fn archived_pages_can_be_replayed_into_an_empty_database() {
+    let db = init_test_db();
+    let run_id = scrape_utah(&db, FIXTURES, 1_700_000_000);
+
+    let (keys, page_one) = db
+        .with_conn(|conn| {
+            Ok((
+                archive::archived_page_keys(conn, None)?,
+                archive::get_archived_page(conn, run_id, 1)?.unwrap(),
+            ))
+        })
+        .unwrap();
+    assert_eq!(keys, vec![(run_id, 1), (run_id, 2)]);
+    assert_eq!(page_one.page_url, UTAH_URL);
+    assert!(page_one.next_data.starts_with(r#"{"props""#));
+
+    // Forget everything the scrape saved, then rebuild it from the archive.
+    db.with_conn(|conn| {
+        conn.execute_batch("DELETE FROM property_sources; DELETE FROM properties;")?;
+        Ok(())
+    })
+    .unwrap();
+
+    let summary = RealtorScraper::reprocess_archive(&db, None).unwrap();
+    assert_eq!(
+        summary,
+        ReprocessSummary {
+            pages: 2,
+            new: 5,
+            changed: 0,
+            quarantined: 0
+        }
+    );
+
+    db.with_conn(|conn| {
+        let observed: Vec<String> = conn
+            .prepare("SELECT DISTINCT observed_at FROM property_history")?
+            .query_map([], |r| r.get(0))?
+            .collect::<Result<_, _>>()?;
+        assert_eq!(observed.len(), 1, "history is stamped with the fetch time");
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(query_i64(&db, "SELECT COUNT(*) FROM properties"), 5);
+}
+
+#[test]
+fn replaying_the_archive_over_later_observations_changes_nothing() {
+    let db = init_test_db();
+    sweep_utah(&db, FIXTURES, SearchKind::ForSale);
+
+    // The next sweep cuts a price and drops page two, delisting its homes.
+    let dir = edited_fixtures("replay_later", &[PAGE_ONE], |_, html| {
+        html.replace(r#""totalProperties": 5"#, r#""totalProperties": 3"#)
+            .replace(r#""list_price": 450000"#, r#""list_price": 435000"#)
+    });
+    sweep_utah(&db, &dir, SearchKind::ForSale);
+
+    let snapshot = || {
+        db.with_conn(|conn| {
+            let rows = |sql: &str| -> rusqlite::Result<Vec<String>> {
+                conn.prepare(sql)?.query_map([], |r| r.get(0))?.collect()
+            };
+            Ok((
+                rows(
+                    "SELECT property_id || ' ' || observed_at || ' ' || field_name || ' '
+                            || IFNULL(previous_value, '') || ' ' || IFNULL(current_value, '')
+                     FROM property_history ORDER BY id",
+                )?,
+                rows(
+                    "SELECT property_id || ' ' || observed_at || ' ' || event_type
+                     FROM change_events ORDER BY id",
+                )?,
+                rows(
+                    "SELECT address_line || ' ' || IFNULL(list_price, '') || ' '
+                            || IFNULL(delisted_at, '') || ' ' || last_seen_at
+                     FROM properties ORDER BY id",
+                )?,
+                rows("SELECT COUNT(*) || '' FROM scrape_quarantine")?,
+            ))
+        })
+        .unwrap()
+    };
+    let before = snapshot();
+    assert!(before
+        .2
+        .iter()
+        .any(|row| row.starts_with("123 N Main St 435000 ")));
+    assert_eq!(
+        before.1.iter().filter(|e| e.ends_with(" delisted")).count(),
+        2
+    );
+
+    let summary = RealtorScraper::reprocess_archive(&db, None).unwrap();
+    assert_eq!(
+        summary,
+        ReprocessSummary {
+            pages: 3,
+            ..Default::default()
+        }
+    );
+    assert_eq!(snapshot(), before);
+}
@@ -68,6 +68,46 @@ fn admin_page_loads_for_authenticated_user() {
     assert!(body.contains("admin@example.com")); // User should be in the table
 }
 
+fn admin_page_body(db: &crate::db::connection::Database, session_token: &str) -> String {
+    let req = Request::builder()
+        .method(Method::GET)
+        .uri("/admin")
+        .header("Cookie", format!("session={}", session_token))
+        .body(Body::empty())
+        .unwrap();
+    let resp = handle(req, db).expect("Handler failed");
+    assert_eq!(resp.status(), 200);
+    let mut body = String::new();
+    resp.into_body().reader().read_to_string(&mut body).unwrap();
+    body
+}
+
+#[test]
+fn county_and_zip_shards_are_disabled_until_geo_data_is_loaded() {
+    let db = init_test_db();
+    let (_, session_token) = create_authenticated_user(&db);
+
+    let body = admin_page_body(&db, &session_token);
+    assert!(body.contains("GEO_DATA_DIR"));
+    assert!(body.contains(r#"<option value="county" disabled>Split by county</option>"#));
+    assert!(body.contains(r#"<option value="zip" disabled>Split by ZIP</option>"#));
+
+    db.with_conn(|conn| {
+        conn.execute_batch(
+            "INSERT INTO county (geoid, state_fips, county_fips, name) VALUES ('49035', '49', '035', 'Salt Lake County');
+             INSERT INTO zcta (zcta) VALUES ('84101');
+             INSERT INTO zcta_county (zcta, county_geoid) VALUES ('84101', '49035');",
+        )
+        .map_err(|e| crate::errors::ServerError::DbError(e.to_string()))
+    })
+    .unwrap();
+
+    let body = admin_page_body(&db, &session_token);
+    assert!(!body.contains("GEO_DATA_DIR"));
+    assert!(body.contains(r#"<option value="county">Split by county</option>"#));
+    assert!(body.contains(r#"<option value="zip">Split by ZIP</option>"#));
+}
+
 #[test]
 fn admin_can_reset_usage() {
     let db = init_test_db();
@@ -106,3 +146,137 @@ fn admin_can_reset_usage() {
         .unwrap();
     assert_eq!(usage_after, 0, "Usage should be 0 after reset");
 }
+
+#[test]
+fn admin_can_create_and_pause_a_schedule() {
+    let db = init_test_db();
+    let (_, session_token) = create_authenticated_user(&db);
+
+    let req = Request::builder()
+        .method(Method::POST)
+        .uri("/admin/schedules")
+        .header("Cookie", format!("session={}", session_token))
+        .body(Body::from(
+            "state=UT&shard_by=county&mode=incremental&stop_after=30&cadence_hours=24",
+        ))
+        .unwrap();
+    let resp = handle(req, &db).expect("Handler failed");
+    assert_eq!(resp.status(), 302);
+
+    let schedules = db
+        .with_conn(|conn| crate::db::schedules::list_schedules(conn))
+        .unwrap();
+    assert_eq!(schedules.len(), 1);
+    let schedule = &schedules[0];
+    assert_eq!(schedule.state, "UT");
+    assert_eq!(schedule.shard_by, "county");
+    assert_eq!(schedule.stop_after, Some(30));
+    assert!(schedule.enabled);
+
+    let req = Request::builder()
+        .method(Method::POST)
+        .uri(format!("/admin/schedules/{}/toggle", schedule.id))
+        .header("Cookie", format!("session={}", session_token))
+        .body(Body::empty())
+        .unwrap();
+    handle(req, &db).expect("Handler failed");
+
+    let schedule = db
+        .with_conn(|conn| crate::db::schedules::get_schedule(conn, schedule.id))
+        .unwrap()
+        .unwrap();
+    assert!(!schedule.enabled);
+
+    let req = Request::builder()
+        .method(Method::GET)
+        .uri("/admin")
+        .header("Cookie", format!("session={}", session_token))
+        .body(Body::empty())
+        .unwrap();
+    let resp = handle(req, &db).expect("Handler failed");
+    let mut body = String::new();
+    resp.into_body().reader().read_to_string(&mut body).unwrap();
+    assert!(body.contains("Paused"));
+}
+
+#[test]
+fn admin_can_merge_a_duplicate_pair_and_the_merge_is_audited() {
+    let db = init_test_db();
+    let (user_id, session_token) = create_authenticated_user(&db);
+    db.with_conn(|conn| {
+        conn.execute_batch(
+            "INSERT INTO properties (id, address_line, city, postal_code, address_key, first_seen_at, last_seen_at)
+             VALUES (1, '9 Canyon Rd', 'Provo', '84604', '9 CANYON RD|PROVO|84604', '2024-01-01 00:00:00', '2024-01-01 00:00:00'),
+                    (2, '9 Canyon Rd Unit A', 'Provo', '84604', '9 CANYON RD # A|PROVO|84604', '2024-02-01 00:00:00', '2024-02-01 00:00:00');
+             INSERT INTO property_history (property_id, observed_at, field_name, current_value)
+             VALUES (2, '2024-02-01 00:00:00', 'status', 'for_sale');
+             INSERT INTO property_sources (property_id, source_name, source_listing_id, first_seen_at, last_seen_at)
+             VALUES (2, 'realtor', 'M-2', '2024-02-01 00:00:00', '2024-02-01 00:00:00');",
+        )?;
+        Ok(())
+    })
+    .unwrap();
+
+    let post = |uri: String, body: &'static str| {
+        let req = Request::builder()
+            .method(Method::POST)
+            .uri(uri)
+            .header("Cookie", format!("session={}", session_token))
+            .body(Body::from(body))
+            .unwrap();
+        handle(req, &db).expect("Handler failed")
+    };
+    assert_eq!(post("/admin/duplicates/scan".into(), "").status(), 302);
+
+    let req = Request::builder()
+        .method(Method::GET)
+        .uri("/admin/duplicates")
+        .header("Cookie", format!("session={}", session_token))
+        .body(Body::empty())
+        .unwrap();
+    let mut body = String::new();
+    handle(req, &db)
+        .expect("Handler failed")
+        .into_body()
+        .reader()
+        .read_to_string(&mut body)
+        .unwrap();
+    assert!(body.contains("9 Canyon Rd Unit A, Provo 84604"));
+    assert!(body.contains("same street address"));
+
+    let candidate = db
+        .with_conn(|conn| crate::db::duplicates::list_open_candidates(conn, 10))
+        .unwrap()
+        .remove(0);
+    assert_eq!(
+        post(
+            format!("/admin/duplicates/{}/merge", candidate.id),
+            "keep=1"
+        )
+        .status(),
+        302
+    );
+
+    db.with_conn(|conn| {
+        let remaining: i64 = conn.query_row("SELECT COUNT(*) FROM properties", [], |r| r.get(0))?;
+        assert_eq!(remaining, 1);
+        let moved: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM property_history WHERE property_id = 1",
+            [],
+            |r| r.get(0),
+        )?;
+        assert_eq!(moved, 1);
+        Ok(())
+    })
+    .unwrap();
+
+    let merges = db
+        .with_conn(|conn| crate::db::duplicates::list_recent_merges(conn, 10))
+        .unwrap();
+    assert_eq!(merges.len(), 1);
+    assert_eq!(merges[0].surviving_property_id, 1);
+    assert_eq!(merges[0].merged_property_id, 2);
+    assert_eq!(merges[0].merged_address, "9 Canyon Rd Unit A, Provo 84604");
+    assert_eq!((merges[0].history_moved, merges[0].sources_moved), (1, 1));
+    assert_eq!(merges[0].merged_by, Some(user_id));
+}
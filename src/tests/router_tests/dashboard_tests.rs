@@ -90,3 +90,170 @@ fn dashboard_redirects_without_session() {
     let location = resp.headers().get("Location").unwrap().to_str().unwrap();
     assert_eq!(location, "/", "Should redirect to home");
 }
+
+#[test]
+fn calendar_feed_is_served_only_for_the_current_token() {
+    let db = make_db();
+    let now = now_unix();
+    let issued = request_magic_link(&db, "calendar_user@example.com", now).unwrap();
+    let redeemed = redeem_magic_link(&db, &issued.token, now).unwrap();
+
+    db.with_conn(|conn| {
+        conn.execute_batch(
+            "INSERT INTO properties (id, address_line, city, postal_code, state_abbr, address_key,
+                                     first_seen_at, last_seen_at)
+             VALUES (1, '9 Canyon Rd', 'Sandy', '84092', 'UT', '9 CANYON RD|SANDY|84092',
+                     '2025-01-01 00:00:00', '2025-01-01 00:00:00');
+             INSERT INTO property_open_houses (property_id, starts_at, ends_at, time_zone,
+                                               first_seen_at, last_seen_at)
+             VALUES (1, '2099-10-17 12:00:00', '2099-10-17 14:00:00', 'MST',
+                     '2025-01-01 00:00:00', '2025-01-01 00:00:00');",
+        )?;
+        Ok(())
+    })
+    .unwrap();
+
+    let feed = |token: &str| {
+        let req = Request::builder()
+            .method(Method::GET)
+            .uri(format!("/calendar/{token}.ics?state=UT"))
+            .body(Body::empty())
+            .unwrap();
+        handle(req, &db)
+    };
+    let body = |mut resp: astra::Response| {
+        let mut body = String::new();
+        resp.body_mut().reader().read_to_string(&mut body).unwrap();
+        body
+    };
+
+    let rotate = |area: &str| {
+        db.with_conn(|conn| {
+            crate::db::open_houses::rotate_feed_token(conn, redeemed.user_id, area, now)
+        })
+        .unwrap()
+    };
+    let idaho = rotate("state=ID");
+    let old = rotate("state=UT");
+    let current = rotate("state=UT");
+
+    let resp = feed(&current).expect("Handler failed");
+    assert_eq!(resp.status(), 200);
+    let content_type = resp
+        .headers()
+        .get("Content-Type")
+        .unwrap()
+        .to_str()
+        .unwrap();
+    assert!(content_type.starts_with("text/calendar"));
+    let ut_body = body(resp);
+    assert!(ut_body.starts_with("BEGIN:VCALENDAR\r\n"));
+    assert!(ut_body.contains("9 Canyon Rd"));
+
+    assert!(feed(&old).is_err(), "a replaced link stops working");
+    let resp = feed(&idaho).expect("Handler failed");
+    assert_eq!(resp.status(), 200, "another area's link keeps working");
+    assert!(
+        !body(resp).contains("9 Canyon Rd"),
+        "a link only serves the area it was made for, whatever its query string says"
+    );
+    assert!(feed("not-a-token").is_err());
+}
+
+#[test]
+fn property_page_shows_sources_history_and_price_chart() {
+    use crate::scraper::fetcher::FileFetcher;
+    use crate::scraper::shards::ScrapeShard;
+    use crate::scraper::RealtorScraper;
+
+    let db = make_db();
+    let now = now_unix();
+    let issued = request_magic_link(&db, "property_user@example.com", now).unwrap();
+    let redeemed = redeem_magic_link(&db, &issued.token, now).unwrap();
+    let session_token = db
+        .with_conn(|conn| sessions::create_session(conn, redeemed.user_id, now))
+        .unwrap();
+
+    RealtorScraper::with_fetcher(Box::new(FileFetcher::new("src/tests/fixtures/realtor")))
+        .run_sweep(&db, vec![ScrapeShard::state("UT", "Utah")]);
+    let id: i64 = db
+        .with_conn(|conn| {
+            Ok(conn.query_row(
+                "SELECT id FROM properties WHERE address_line = '123 N Main St'",
+                [],
+                |r| r.get(0),
+            )?)
+        })
+        .unwrap();
+
+    let get = |uri: String, session: Option<&str>| {
+        let mut req = Request::builder().method(Method::GET).uri(uri);
+        if let Some(token) = session {
+            req = req.header("Cookie", format!("session={}", token));
+        }
+        handle(req.body(Body::empty()).unwrap(), &db)
+    };
+
+    let mut resp = get(format!("/properties/{id}"), Some(&session_token)).expect("Handler failed");
+    assert_eq!(resp.status(), 200);
+    let mut body = String::new();
+    resp.body_mut().reader().read_to_string(&mut body).unwrap();
+    assert!(body.contains("123 N Main St"));
+    assert!(body.contains("<svg"), "the price chart is inline SVG");
+    assert!(body.contains("$450,000"));
+    assert!(body.contains("List price"), "the timeline lists each field");
+    assert!(body.contains("realtor"));
+
+    assert!(get("/properties/999999".to_string(), Some(&session_token)).is_err());
+    let resp = get(format!("/properties/{id}"), None).expect("Handler failed");
+    assert_eq!(resp.status(), 302);
+}
+
+#[test]
+fn market_page_filters_stats_by_area() {
+    use crate::scraper::fetcher::FileFetcher;
+    use crate::scraper::shards::ScrapeShard;
+    use crate::scraper::RealtorScraper;
+
+    let db = make_db();
+    let now = now_unix();
+    let issued = request_magic_link(&db, "market_user@example.com", now).unwrap();
+    let redeemed = redeem_magic_link(&db, &issued.token, now).unwrap();
+    let session_token = db
+        .with_conn(|conn| sessions::create_session(conn, redeemed.user_id, now))
+        .unwrap();
+
+    RealtorScraper::with_fetcher(Box::new(FileFetcher::new("src/tests/fixtures/realtor")))
+        .run_sweep(&db, vec![ScrapeShard::state("UT", "Utah")]);
+    let today = chrono::Utc::now().naive_utc();
+    db.with_conn(|conn| {
+        crate::db::market_stats::compute_market_stats(conn, today.date(), today.date(), today)
+    })
+    .unwrap();
+
+    let get = |uri: &str, session: Option<&str>| {
+        let mut req = Request::builder().method(Method::GET).uri(uri);
+        if let Some(token) = session {
+            req = req.header("Cookie", format!("session={}", token));
+        }
+        handle(req.body(Body::empty()).unwrap(), &db)
+    };
+    let body = |uri: &str| {
+        let mut resp = get(uri, Some(&session_token)).expect("Handler failed");
+        assert_eq!(resp.status(), 200);
+        let mut body = String::new();
+        resp.body_mut().reader().read_to_string(&mut body).unwrap();
+        body
+    };
+
+    let state = body("/market?state=ut");
+    assert!(state.contains("Market Trends"));
+    assert!(state.contains(&today.format("%Y-%m-%d").to_string()));
+    assert!(state.contains("Sale-to-List"));
+
+    let zip = body("/market?state=UT&zip=00000");
+    assert!(zip.contains("No market stats for ZIP 00000"));
+
+    assert!(get("/market?state=UT&from=yesterday", Some(&session_token)).is_err());
+    assert_eq!(get("/market", None).expect("Handler failed").status(), 302);
+}
@@ -0,0 +1,115 @@
+// src/tests/scheduler_tests.rs
+
+use crate::db::schedules::{self, ScheduleForm};
+use crate::db::scrapes;
+use crate::scraper::jobs::JobRegistry;
+use crate::scraper::scheduler::run_due_schedules;
+use crate::tests::utils::{init_test_db, utah_run};
+
+/// A daily incremental schedule for `state`.
+fn daily(state: &str, shard_by: &str) -> ScheduleForm {
+    ScheduleForm {
+        state: state.into(),
+        shard_by: shard_by.into(),
+        mode: "incremental".into(),
+        stop_after: Some(25),
+        search_kind: "for_sale".into(),
+        cadence_hours: 24,
+    }
+}
+
+#[test]
+fn scheduler_skips_states_with_a_run_in_progress() {
+    let db = init_test_db();
+    let now = 1_700_000_000;
+    let (ut, _) = db
+        .with_conn(|conn| {
+            let ut = schedules::create_schedule(conn, &daily("UT", "state"), now - 60, now - 60)?;
+            let id = schedules::create_schedule(conn, &daily("ID", "state"), now + 3600, now)?;
+            Ok((ut, id))
+        })
+        .unwrap();
+
+    // UT is due but still has a run open; ID isn't due yet.
+    let run_id = db
+        .with_conn(|conn| scrapes::start_scrape_run(conn, &utah_run(), now - 600))
+        .unwrap();
+    let registry = Box::leak(Box::new(JobRegistry::new(2)));
+    let launched = run_due_schedules(&db, registry, now).unwrap();
+    assert!(launched.is_empty());
+
+    db.with_conn(|conn| scrapes::end_scrape_run(conn, run_id, now, 1, 3, true, None))
+        .unwrap();
+    let due = db
+        .with_conn(|conn| schedules::due_schedules(conn, now))
+        .unwrap();
+    assert_eq!(due.iter().map(|s| s.id).collect::<Vec<_>>(), vec![ut]);
+
+    db.with_conn(|conn| schedules::mark_schedule_run(conn, ut, now))
+        .unwrap();
+    let schedule = db
+        .with_conn(|conn| schedules::get_schedule(conn, ut))
+        .unwrap()
+        .unwrap();
+    assert_eq!(schedule.last_run_at, Some(now));
+    assert_eq!(schedule.next_run_at, now + 24 * 3600);
+}
+
+#[test]
+fn a_schedule_refused_a_job_slot_stays_due() {
+    let db = init_test_db();
+    let now = 1_700_000_000;
+    let ut = db
+        .with_conn(|conn| {
+            schedules::create_schedule(conn, &daily("UT", "state"), now - 60, now - 60)
+        })
+        .unwrap();
+
+    // Every slot is taken by scrapes of other states.
+    let registry = Box::leak(Box::new(JobRegistry::new(2)));
+    let _id = registry.start("ID", now).unwrap();
+    let _nv = registry.start("NV", now).unwrap();
+
+    let launched = run_due_schedules(&db, registry, now).unwrap();
+    assert!(launched.is_empty());
+
+    let schedule = db
+        .with_conn(|conn| schedules::get_schedule(conn, ut))
+        .unwrap()
+        .unwrap();
+    assert_eq!(schedule.last_run_at, None);
+    assert_eq!(schedule.next_run_at, now - 60);
+    let due = db
+        .with_conn(|conn| schedules::due_schedules(conn, now + 60))
+        .unwrap();
+    assert_eq!(due.iter().map(|s| s.id).collect::<Vec<_>>(), vec![ut]);
+}
+
+#[test]
+fn a_schedule_that_cannot_start_records_why_and_waits_for_its_next_slot() {
+    let db = init_test_db();
+    let now = 1_700_000_000;
+    // No Census files are loaded, so county shards can't be planned.
+    let ut = db
+        .with_conn(|conn| {
+            schedules::create_schedule(conn, &daily("UT", "county"), now - 60, now - 60)
+        })
+        .unwrap();
+
+    let registry = Box::leak(Box::new(JobRegistry::new(2)));
+    assert!(run_due_schedules(&db, registry, now).unwrap().is_empty());
+
+    let schedule = db
+        .with_conn(|conn| schedules::get_schedule(conn, ut))
+        .unwrap()
+        .unwrap();
+    assert_eq!(schedule.last_run_at, None);
+    assert_eq!(schedule.last_attempt_at, Some(now));
+    assert!(schedule.last_error.is_some());
+    assert_eq!(schedule.next_run_at, now + 24 * 3600);
+    assert!(db
+        .with_conn(|conn| schedules::due_schedules(conn, now + 60))
+        .unwrap()
+        .is_empty());
+    assert!(registry.snapshot().is_empty());
+}
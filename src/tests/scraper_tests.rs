@@ -0,0 +1,650 @@
+// src/tests/scraper_tests.rs
+
+use crate::db::properties::save_scraped_properties;
+use crate::db::scrapes;
+use crate::geos;
+use crate::scraper::cassette::{RecordedError, RecordingFetcher, ReplayFetcher};
+use crate::scraper::config::ScraperConfig;
+use crate::scraper::fetcher::{FetchedPage, FileFetcher, PageFetcher};
+use crate::scraper::jobs::JobRegistry;
+use crate::scraper::scraper::{PageControl, PageEvent, ScrapeMode, SearchOutcome, SearchStart};
+use crate::scraper::shards::{plan_shards, ScrapeShard, ShardBy};
+use crate::scraper::{RealtorScraper, ScraperError};
+use crate::tests::utils::{
+    edited_fixtures, init_test_db, query_i64, scrape_utah, tmp_dir, utah_run, FIXTURES,
+    GEO_FIXTURES, PAGE_ONE, PAGE_TWO, UTAH_URL,
+};
+use std::path::PathBuf;
+
+fn enriching() -> ScraperConfig {
+    ScraperConfig {
+        enrich_details: true,
+        ..Default::default()
+    }
+}
+
+/// Records the fixture pages into a fresh cassette directory.
+fn record_fixture_cassette() -> PathBuf {
+    let recorder =
+        RecordingFetcher::new(Box::new(FileFetcher::new(FIXTURES)), tmp_dir("cassette")).unwrap();
+    let dir = recorder.dir().to_path_buf();
+    let scraper = RealtorScraper::with_fetcher(Box::new(recorder));
+    scraper
+        .fetch_all_properties_paginated(UTAH_URL, SearchStart::default(), |_| {
+            Ok(PageControl::Continue)
+        })
+        .expect("recording should succeed");
+    dir
+}
+
+/// Serves the fixtures but reports missing pages as `NotFound`, the way
+/// ZenRows does.
+struct NotFoundAsError(FileFetcher);
+
+impl PageFetcher for NotFoundAsError {
+    fn fetch(&self, url: &str) -> Result<FetchedPage, ScraperError> {
+        let page = self.0.fetch(url)?;
+        if page.status == 404 {
+            return Err(ScraperError::NotFound(url.to_string()));
+        }
+        Ok(page)
+    }
+
+    fn name(&self) -> &'static str {
+        "not-found-as-error"
+    }
+
+    fn is_remote(&self) -> bool {
+        false
+    }
+}
+
+#[test]
+fn file_fetcher_drives_full_pagination_offline() {
+    let scraper = RealtorScraper::with_fetcher(Box::new(FileFetcher::new(FIXTURES)));
+
+    let mut pages = Vec::new();
+    scraper
+        .fetch_all_properties_paginated(UTAH_URL, SearchStart::default(), |event| {
+            if let PageEvent::Fetched { properties, .. } = event {
+                pages.push(properties.len());
+            }
+            Ok(PageControl::Continue)
+        })
+        .expect("offline scrape should succeed");
+
+    // Two fixture pages; the missing third page ends the run.
+    assert_eq!(pages, vec![3, 2]);
+}
+
+#[test]
+fn a_not_found_error_ends_pagination_like_a_404_page() {
+    let scraper =
+        RealtorScraper::with_fetcher(Box::new(NotFoundAsError(FileFetcher::new(FIXTURES))));
+
+    let mut pages = Vec::new();
+    scraper
+        .fetch_all_properties_paginated(UTAH_URL, SearchStart::default(), |event| {
+            if let PageEvent::Fetched { properties, .. } = event {
+                pages.push(properties.len());
+            }
+            Ok(PageControl::Continue)
+        })
+        .expect("a missing page should end the run, not fail it");
+    assert_eq!(pages, vec![3, 2]);
+}
+
+#[test]
+fn recorded_cassette_lists_every_page_in_order() {
+    let dir = record_fixture_cassette();
+
+    let entries = crate::scraper::cassette::read_index(&dir).unwrap();
+    let urls: Vec<&str> = entries.iter().map(|e| e.url.as_str()).collect();
+    assert_eq!(
+        urls,
+        vec![
+            UTAH_URL,
+            &format!("{UTAH_URL}/pg-2"),
+            &format!("{UTAH_URL}/pg-3")
+        ]
+    );
+    let statuses: Vec<u16> = entries.iter().map(|e| e.status).collect();
+    assert_eq!(statuses, vec![200, 200, 404]);
+    assert!(dir.join(entries[1].body_file.as_ref().unwrap()).exists());
+}
+
+#[test]
+fn two_runs_recorded_into_one_directory_stay_apart() {
+    let root = tmp_dir("record_dir");
+    let record = || {
+        let recorder = RecordingFetcher::new(Box::new(FileFetcher::new(FIXTURES)), &root).unwrap();
+        let dir = recorder.dir().to_path_buf();
+        RealtorScraper::with_fetcher(Box::new(recorder))
+            .fetch_all_properties_paginated(UTAH_URL, SearchStart::default(), |_| {
+                Ok(PageControl::Continue)
+            })
+            .unwrap();
+        dir
+    };
+
+    let first = record();
+    assert!(
+        ReplayFetcher::open(&root).is_ok(),
+        "a lone recording replays from the record directory"
+    );
+    let second = record();
+    assert_ne!(first, second);
+
+    for dir in [&first, &second] {
+        let entries = crate::scraper::cassette::read_index(dir).unwrap();
+        let seqs: Vec<usize> = entries.iter().map(|e| e.seq).collect();
+        assert_eq!(seqs, vec![1, 2, 3]);
+    }
+    assert!(matches!(
+        ReplayFetcher::open(&root),
+        Err(ScraperError::Config(_))
+    ));
+
+    let replay = ReplayFetcher::open(&second).unwrap();
+    for url in [
+        UTAH_URL.to_string(),
+        format!("{UTAH_URL}/pg-2"),
+        format!("{UTAH_URL}/pg-3"),
+    ] {
+        assert!(replay.fetch(&url).is_ok());
+    }
+    assert!(replay.fetch(UTAH_URL).is_err(), "only one run's pages");
+}
+
+#[test]
+fn failed_fetches_are_recorded_and_replayed_as_failures() {
+    let recorder = RecordingFetcher::new(
+        Box::new(NotFoundAsError(FileFetcher::new(FIXTURES))),
+        tmp_dir("cassette"),
+    )
+    .unwrap();
+    let dir = recorder.dir().to_path_buf();
+    RealtorScraper::with_fetcher(Box::new(recorder))
+        .fetch_all_properties_paginated(UTAH_URL, SearchStart::default(), |_| {
+            Ok(PageControl::Continue)
+        })
+        .unwrap();
+
+    let entries = crate::scraper::cassette::read_index(&dir).unwrap();
+    assert_eq!(entries.len(), 3);
+    assert_eq!(
+        entries[2].error,
+        Some(RecordedError::NotFound(format!("{UTAH_URL}/pg-3")))
+    );
+    assert_eq!(entries[2].body_file, None);
+
+    let replay = ReplayFetcher::open(&dir).unwrap();
+    assert!(replay.fetch(UTAH_URL).is_ok());
+    assert!(replay.fetch(&format!("{UTAH_URL}/pg-2")).is_ok());
+    assert!(matches!(
+        replay.fetch(&format!("{UTAH_URL}/pg-3")),
+        Err(ScraperError::NotFound(_))
+    ));
+}
+
+#[test]
+fn replayed_cassettes_drive_change_tracking_end_to_end() {
+    let db = init_test_db();
+    let dir = record_fixture_cassette();
+
+    // First sweep: every listing is new.
+    let scraper = RealtorScraper::with_fetcher(Box::new(ReplayFetcher::open(&dir).unwrap()));
+    scraper
+        .fetch_all_properties_paginated(UTAH_URL, SearchStart::default(), |event| match event {
+            PageEvent::Fetched { properties, .. } => save_scraped_properties(&db, &properties)
+                .map(|_| PageControl::Continue)
+                .map_err(|e| crate::scraper::ScraperError::Network(e.to_string())),
+            PageEvent::Failed { .. } => Ok(PageControl::Continue),
+        })
+        .unwrap();
+
+    // Second sweep: same cassette with one price dropped.
+    let page_one = dir.join("0001.html");
+    let html = std::fs::read_to_string(&page_one).unwrap();
+    std::fs::write(&page_one, html.replace("450000", "435000")).unwrap();
+
+    let scraper = RealtorScraper::with_fetcher(Box::new(ReplayFetcher::open(&dir).unwrap()));
+    scraper
+        .fetch_all_properties_paginated(UTAH_URL, SearchStart::default(), |event| match event {
+            PageEvent::Fetched { properties, .. } => save_scraped_properties(&db, &properties)
+                .map(|_| PageControl::Continue)
+                .map_err(|e| crate::scraper::ScraperError::Network(e.to_string())),
+            PageEvent::Failed { .. } => Ok(PageControl::Continue),
+        })
+        .unwrap();
+
+    db.with_conn(|conn| {
+        let properties: i64 =
+            conn.query_row("SELECT COUNT(*) FROM properties", [], |r| r.get(0))?;
+        assert_eq!(properties, 5);
+
+        let (prev, curr): (Option<String>, String) = conn.query_row(
+            r#"
+            SELECT h.previous_value, h.current_value
+            FROM property_history h
+            JOIN properties p ON p.id = h.property_id
+            WHERE p.address_line = '123 N Main St'
+              AND h.field_name = 'list_price'
+              AND h.previous_value IS NOT NULL
+            "#,
+            [],
+            |r| Ok((r.get(0)?, r.get(1)?)),
+        )?;
+        assert_eq!(prev.as_deref(), Some("450000"));
+        assert_eq!(curr, "435000");
+        Ok(())
+    })
+    .unwrap();
+}
+
+#[test]
+fn resumed_run_continues_after_last_successful_page() {
+    let db = init_test_db();
+
+    // A run that saved page 1 and then died on page 2.
+    let run_id = db
+        .with_conn(|conn| {
+            let run_id = scrapes::start_scrape_run(conn, &utah_run(), 1_700_000_000)?;
+            scrapes::record_scrape_page(conn, run_id, 1, UTAH_URL, true, 3, 0)?;
+            scrapes::record_scrape_page(conn, run_id, 2, &format!("{UTAH_URL}/pg-2"), false, 0, 0)?;
+            scrapes::end_scrape_run(
+                conn,
+                run_id,
+                1_700_000_100,
+                1,
+                3,
+                false,
+                Some("boom".into()),
+            )?;
+            Ok(run_id)
+        })
+        .unwrap();
+
+    let start_page = db
+        .with_conn(|conn| scrapes::last_successful_page(conn, run_id))
+        .unwrap()
+        .map(|p| p + 1);
+    assert_eq!(start_page, Some(2));
+
+    db.with_conn(|conn| scrapes::reopen_scrape_run(conn, run_id))
+        .unwrap();
+    let scraper = RealtorScraper::with_fetcher(Box::new(FileFetcher::new(FIXTURES)));
+    scraper.execute_run(&db, run_id, UTAH_URL, 2);
+
+    let run = db
+        .with_conn(|conn| scrapes::get_scrape_run(conn, run_id))
+        .unwrap()
+        .unwrap();
+    assert_eq!(run.success, Some(true));
+    assert_eq!(run.pages_fetched, Some(2));
+    assert_eq!(run.properties_seen, Some(5));
+
+    // Only page 2's listings were fetched on resume.
+    assert_eq!(query_i64(&db, "SELECT COUNT(*) FROM properties"), 2);
+}
+
+#[test]
+fn resumed_run_that_reaches_the_page_cap_counts_earlier_pages() {
+    let db = init_test_db();
+
+    // Page 1 held 3 of the 5 results; page 2's last 2 fill the two-page cap.
+    let run_id = db
+        .with_conn(|conn| {
+            let run_id = scrapes::start_scrape_run(conn, &utah_run(), 1_700_000_000)?;
+            scrapes::record_scrape_page(conn, run_id, 1, UTAH_URL, true, 3, 0)?;
+            scrapes::record_scrape_page(conn, run_id, 2, &format!("{UTAH_URL}/pg-2"), false, 0, 0)?;
+            Ok(run_id)
+        })
+        .unwrap();
+
+    let scraper = RealtorScraper::with_fetcher(Box::new(FileFetcher::new(FIXTURES))).with_config(
+        ScraperConfig {
+            page_cap: 2,
+            ..Default::default()
+        },
+    );
+    let outcome = scraper.execute_run(&db, run_id, UTAH_URL, 2);
+
+    assert_eq!(outcome, Some(SearchOutcome::Complete { total: Some(5) }));
+}
+
+#[test]
+fn zip_shards_come_from_census_tables_when_loaded() {
+    let db = init_test_db();
+
+    let shards = db
+        .with_conn(|conn| {
+            conn.execute_batch(
+                "INSERT INTO county (geoid, state_fips, county_fips, name) VALUES
+                    ('49035', '49', '035', 'Salt Lake County'),
+                    ('16001', '16', '001', 'Ada County');
+                 INSERT INTO zcta (zcta) VALUES ('84101'), ('84111'), ('83702');
+                 INSERT INTO zcta_county (zcta, county_geoid) VALUES
+                    ('84101', '49035'), ('84111', '49035'), ('83702', '16001');",
+            )?;
+            plan_shards(conn, "UT", ShardBy::Zip)
+        })
+        .unwrap();
+
+    let labels: Vec<_> = shards.iter().filter_map(|s| s.label.clone()).collect();
+    assert_eq!(labels, vec!["zip:84101", "zip:84111"]);
+    assert_eq!(
+        shards[0].search_url,
+        "https://www.realtor.com/realestateandhomes-search/84101"
+    );
+}
+
+#[test]
+fn county_and_zip_shards_come_from_the_census_files_in_geo_data_dir() {
+    let db = init_test_db();
+    // Listings seen before don't stand in for the reference data.
+    scrape_utah(&db, FIXTURES, 1_700_000_000);
+    for by in [ShardBy::County, ShardBy::Zip] {
+        let refused = db.with_conn(|conn| plan_shards(conn, "UT", by));
+        assert!(refused.is_err(), "{by:?} without reference data");
+    }
+
+    geos::load_reference_data(&db, GEO_FIXTURES).unwrap();
+    let urls = |by| {
+        db.with_conn(|conn| plan_shards(conn, "UT", by))
+            .unwrap()
+            .into_iter()
+            .map(|s| s.search_url)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        urls(ShardBy::County),
+        vec![
+            "https://www.realtor.com/realestateandhomes-search/Salt-Lake-County_UT",
+            "https://www.realtor.com/realestateandhomes-search/Utah-County_UT",
+        ]
+    );
+    assert_eq!(
+        urls(ShardBy::Zip),
+        vec![
+            "https://www.realtor.com/realestateandhomes-search/84101",
+            "https://www.realtor.com/realestateandhomes-search/84111",
+            "https://www.realtor.com/realestateandhomes-search/84604",
+        ]
+    );
+}
+
+#[test]
+fn geo_data_dir_missing_a_file_fails_to_load() {
+    let db = init_test_db();
+    let dir = tmp_dir("geo");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::copy(
+        format!("{GEO_FIXTURES}/counties.txt"),
+        dir.join("counties.txt"),
+    )
+    .unwrap();
+
+    let err = geos::load_reference_data(&db, dir.to_str().unwrap()).unwrap_err();
+    assert!(err.to_string().contains("zcta_county.txt"), "{err}");
+}
+
+#[test]
+fn capped_search_is_split_into_price_bands_until_each_fits() {
+    let db = init_test_db();
+
+    // With a one-page cap the 5-result Utah search can't be paged through,
+    // and neither can its $0-$1M band.
+    let scraper = RealtorScraper::with_fetcher(Box::new(FileFetcher::new(FIXTURES))).with_config(
+        ScraperConfig {
+            page_cap: 1,
+            ..Default::default()
+        },
+    );
+    scraper
+        .run_shards(&db, vec![ScrapeShard::state("UT", "Utah")], None)
+        .unwrap();
+
+    let mut runs = db
+        .with_conn(|conn| scrapes::get_recent_scrapes(conn))
+        .unwrap();
+    runs.sort_by_key(|r| r.id);
+    let shards: Vec<_> = runs.iter().map(|r| r.shard.as_deref()).collect();
+    assert_eq!(
+        shards,
+        vec![
+            None,
+            Some("price:0-1000000"),
+            Some("price:0-500000"),
+            Some("price:500001-1000000"),
+            Some("price:1000001-na"),
+        ]
+    );
+
+    let root = &runs[0];
+    assert_eq!(root.split_into, Some(2));
+    assert_eq!(root.expected_total, Some(5));
+    assert_eq!(root.coverage, Some(1.0));
+    assert_eq!(runs[1].parent_run_id, Some(root.id));
+    assert_eq!(runs[2].parent_run_id, Some(runs[1].id));
+    assert!(runs.iter().all(|r| r.success == Some(true)));
+    // Every band records the settings it ran with.
+    let config: ScraperConfig =
+        serde_json::from_str(runs[3].config.as_deref().expect("config saved")).unwrap();
+    assert_eq!(config.page_cap, 1);
+
+    assert_eq!(query_i64(&db, "SELECT COUNT(*) FROM properties"), 5);
+}
+
+#[test]
+fn a_run_that_cannot_be_recorded_stops_the_scrape() {
+    let db = init_test_db();
+    db.with_conn(|conn| {
+        conn.execute_batch(
+            "CREATE TRIGGER refuse_runs BEFORE INSERT ON scrape_runs
+             BEGIN SELECT RAISE(ABORT, 'database is full'); END;",
+        )?;
+        Ok(())
+    })
+    .unwrap();
+
+    let result = RealtorScraper::with_fetcher(Box::new(FileFetcher::new(FIXTURES))).run_shards(
+        &db,
+        vec![ScrapeShard::state("UT", "Utah")],
+        None,
+    );
+    assert!(result.is_err());
+
+    // Nothing was scraped under a made-up run id.
+    let (pages, saved): (i64, i64) = db
+        .with_conn(|conn| {
+            Ok(conn.query_row(
+                "SELECT (SELECT COUNT(*) FROM scrape_run_pages), (SELECT COUNT(*) FROM properties)",
+                [],
+                |r| Ok((r.get(0)?, r.get(1)?)),
+            )?)
+        })
+        .unwrap();
+    assert_eq!((pages, saved), (0, 0));
+}
+
+#[test]
+fn incremental_scrape_stops_once_it_reaches_known_listings() {
+    let db = init_test_db();
+
+    // Full sweep first so the Utah listings are known.
+    let scraper = RealtorScraper::with_fetcher(Box::new(FileFetcher::new(FIXTURES)));
+    scraper
+        .run_shards(&db, vec![ScrapeShard::state("UT", "Utah")], None)
+        .unwrap();
+
+    // Newest first: one new listing, then two known ones. Page 2 of the sorted
+    // search exists but should never be fetched.
+    let scraper = RealtorScraper::with_fetcher(Box::new(FileFetcher::new(FIXTURES)))
+        .with_mode(ScrapeMode::Incremental { stop_after: 2 });
+    scraper
+        .run_shards(&db, vec![ScrapeShard::state("UT", "Utah")], None)
+        .unwrap();
+
+    let runs = db
+        .with_conn(|conn| scrapes::get_recent_scrapes(conn))
+        .unwrap();
+    let incremental = runs.iter().max_by_key(|r| r.id).unwrap();
+    assert_eq!(incremental.mode.as_deref(), Some("incremental"));
+    assert_eq!(incremental.stop_after, Some(2));
+    assert_eq!(
+        incremental.search_url.as_deref(),
+        Some("https://www.realtor.com/realestateandhomes-search/Utah/sby-6")
+    );
+    assert_eq!(incremental.success, Some(true));
+    assert_eq!(incremental.pages_fetched, Some(1));
+
+    assert_eq!(query_i64(&db, "SELECT COUNT(*) FROM properties"), 6);
+}
+
+#[test]
+fn cancelled_job_stops_after_the_current_page() {
+    let db = init_test_db();
+    let registry = Box::leak(Box::new(JobRegistry::new(1)));
+    let job = registry.start("UT", 1_700_000_000).unwrap();
+    registry.cancel(registry.snapshot()[0].id);
+
+    let run_id = db
+        .with_conn(|conn| scrapes::start_scrape_run(conn, &utah_run(), 1_700_000_000))
+        .unwrap();
+    let scraper = RealtorScraper::with_fetcher(Box::new(FileFetcher::new(FIXTURES))).with_job(job);
+    scraper.execute_run(&db, run_id, UTAH_URL, 1);
+
+    let runs = db
+        .with_conn(|conn| scrapes::get_recent_scrapes(conn))
+        .unwrap();
+    assert_eq!(runs.len(), 1);
+    assert_eq!(runs[0].success, Some(false));
+    assert_eq!(runs[0].error_message.as_deref(), Some("Cancelled by admin"));
+    assert_eq!(runs[0].pages_fetched, Some(1));
+
+    // The job unregisters once the scraper holding it is gone.
+    drop(scraper);
+    assert!(registry.snapshot().is_empty());
+}
+
+#[test]
+fn captcha_pages_fail_as_blocked_and_count_towards_block_rate() {
+    let dir = edited_fixtures("blocked", &[PAGE_ONE], |_, html| html);
+    std::fs::write(
+        dir.join(PAGE_TWO),
+        r#"<html><body><h1>Access to this page has been denied</h1><div id="px-captcha"></div></body></html>"#,
+    )
+    .unwrap();
+
+    let db = init_test_db();
+    let run_id = db
+        .with_conn(|conn| scrapes::start_scrape_run(conn, &utah_run(), 1_700_000_000))
+        .unwrap();
+    let scraper = RealtorScraper::with_fetcher(Box::new(FileFetcher::new(&dir)));
+    assert_eq!(scraper.execute_run(&db, run_id, UTAH_URL, 1), None);
+
+    let run = db
+        .with_conn(|conn| scrapes::get_scrape_run(conn, run_id))
+        .unwrap()
+        .unwrap();
+    assert!(run
+        .error_message
+        .unwrap()
+        .starts_with("Blocked by site: bot wall"));
+    // Page 2 was blocked on all three attempts; page 1 came through.
+    assert_eq!(run.blocked_attempts, Some(3));
+    assert_eq!(run.block_rate, Some(0.75));
+}
+
+#[test]
+fn enrichment_stores_agent_description_and_price_history_from_detail_pages() {
+    let db = init_test_db();
+    RealtorScraper::with_fetcher(Box::new(FileFetcher::new(FIXTURES)))
+        .with_config(enriching())
+        .run_shards(&db, vec![ScrapeShard::state("UT", "Utah")], None)
+        .unwrap();
+
+    // Only two listings have detail fixtures; the rest are logged and skipped.
+    type DetailRow = (String, String, String, Option<i64>, Option<i64>);
+    let details: Vec<DetailRow> = db
+        .with_conn(|conn| {
+            let rows = conn
+                .prepare(
+                    "SELECT p.address_line, d.agent_name, d.brokerage, d.days_on_market, d.photo_count
+                     FROM property_details d JOIN properties p ON p.id = d.property_id
+                     ORDER BY p.address_line",
+                )?
+                .query_map([], |r| {
+                    Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?, r.get(4)?))
+                })?
+                .collect::<Result<_, _>>()?;
+            Ok(rows)
+        })
+        .unwrap();
+    assert_eq!(details.len(), 2);
+    assert_eq!(details[0].0, "123 N Main St");
+    assert_eq!(details[0].1, "Kerry Oman");
+    assert_eq!(
+        details[0].2,
+        "Summit Sotheby's International Realty - Draper"
+    );
+    assert!(details[0].3.is_some_and(|days| days > 0));
+    assert_eq!(details[0].4, Some(3));
+    assert_eq!(
+        details[1],
+        (
+            "77 E 400 S Apt 4".to_string(),
+            "Dana Whitfield".to_string(),
+            "Equity Real Estate".to_string(),
+            Some(12),
+            Some(24)
+        )
+    );
+
+    let events: Vec<(String, String, i64)> = db
+        .with_conn(|conn| {
+            let rows = conn
+                .prepare(
+                    "SELECT e.event_date, e.event_name, e.price FROM property_price_events e
+                     JOIN properties p ON p.id = e.property_id
+                     WHERE p.address_line = '123 N Main St' ORDER BY e.event_date",
+                )?
+                .query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)))?
+                .collect::<Result<_, _>>()?;
+            Ok(rows)
+        })
+        .unwrap();
+    assert_eq!(
+        events,
+        vec![
+            ("2019-06-14".to_string(), "Sold".to_string(), 312000),
+            ("2025-09-01".to_string(), "Listed".to_string(), 450000),
+        ]
+    );
+
+    // A second sweep changes nothing, so no detail pages are fetched again.
+    let fetched_before: String = db
+        .with_conn(|conn| {
+            Ok(
+                conn.query_row("SELECT MAX(fetched_at) FROM property_details", [], |r| {
+                    r.get(0)
+                })?,
+            )
+        })
+        .unwrap();
+    RealtorScraper::with_fetcher(Box::new(FileFetcher::new(FIXTURES)))
+        .with_config(enriching())
+        .run_shards(&db, vec![ScrapeShard::state("UT", "Utah")], None)
+        .unwrap();
+    let fetched_after: String = db
+        .with_conn(|conn| {
+            Ok(
+                conn.query_row("SELECT MAX(fetched_at) FROM property_details", [], |r| {
+                    r.get(0)
+                })?,
+            )
+        })
+        .unwrap();
+    assert_eq!(fetched_before, fetched_after);
+}
@@ -1,6 +1,20 @@
 use crate::db::connection::{init_db, Database};
+use crate::db::scrapes::{self, NewScrapeRun};
+use crate::scraper::fetcher::FileFetcher;
+use crate::scraper::scraper::SearchKind;
+use crate::scraper::shards::ScrapeShard;
+use crate::scraper::RealtorScraper;
+use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 
+pub const FIXTURES: &str = "src/tests/fixtures/realtor";
+pub const GEO_FIXTURES: &str = "src/tests/fixtures/geo";
+pub const UTAH_URL: &str = "https://www.realtor.com/realestateandhomes-search/Utah";
+
+/// The two pages of the Utah for-sale search in `FIXTURES`.
+pub const PAGE_ONE: &str = "realestateandhomes-search_Utah.html";
+pub const PAGE_TWO: &str = "realestateandhomes-search_Utah_pg-2.html";
+
 pub fn init_test_db() -> Database {
     let mut path = std::env::temp_dir();
     let nanos = SystemTime::now()
@@ -15,3 +29,74 @@ pub fn init_test_db() -> Database {
 
     db
 }
+
+pub fn tmp_dir(name: &str) -> PathBuf {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    std::env::temp_dir().join(format!("{}_{}", name, nanos))
+}
+
+pub fn utah_run() -> NewScrapeRun<'static> {
+    NewScrapeRun {
+        state_abbr: "UT",
+        shard: None,
+        search_url: UTAH_URL,
+        parent_run_id: None,
+        mode: "full",
+        stop_after: None,
+        search_kind: "for_sale",
+        config: None,
+    }
+}
+
+/// Copies `pages` from `FIXTURES` into a fresh directory, passing each
+/// page's name and HTML through `edit`.
+pub fn edited_fixtures(
+    name: &str,
+    pages: &[&str],
+    edit: impl Fn(&str, String) -> String,
+) -> PathBuf {
+    let dir = tmp_dir(name);
+    std::fs::create_dir_all(&dir).unwrap();
+    for page in pages {
+        let html = std::fs::read_to_string(format!("{FIXTURES}/{page}")).unwrap();
+        std::fs::write(dir.join(page), edit(page, html)).unwrap();
+    }
+    dir
+}
+
+/// The Utah search after 123 N Main St goes pending with a cut to $439,000
+/// and page two's listings drop off.
+pub fn main_st_pending_without_page_two(name: &str) -> PathBuf {
+    edited_fixtures(name, &[PAGE_ONE], |_, html| {
+        html.replacen(r#""list_price": 450000"#, r#""list_price": 439000"#, 1)
+            .replacen(r#""is_pending": null"#, r#""is_pending": true"#, 1)
+            .replace(r#""totalProperties": 5"#, r#""totalProperties": 3"#)
+    })
+}
+
+/// Sweeps the whole of Utah for `kind` over the pages in `dir`.
+pub fn sweep_utah(db: &Database, dir: impl AsRef<Path>, kind: SearchKind) {
+    RealtorScraper::with_fetcher(Box::new(FileFetcher::new(dir.as_ref())))
+        .with_kind(kind)
+        .run_sweep(db, vec![ScrapeShard::state("UT", "Utah")]);
+}
+
+/// Scrapes the Utah search in `dir` as a single run started at
+/// `started_at` and returns the run's id.
+pub fn scrape_utah(db: &Database, dir: impl AsRef<Path>, started_at: i64) -> i64 {
+    let run_id = db
+        .with_conn(|conn| scrapes::start_scrape_run(conn, &utah_run(), started_at))
+        .unwrap();
+    RealtorScraper::with_fetcher(Box::new(FileFetcher::new(dir.as_ref())))
+        .execute_run(db, run_id, UTAH_URL, 1);
+    run_id
+}
+
+/// Runs a query that returns a single number, such as a `COUNT(*)`.
+pub fn query_i64(db: &Database, sql: &str) -> i64 {
+    db.with_conn(|conn| Ok(conn.query_row(sql, [], |r| r.get(0))?))
+        .unwrap()
+}